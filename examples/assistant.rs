@@ -1,24 +1,20 @@
 use openai_api_rs::v1::api::Client;
-use openai_api_rs::v1::assistant::AssistantRequest;
+use openai_api_rs::v1::assistant::{AssistantRequest, AssistantTool};
 use openai_api_rs::v1::common::GPT4_1106_PREVIEW;
 use openai_api_rs::v1::message::{CreateMessageRequest, MessageRole};
 use openai_api_rs::v1::run::CreateRunRequest;
 use openai_api_rs::v1::thread::CreateThreadRequest;
-use std::collections::HashMap;
 use std::env;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let client = Client::new(env::var("OPENAI_API_KEY").unwrap().to_string());
 
-    let mut tools = HashMap::new();
-    tools.insert("type".to_string(), "code_interpreter".to_string());
-
     let req = AssistantRequest::new(GPT4_1106_PREVIEW.to_string());
     let req = req
         .clone()
         .description("this is a test assistant".to_string());
     let req = req.clone().instructions("You are a personal math tutor. When asked a question, write and run Python code to answer the question.".to_string());
-    let req = req.clone().tools(vec![tools]);
+    let req = req.clone().tools(vec![AssistantTool::CodeInterpreter]);
     println!("{:?}", req);
 
     let result = client.create_assistant(req)?;
@@ -37,19 +33,9 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("{:?}", message_result.id.clone());
 
     let run_req = CreateRunRequest::new(result.id);
-    let run_result = client.create_run(thread_result.id.clone(), run_req)?;
-
-    loop {
-        let run_result = client
-            .retrieve_run(thread_result.id.clone(), run_result.id.clone())
-            .unwrap();
-        if run_result.status == "completed" {
-            break;
-        } else {
-            println!("waiting...");
-            std::thread::sleep(std::time::Duration::from_secs(1));
-        }
-    }
+    let run_result =
+        client.create_run_and_poll(thread_result.id.clone(), run_req, |_| Ok(vec![]))?;
+    println!("{:?}", run_result.status);
 
     let list_message_result = client.list_messages(thread_result.id.clone()).unwrap();
     for data in list_message_result.data {
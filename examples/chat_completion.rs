@@ -8,11 +8,9 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     let req = ChatCompletionRequest::new(
         GPT4.to_string(),
-        vec![chat_completion::ChatCompletionMessage {
-            role: chat_completion::MessageRole::user,
-            content: chat_completion::Content::Text(String::from("What is bitcoin?")),
-            name: None,
-        }],
+        vec![chat_completion::ChatCompletionMessage::user(
+            "What is bitcoin?",
+        )],
     );
 
     let result = client.chat_completion(req)?;
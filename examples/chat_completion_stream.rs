@@ -0,0 +1,36 @@
+use openai_api_rs::v1::api::Client;
+use openai_api_rs::v1::chat_completion::{self, ChatCompletionRequest};
+use openai_api_rs::v1::common::GPT4;
+use std::env;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let client = Client::new(env::var("OPENAI_API_KEY").unwrap().to_string());
+
+    let req = ChatCompletionRequest::new(
+        GPT4.to_string(),
+        vec![chat_completion::ChatCompletionMessage {
+            role: chat_completion::MessageRole::user,
+            content: Some(chat_completion::Content::PlainText(String::from(
+                "What is bitcoin?",
+            ))),
+            name: None,
+            tool_calls: None,
+            tool_call_id: None,
+            function_call: None,
+        }],
+    );
+
+    for chunk in client.chat_completion_stream(req)? {
+        let chunk = chunk?;
+        for choice in chunk.choices {
+            if let Some(content) = choice.delta.content {
+                print!("{}", content);
+            }
+        }
+    }
+    println!();
+
+    Ok(())
+}
+
+// OPENAI_API_KEY=xxxx cargo run --package openai-api-rs --example chat_completion_stream
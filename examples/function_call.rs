@@ -2,7 +2,7 @@ use openai_api_rs::v1::api::Client;
 use openai_api_rs::v1::chat_completion::{self, ChatCompletionRequest};
 use openai_api_rs::v1::common::GPT3_5_TURBO_0613;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 use std::{env, vec};
 
 fn get_coin_price(coin: &str) -> f64 {
@@ -17,7 +17,7 @@ fn get_coin_price(coin: &str) -> f64 {
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let client = Client::new(env::var("OPENAI_API_KEY").unwrap().to_string());
 
-    let mut properties = HashMap::new();
+    let mut properties = BTreeMap::new();
     properties.insert(
         "coin".to_string(),
         Box::new(chat_completion::JSONSchemaDefine {
@@ -31,8 +31,13 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         GPT3_5_TURBO_0613.to_string(),
         vec![chat_completion::ChatCompletionMessage {
             role: chat_completion::MessageRole::user,
-            content: chat_completion::Content::Text(String::from("What is the price of Ethereum?")),
+            content: Some(chat_completion::Content::PlainText(String::from(
+                "What is the price of Ethereum?",
+            ))),
             name: None,
+            tool_calls: None,
+            tool_call_id: None,
+            function_call: None,
         }],
     )
     .tools(vec![chat_completion::Tool {
@@ -40,11 +45,12 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         function: chat_completion::Function {
             name: String::from("get_coin_price"),
             description: Some(String::from("Get the price of a cryptocurrency")),
-            parameters: chat_completion::FunctionParameters {
+            parameters: serde_json::to_value(chat_completion::FunctionParameters {
                 schema_type: chat_completion::JSONSchemaType::Object,
                 properties: Some(properties),
                 required: Some(vec![String::from("coin")]),
-            },
+            })?,
+            strict: None,
         },
     }])
     .tool_choice(chat_completion::ToolChoiceType::Auto);
@@ -76,8 +82,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             let tool_calls = result.choices[0].message.tool_calls.as_ref().unwrap();
             for tool_call in tool_calls {
                 let name = tool_call.function.name.clone().unwrap();
-                let arguments = tool_call.function.arguments.clone().unwrap();
-                let c: Currency = serde_json::from_str(&arguments)?;
+                let c: Currency = tool_call.function.parse_arguments()?;
                 let coin = c.coin;
                 if name == "get_coin_price" {
                     let price = get_coin_price(&coin);
@@ -88,6 +93,9 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         Some(chat_completion::FinishReason::content_filter) => {
             println!("ContentFilter");
         }
+        Some(chat_completion::FinishReason::function_call) => {
+            println!("FunctionCall");
+        }
         Some(chat_completion::FinishReason::null) => {
             println!("Null");
         }
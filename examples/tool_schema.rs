@@ -0,0 +1,50 @@
+use openai_api_rs::v1::api::Client;
+use openai_api_rs::v1::chat_completion::{self, ChatCompletionMessage, ChatCompletionRequest};
+use openai_api_rs::v1::common::GPT4_0613;
+use openai_api_rs::v1::tool_schema::{ToolDefinition, ToolSchema};
+use serde::Deserialize;
+use std::env;
+
+/// Get the price of a cryptocurrency
+#[derive(ToolSchema, Deserialize)]
+#[tool(name = "get_coin_price")]
+struct GetCoinPrice {
+    /// The cryptocurrency to get the price of
+    coin: String,
+    /// The currency to quote the price in, USD when left out
+    currency: Option<Currency>,
+}
+
+#[derive(ToolSchema, Deserialize, Debug)]
+#[allow(non_camel_case_types)]
+enum Currency {
+    usd,
+    eur,
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let client = Client::new(env::var("OPENAI_API_KEY").unwrap().to_string());
+
+    let req = ChatCompletionRequest::new(
+        GPT4_0613.to_string(),
+        vec![ChatCompletionMessage::user(
+            "What is the price of Ethereum in euros?",
+        )],
+    )
+    .tools(vec![GetCoinPrice::tool()])
+    .tool_choice(chat_completion::ToolChoiceType::Auto);
+
+    let result = client.chat_completion(req)?;
+
+    if let Some(tool_calls) = &result.choices[0].message.tool_calls {
+        for tool_call in tool_calls {
+            if tool_call.function.name.as_deref() == Some(GetCoinPrice::name()) {
+                let args: GetCoinPrice = tool_call.function.parse_arguments()?;
+                println!("{} price in {:?}", args.coin, args.currency);
+            }
+        }
+    }
+    Ok(())
+}
+
+// OPENAI_API_KEY=xxxx cargo run --package openai-api-rs --example tool_schema --features derive
@@ -8,26 +8,10 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     let req = ChatCompletionRequest::new(
         GPT4_VISION_PREVIEW.to_string(),
-        vec![chat_completion::ChatCompletionMessage {
-            role: chat_completion::MessageRole::user,
-            content: chat_completion::Content::ImageUrl(vec![
-                chat_completion::ImageUrl {
-                    r#type: chat_completion::ContentType::text,
-                    text: Some(String::from("What’s in this image?")),
-                    image_url: None,
-                },
-                chat_completion::ImageUrl {
-                    r#type: chat_completion::ContentType::image_url,
-                    text: None,
-                    image_url: Some(chat_completion::ImageUrlType {
-                        url: String::from(
-                            "https://upload.wikimedia.org/wikipedia/commons/5/50/Bitcoin.png",
-                        ),
-                    }),
-                },
-            ]),
-            name: None,
-        }],
+        vec![chat_completion::ChatCompletionMessage::user_with_image(
+            "What’s in this image?",
+            "https://upload.wikimedia.org/wikipedia/commons/5/50/Bitcoin.png",
+        )],
     );
 
     let result = client.chat_completion(req)?;
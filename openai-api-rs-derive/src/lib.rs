@@ -0,0 +1,384 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::ext::IdentExt;
+use syn::punctuated::Punctuated;
+use syn::{
+    parse_macro_input, Attribute, Data, DeriveInput, Expr, Fields, Lit, LitStr, Meta, Token,
+};
+
+/// Derives `ToolSchema` for a struct with named fields or an enum of unit variants.
+///
+/// Structs also get `ToolDefinition`, so they can be offered as a tool: the name
+/// is the struct name in snake_case unless set with `#[tool(name = "...")]`, and
+/// doc comments on the struct and its fields become the descriptions.
+///
+/// Field and variant names follow `#[serde(rename = "...")]` and
+/// `#[serde(rename_all = "...")]`, so the schema asks for the names the type
+/// deserializes from.
+#[proc_macro_derive(ToolSchema, attributes(tool))]
+pub fn derive_tool_schema(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let krate = quote!(::openai_api_rs::v1::tool_schema);
+    let rename_all = serde_rename(&input.attrs, "rename_all")?;
+
+    match &input.data {
+        Data::Struct(data) => {
+            let fields = match &data.fields {
+                Fields::Named(fields) => &fields.named,
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        ident,
+                        "ToolSchema needs a struct with named fields",
+                    ))
+                }
+            };
+            let mut properties = Vec::new();
+            for field in fields {
+                let name = match serde_rename(&field.attrs, "rename")? {
+                    Some(name) => name.value(),
+                    None => {
+                        let ident = field.ident.as_ref().unwrap().unraw().to_string();
+                        rename_field(&ident, rename_all.as_ref())?
+                    }
+                };
+                let ty = &field.ty;
+                let description = option_tokens(doc_comment(&field.attrs));
+                properties.push(quote! {
+                    #krate::Property {
+                        name: #name,
+                        description: #description,
+                        schema: <#ty as #krate::ToolSchema>::schema(),
+                        required: !<#ty as #krate::ToolSchema>::optional(),
+                    }
+                });
+            }
+            let name = match tool_name(&input.attrs)? {
+                Some(name) => name,
+                None => snake_case(&ident.to_string()),
+            };
+            let description = option_tokens(doc_comment(&input.attrs));
+            Ok(quote! {
+                impl #impl_generics #krate::ToolSchema for #ident #ty_generics #where_clause {
+                    fn schema() -> #krate::Value {
+                        #krate::object_schema(::std::vec![#(#properties),*])
+                    }
+                }
+
+                impl #impl_generics #krate::ToolDefinition for #ident #ty_generics #where_clause {
+                    fn name() -> &'static str {
+                        #name
+                    }
+
+                    fn description() -> ::std::option::Option<&'static str> {
+                        #description
+                    }
+                }
+            })
+        }
+        Data::Enum(data) => {
+            let mut variants = Vec::new();
+            for variant in &data.variants {
+                if !matches!(variant.fields, Fields::Unit) {
+                    return Err(syn::Error::new_spanned(
+                        variant,
+                        "ToolSchema only supports enums of unit variants",
+                    ));
+                }
+                variants.push(match serde_rename(&variant.attrs, "rename")? {
+                    Some(name) => name.value(),
+                    None => {
+                        rename_variant(&variant.ident.unraw().to_string(), rename_all.as_ref())?
+                    }
+                });
+            }
+            Ok(quote! {
+                impl #impl_generics #krate::ToolSchema for #ident #ty_generics #where_clause {
+                    fn schema() -> #krate::Value {
+                        #krate::enum_schema(&[#(#variants),*])
+                    }
+                }
+            })
+        }
+        Data::Union(_) => Err(syn::Error::new_spanned(
+            ident,
+            "ToolSchema cannot be derived for unions",
+        )),
+    }
+}
+
+fn doc_comment(attrs: &[Attribute]) -> Option<String> {
+    let lines: Vec<String> = attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("doc"))
+        .filter_map(|attr| match &attr.meta {
+            Meta::NameValue(meta) => match &meta.value {
+                Expr::Lit(expr) => match &expr.lit {
+                    Lit::Str(s) => Some(s.value().trim().to_owned()),
+                    _ => None,
+                },
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect();
+    let doc = lines.join(" ").trim().to_owned();
+    if doc.is_empty() {
+        None
+    } else {
+        Some(doc)
+    }
+}
+
+fn tool_name(attrs: &[Attribute]) -> syn::Result<Option<String>> {
+    let mut name = None;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("tool")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("name") {
+                let value: LitStr = meta.value()?.parse()?;
+                name = Some(value.value());
+                Ok(())
+            } else {
+                Err(meta.error("unsupported tool attribute"))
+            }
+        })?;
+    }
+    Ok(name)
+}
+
+// The value of `#[serde(<key> = "...")]`, or of its `deserialize` form
+// `#[serde(<key>(deserialize = "..."))]`: the model's output is what gets
+// deserialized.
+fn serde_rename(attrs: &[Attribute], key: &str) -> syn::Result<Option<LitStr>> {
+    let mut value = None;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("serde")) {
+        let metas = attr.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)?;
+        for meta in metas.iter().filter(|meta| meta.path().is_ident(key)) {
+            match meta {
+                Meta::NameValue(meta) => value = Some(lit_str(&meta.value)?),
+                Meta::List(list) => {
+                    let nested =
+                        list.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)?;
+                    for meta in nested {
+                        if let Meta::NameValue(meta) = meta {
+                            if meta.path.is_ident("deserialize") {
+                                value = Some(lit_str(&meta.value)?);
+                            }
+                        }
+                    }
+                }
+                Meta::Path(path) => {
+                    return Err(syn::Error::new_spanned(path, "expected a string value"))
+                }
+            }
+        }
+    }
+    Ok(value)
+}
+
+fn lit_str(expr: &Expr) -> syn::Result<LitStr> {
+    match expr {
+        Expr::Lit(expr) => match &expr.lit {
+            Lit::Str(s) => Ok(s.clone()),
+            lit => Err(syn::Error::new_spanned(lit, "expected a string")),
+        },
+        expr => Err(syn::Error::new_spanned(expr, "expected a string")),
+    }
+}
+
+// Applies a serde `rename_all` rule to a snake_case field name, as serde does.
+fn rename_field(name: &str, rule: Option<&LitStr>) -> syn::Result<String> {
+    let rule = match rule {
+        Some(rule) => rule,
+        None => return Ok(name.to_owned()),
+    };
+    let pascal = || {
+        name.split('_')
+            .map(|word| {
+                let mut chars = word.chars();
+                match chars.next() {
+                    Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+                    None => String::new(),
+                }
+            })
+            .collect::<String>()
+    };
+    Ok(match rule.value().as_str() {
+        "lowercase" | "snake_case" => name.to_owned(),
+        "UPPERCASE" | "SCREAMING_SNAKE_CASE" => name.to_ascii_uppercase(),
+        "PascalCase" => pascal(),
+        "camelCase" => lowercase_first(&pascal()),
+        "kebab-case" => name.replace('_', "-"),
+        "SCREAMING-KEBAB-CASE" => name.to_ascii_uppercase().replace('_', "-"),
+        _ => return Err(unknown_rule(rule)),
+    })
+}
+
+// Applies a serde `rename_all` rule to a PascalCase variant name, as serde
+// does: every capital letter starts a word, so `HTTPRequest` in snake_case is
+// `h_t_t_p_request`.
+fn rename_variant(name: &str, rule: Option<&LitStr>) -> syn::Result<String> {
+    let rule = match rule {
+        Some(rule) => rule,
+        None => return Ok(name.to_owned()),
+    };
+    let snake = || {
+        let mut out = String::new();
+        for (i, c) in name.char_indices() {
+            if c.is_uppercase() && i > 0 {
+                out.push('_');
+            }
+            out.push(c.to_ascii_lowercase());
+        }
+        out
+    };
+    Ok(match rule.value().as_str() {
+        "lowercase" => name.to_ascii_lowercase(),
+        "UPPERCASE" => name.to_ascii_uppercase(),
+        "PascalCase" => name.to_owned(),
+        "camelCase" => lowercase_first(name),
+        "snake_case" => snake(),
+        "SCREAMING_SNAKE_CASE" => snake().to_ascii_uppercase(),
+        "kebab-case" => snake().replace('_', "-"),
+        "SCREAMING-KEBAB-CASE" => snake().to_ascii_uppercase().replace('_', "-"),
+        _ => return Err(unknown_rule(rule)),
+    })
+}
+
+fn lowercase_first(name: &str) -> String {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => first.to_ascii_lowercase().to_string() + chars.as_str(),
+        None => String::new(),
+    }
+}
+
+fn unknown_rule(rule: &LitStr) -> syn::Error {
+    syn::Error::new_spanned(rule, format!("unknown rename rule `{}`", rule.value()))
+}
+
+fn option_tokens(value: Option<String>) -> TokenStream2 {
+    match value {
+        Some(value) => quote!(::std::option::Option::Some(#value)),
+        None => quote!(::std::option::Option::None),
+    }
+}
+
+// The default tool name. A run of capitals is one word, so `HTTPRequest`
+// becomes `http_request`.
+fn snake_case(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut out = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            let previous = chars[i - 1];
+            let next_is_lower = chars.get(i + 1).is_some_and(|next| next.is_lowercase());
+            if previous.is_lowercase()
+                || previous.is_ascii_digit()
+                || (previous.is_uppercase() && next_is_lower)
+            {
+                out.push('_');
+            }
+        }
+        out.extend(c.to_lowercase());
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syn::parse_quote;
+
+    fn expanded(input: DeriveInput) -> String {
+        expand(&input).unwrap().to_string()
+    }
+
+    #[test]
+    fn snake_case_keeps_acronyms_together() {
+        assert_eq!(snake_case("GetWeather"), "get_weather");
+        assert_eq!(snake_case("HTTPRequest"), "http_request");
+        assert_eq!(snake_case("ParseURL"), "parse_url");
+        assert_eq!(snake_case("Get2FACode"), "get2_fa_code");
+        assert_eq!(snake_case("lookup"), "lookup");
+    }
+
+    #[test]
+    fn variants_follow_rename_all() {
+        let output = expanded(parse_quote! {
+            #[serde(rename_all = "lowercase")]
+            enum Unit {
+                Celsius,
+                #[serde(rename = "F")]
+                Fahrenheit,
+            }
+        });
+        assert!(output.contains("\"celsius\""), "{}", output);
+        assert!(output.contains("\"F\""), "{}", output);
+        assert!(!output.contains("\"Celsius\""), "{}", output);
+    }
+
+    #[test]
+    fn variant_rules_match_serde() {
+        let rule: LitStr = parse_quote!("snake_case");
+        assert_eq!(
+            rename_variant("HTTPRequest", Some(&rule)).unwrap(),
+            "h_t_t_p_request"
+        );
+        let rule: LitStr = parse_quote!("SCREAMING-KEBAB-CASE");
+        assert_eq!(rename_variant("NewYork", Some(&rule)).unwrap(), "NEW-YORK");
+        let rule: LitStr = parse_quote!("camelCase");
+        assert_eq!(rename_variant("NewYork", Some(&rule)).unwrap(), "newYork");
+    }
+
+    #[test]
+    fn fields_follow_rename_all() {
+        let output = expanded(parse_quote! {
+            #[serde(rename_all = "camelCase")]
+            struct GetWeather {
+                city_name: String,
+                #[serde(rename(serialize = "u", deserialize = "unit"))]
+                temperature_unit: String,
+                #[serde(default, rename = "when")]
+                r#date: String,
+                r#type: String,
+            }
+        });
+        assert!(output.contains("\"cityName\""), "{}", output);
+        assert!(output.contains("\"unit\""), "{}", output);
+        assert!(output.contains("\"when\""), "{}", output);
+        assert!(output.contains("\"type\""), "{}", output);
+        assert!(output.contains("\"get_weather\""), "{}", output);
+    }
+
+    #[test]
+    fn field_rules_match_serde() {
+        let rule: LitStr = parse_quote!("PascalCase");
+        assert_eq!(rename_field("city_name", Some(&rule)).unwrap(), "CityName");
+        let rule: LitStr = parse_quote!("SCREAMING_SNAKE_CASE");
+        assert_eq!(rename_field("city_name", Some(&rule)).unwrap(), "CITY_NAME");
+        let rule: LitStr = parse_quote!("kebab-case");
+        assert_eq!(rename_field("city_name", Some(&rule)).unwrap(), "city-name");
+    }
+
+    #[test]
+    fn unknown_rules_are_rejected() {
+        let input: DeriveInput = parse_quote! {
+            #[serde(rename_all = "Title Case")]
+            enum Unit {
+                Celsius,
+            }
+        };
+        let error = expand(&input).unwrap_err().to_string();
+        assert!(error.contains("unknown rename rule"), "{}", error);
+    }
+}
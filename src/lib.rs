@@ -1 +1,4 @@
 pub mod v1;
+
+#[cfg(all(feature = "vllm", target_arch = "wasm32"))]
+compile_error!("the `vllm` feature embeds a Python interpreter and is not available on wasm32");
@@ -0,0 +1,390 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[cfg(not(target_arch = "wasm32"))]
+use crate::v1::api::Client;
+use crate::v1::chat_completion::{
+    ChatCompletionChoice, ChatCompletionMessage, ChatCompletionMessageForResponse,
+    ChatCompletionRequest, ChatCompletionResponse, Content, FinishReason, MessageRole,
+    StructuredContent, ToolCall, ToolCallFunction, ToolChoiceType,
+};
+use crate::v1::common::{PromptTokensDetails, Usage};
+use crate::v1::error::APIError;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::v1::middleware::Method;
+
+pub const ANTHROPIC_API_URL: &str = "https://api.anthropic.com/v1";
+pub const ANTHROPIC_VERSION: &str = "2023-06-01";
+/// Used when the request sets no token limit, which Anthropic requires.
+pub const DEFAULT_MAX_TOKENS: i64 = 4096;
+
+/// A request to the Anthropic Messages API (`POST /v1/messages`).
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct MessagesRequest {
+    pub model: String,
+    pub max_tokens: i64,
+    pub messages: Vec<Message>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub system: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop_sequences: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stream: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tools: Option<Vec<Tool>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_choice: Option<ToolChoice>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<Metadata>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    User,
+    Assistant,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Message {
+    pub role: Role,
+    pub content: Vec<ContentBlock>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ContentBlock {
+    Text {
+        text: String,
+    },
+    Image {
+        source: ImageSource,
+    },
+    ToolUse {
+        id: String,
+        name: String,
+        input: Value,
+    },
+    ToolResult {
+        tool_use_id: String,
+        content: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        is_error: Option<bool>,
+    },
+    Thinking {
+        thinking: String,
+        #[serde(default)]
+        signature: String,
+    },
+    /// A block type this crate does not model, such as `redacted_thinking`,
+    /// so that responses containing one still parse. Dropped on conversion.
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ImageSource {
+    Base64 { media_type: String, data: String },
+    Url { url: String },
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Tool {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub input_schema: Value,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ToolChoice {
+    Auto,
+    Any,
+    None,
+    Tool { name: String },
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct Metadata {
+    pub user_id: String,
+}
+
+/// A response from the Anthropic Messages API.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct MessagesResponse {
+    pub id: String,
+    pub model: String,
+    pub role: Role,
+    pub content: Vec<ContentBlock>,
+    pub stop_reason: Option<String>,
+    #[serde(default)]
+    pub stop_sequence: Option<String>,
+    pub usage: AnthropicUsage,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Default)]
+pub struct AnthropicUsage {
+    pub input_tokens: i32,
+    pub output_tokens: i32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_creation_input_tokens: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_read_input_tokens: Option<i32>,
+}
+
+/// Converts a chat request to the Messages format.
+///
+/// System and developer messages become the `system` prompt, tool results
+/// become `tool_result` blocks in a user turn, and consecutive turns of the
+/// same role are merged, as Anthropic expects alternating turns. Fails on
+/// content Anthropic cannot take, such as input audio, and on temperatures
+/// above 1, Anthropic's maximum.
+impl TryFrom<&ChatCompletionRequest> for MessagesRequest {
+    type Error = APIError;
+
+    fn try_from(req: &ChatCompletionRequest) -> Result<Self, APIError> {
+        if let Some(temperature) = req.temperature.filter(|t| *t > 1.0) {
+            return Err(APIError::InvalidRequest(format!(
+                "temperature {} is above 1, the maximum Anthropic accepts",
+                temperature
+            )));
+        }
+        let mut system = Vec::new();
+        let mut messages: Vec<Message> = Vec::new();
+        for message in &req.messages {
+            let (role, content) = match message.role {
+                MessageRole::system | MessageRole::developer => {
+                    system.push(text_of(message)?);
+                    continue;
+                }
+                MessageRole::user => (Role::User, content_blocks(message.content.as_ref())?),
+                MessageRole::assistant => (Role::Assistant, assistant_blocks(message)?),
+                MessageRole::tool => (
+                    Role::User,
+                    vec![ContentBlock::ToolResult {
+                        tool_use_id: message.tool_call_id.clone().ok_or_else(|| {
+                            APIError::InvalidRequest("tool message without tool_call_id".into())
+                        })?,
+                        content: text_of(message)?,
+                        is_error: None,
+                    }],
+                ),
+                MessageRole::function => {
+                    return Err(APIError::InvalidRequest(
+                        "function messages are not supported by Anthropic; use tool messages"
+                            .into(),
+                    ))
+                }
+            };
+            match messages.last_mut() {
+                Some(last) if last.role == role => last.content.extend(content),
+                _ => messages.push(Message { role, content }),
+            }
+        }
+        Ok(Self {
+            model: req.model.clone(),
+            max_tokens: req.completion_token_limit().unwrap_or(DEFAULT_MAX_TOKENS),
+            messages,
+            system: (!system.is_empty()).then(|| system.join("\n\n")),
+            temperature: req.temperature,
+            top_p: req.top_p,
+            stop_sequences: req.stop.clone(),
+            stream: req.stream,
+            tools: req.tools.as_ref().map(|tools| {
+                tools
+                    .iter()
+                    .map(|tool| Tool {
+                        name: tool.function.name.clone(),
+                        description: tool.function.description.clone(),
+                        input_schema: tool.function.parameters.clone(),
+                    })
+                    .collect()
+            }),
+            tool_choice: req.tool_choice.as_ref().map(|choice| match choice {
+                ToolChoiceType::None => ToolChoice::None,
+                ToolChoiceType::Auto => ToolChoice::Auto,
+                ToolChoiceType::Any => ToolChoice::Any,
+                ToolChoiceType::ToolChoice { tool } => ToolChoice::Tool {
+                    name: tool.function.name.clone(),
+                },
+            }),
+            metadata: req.user.clone().map(|user_id| Metadata { user_id }),
+        })
+    }
+}
+
+/// Converts a Messages response to a chat completion with a single choice.
+/// Thinking blocks become `reasoning_content` and `tool_use` blocks become
+/// tool calls.
+impl From<MessagesResponse> for ChatCompletionResponse {
+    fn from(res: MessagesResponse) -> Self {
+        let mut text = String::new();
+        let mut thinking = String::new();
+        let mut tool_calls = Vec::new();
+        for block in res.content {
+            match block {
+                ContentBlock::Text { text: part } => text.push_str(&part),
+                ContentBlock::Thinking { thinking: part, .. } => thinking.push_str(&part),
+                ContentBlock::ToolUse { id, name, input } => tool_calls.push(ToolCall {
+                    id,
+                    r#type: "function".to_owned(),
+                    function: ToolCallFunction {
+                        name: Some(name),
+                        arguments: Some(input.to_string()),
+                    },
+                }),
+                ContentBlock::Image { .. }
+                | ContentBlock::ToolResult { .. }
+                | ContentBlock::Unknown => {}
+            }
+        }
+        let finish_reason = res.stop_reason.as_deref().map(|reason| match reason {
+            "max_tokens" => FinishReason::length,
+            "tool_use" => FinishReason::tool_calls,
+            "refusal" => FinishReason::content_filter,
+            _ => FinishReason::stop,
+        });
+        let mut message = ChatCompletionMessageForResponse::new(
+            MessageRole::assistant,
+            (!text.is_empty()).then_some(text),
+        );
+        message.reasoning_content = (!thinking.is_empty()).then_some(thinking);
+        message.tool_calls = (!tool_calls.is_empty()).then_some(tool_calls);
+        let tokens = res.usage;
+        let prompt_tokens = tokens.input_tokens
+            + tokens.cache_creation_input_tokens.unwrap_or(0)
+            + tokens.cache_read_input_tokens.unwrap_or(0);
+        let mut usage = Usage::new(prompt_tokens, tokens.output_tokens);
+        usage.prompt_tokens_details =
+            tokens
+                .cache_read_input_tokens
+                .map(|cached| PromptTokensDetails {
+                    cached_tokens: Some(cached),
+                    audio_tokens: None,
+                });
+        ChatCompletionResponse::new(
+            res.id,
+            res.model,
+            vec![ChatCompletionChoice::new(0, message, finish_reason)],
+            usage,
+        )
+    }
+}
+
+/// Sends a chat request to the Anthropic Messages API and converts the reply.
+///
+/// `client` should point at `ANTHROPIC_API_URL` with an Anthropic API key;
+/// its middleware, retries and timeout apply. Streaming is not supported.
+///
+/// ```no_run
+/// use openai_api_rs::v1::anthropic::{self, ANTHROPIC_API_URL};
+/// use openai_api_rs::v1::api::Client;
+/// use openai_api_rs::v1::chat_completion::{ChatCompletionMessage, ChatCompletionRequest};
+///
+/// let client = Client::new_with_endpoint(ANTHROPIC_API_URL.to_owned(), "sk-ant-...".to_owned());
+/// let req = ChatCompletionRequest::new(
+///     "claude-sonnet-4-5".to_owned(),
+///     vec![ChatCompletionMessage::user("Hello")],
+/// );
+/// let res = anthropic::chat_completion(&client, &req)?;
+/// # Ok::<(), openai_api_rs::v1::error::APIError>(())
+/// ```
+#[cfg(not(target_arch = "wasm32"))]
+pub fn chat_completion(
+    client: &Client,
+    req: &ChatCompletionRequest,
+) -> Result<ChatCompletionResponse, APIError> {
+    if req.stream == Some(true) {
+        return Err(APIError::InvalidRequest(
+            "streaming is not supported by the Anthropic adapter".into(),
+        ));
+    }
+    let body = MessagesRequest::try_from(req)?;
+    let mut request = client
+        .build_request(Method::Post, "/messages")
+        .with_header("x-api-key", &client.api_key)
+        .with_header("anthropic-version", ANTHROPIC_VERSION);
+    request.headers.remove("Authorization");
+    request.body = Some(serde_json::to_vec(&body).map_err(|e| APIError::Decode(e.to_string()))?);
+    req.request_options().apply(&mut request);
+    let res = client.execute(request)?;
+    let message: MessagesResponse = crate::v1::common::from_slice(&res.body)?;
+    let mut response = ChatCompletionResponse::from(message);
+    client.record_usage(&response.model, &response.usage);
+    response.headers = Some(res.headers);
+    Ok(response)
+}
+
+fn text_of(message: &ChatCompletionMessage) -> Result<String, APIError> {
+    Ok(match &message.content {
+        None => String::new(),
+        Some(Content::PlainText(text)) => text.clone(),
+        Some(Content::Structured(parts)) => parts
+            .iter()
+            .map(|part| match part {
+                StructuredContent::Text { text } => Ok(text.as_str()),
+                _ => Err(APIError::InvalidRequest(format!(
+                    "{:?} messages can only hold text for Anthropic",
+                    message.role
+                ))),
+            })
+            .collect::<Result<Vec<_>, _>>()?
+            .join("\n"),
+    })
+}
+
+fn content_blocks(content: Option<&Content>) -> Result<Vec<ContentBlock>, APIError> {
+    match content {
+        None => Ok(Vec::new()),
+        Some(Content::PlainText(text)) => Ok(vec![ContentBlock::Text { text: text.clone() }]),
+        Some(Content::Structured(parts)) => parts
+            .iter()
+            .map(|part| match part {
+                StructuredContent::Text { text } => Ok(ContentBlock::Text { text: text.clone() }),
+                StructuredContent::ImageUrl { image_url } => Ok(ContentBlock::Image {
+                    source: image_source(&image_url.url),
+                }),
+                StructuredContent::InputAudio { .. } => Err(APIError::InvalidRequest(
+                    "input audio is not supported by Anthropic".into(),
+                )),
+            })
+            .collect(),
+    }
+}
+
+fn assistant_blocks(message: &ChatCompletionMessage) -> Result<Vec<ContentBlock>, APIError> {
+    let mut blocks = content_blocks(message.content.as_ref())?;
+    // An empty text block is rejected, and tool calls often come with "".
+    blocks.retain(|block| !matches!(block, ContentBlock::Text { text } if text.is_empty()));
+    for call in message.tool_calls.iter().flatten() {
+        let input = call
+            .function
+            .parse_arguments()
+            .map_err(|e| APIError::InvalidRequest(format!("tool call {}: {}", call.id, e)))?;
+        blocks.push(ContentBlock::ToolUse {
+            id: call.id.clone(),
+            name: call.function.name.clone().unwrap_or_default(),
+            input,
+        });
+    }
+    Ok(blocks)
+}
+
+// `data:image/png;base64,...` URIs are sent inline, anything else as a URL.
+fn image_source(url: &str) -> ImageSource {
+    url.strip_prefix("data:")
+        .and_then(|rest| rest.split_once(";base64,"))
+        .map(|(media_type, data)| ImageSource::Base64 {
+            media_type: media_type.to_owned(),
+            data: data.to_owned(),
+        })
+        .unwrap_or_else(|| ImageSource::Url {
+            url: url.to_owned(),
+        })
+}
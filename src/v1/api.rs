@@ -1,15 +1,24 @@
-use crate::v1::assistant::{
-    AssistantFileObject, AssistantFileRequest, AssistantObject, AssistantRequest, DeletionStatus,
-    ListAssistant, ListAssistantFile,
-};
+use crate::impl_builder_methods;
+#[allow(deprecated)]
+use crate::v1::assistant::{AssistantFileObject, AssistantFileRequest, ListAssistantFile};
+use crate::v1::assistant::{AssistantObject, AssistantRequest, DeletionStatus, ListAssistant};
 use crate::v1::audio::{
-    AudioSpeechRequest, AudioSpeechResponse, AudioTranscriptionRequest, AudioTranscriptionResponse,
-    AudioTranslationRequest, AudioTranslationResponse,
+    AudioResponseFormat, AudioSpeechRequest, AudioSpeechResponse, AudioTranscriptionRequest,
+    AudioTranscriptionResponse, AudioTranslationRequest, AudioTranslationResponse,
+    SpeechResponseFormat, SpeechStream,
+};
+use crate::v1::azure::AzureConfig;
+use crate::v1::cache::{self, CacheStore};
+use crate::v1::chat_completion::{
+    ChatCompletionRequest, ChatCompletionResponse, ChatCompletionResults,
+    ChatCompletionStreamResponse, ToolCall,
 };
-use crate::v1::chat_completion::{ChatCompletionRequest, ChatCompletionResponse};
+use crate::v1::coalesce::Coalescer;
+use crate::v1::common::{self, ResponseHeaders, Usage};
 use crate::v1::completion::{CompletionRequest, CompletionResponse};
+use crate::v1::cost::CostTracker;
 use crate::v1::edit::{EditRequest, EditResponse};
-use crate::v1::embedding::{EmbeddingRequest, EmbeddingResponse};
+use crate::v1::embedding::{EmbeddingBatching, EmbeddingRequest, EmbeddingResponse};
 use crate::v1::error::APIError;
 use crate::v1::file::{
     FileDeleteRequest, FileDeleteResponse, FileListResponse, FileRetrieveContentRequest,
@@ -25,29 +34,102 @@ use crate::v1::image::{
     ImageEditRequest, ImageEditResponse, ImageGenerationRequest, ImageGenerationResponse,
     ImageVariationRequest, ImageVariationResponse,
 };
-use crate::v1::message::{
-    CreateMessageRequest, ListMessage, ListMessageFile, MessageFileObject, MessageObject,
-    ModifyMessageRequest,
+use crate::v1::message::{CreateMessageRequest, ListMessage, MessageObject, ModifyMessageRequest};
+#[allow(deprecated)]
+use crate::v1::message::{ListMessageFile, MessageFileObject};
+pub use crate::v1::middleware::RequestOptions;
+use crate::v1::middleware::{
+    new_idempotency_key, HttpRequest, HttpResponse, Method, Middleware, IDEMPOTENCY_KEY_HEADER,
+};
+use crate::v1::model::{ListModel, ModelObject};
+use crate::v1::moderation::{
+    CreateModerationRequest, CreateModerationResponse, ModerationGuard, ModerationVerdict,
 };
-use crate::v1::moderation::{CreateModerationRequest, CreateModerationResponse};
+use crate::v1::multipart::Form;
+use crate::v1::retry::{RetryAttempt, RetryPolicy};
 use crate::v1::run::{
-    CreateRunRequest, CreateThreadAndRunRequest, ListRun, ListRunStep, ModifyRunRequest, RunObject,
-    RunStepObject,
+    AssistantStreamEvent, CreateRunRequest, CreateThreadAndRunRequest, ListRun, ListRunStep,
+    ModifyRunRequest, RunEventStream, RunObject, RunStepObject, SubmitToolOutputsRequest,
+    ToolOutput,
 };
+use crate::v1::stream::{SseStream, StreamingResponse};
 use crate::v1::thread::{CreateThreadRequest, ModifyThreadRequest, ThreadObject};
+use crate::v1::throttle::Throttle;
+use crate::v1::vector_store::{
+    CreateVectorStoreFileBatchRequest, CreateVectorStoreFileRequest, CreateVectorStoreRequest,
+    ListVectorStore, ListVectorStoreFile, ModifyVectorStoreRequest, VectorStoreFileBatchObject,
+    VectorStoreFileObject, VectorStoreObject,
+};
 
-use minreq::Response;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::borrow::Cow;
+use std::collections::HashMap;
 use std::fs::{create_dir_all, File};
 use std::io::Write;
 use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 const API_URL_V1: &str = "https://api.openai.com/v1";
+const RUN_POLL_MIN_DELAY: Duration = Duration::from_millis(500);
+const RUN_POLL_MAX_DELAY: Duration = Duration::from_secs(5);
+
+/// Called with the response's model and token usage.
+pub type UsageHook = Arc<dyn Fn(&str, &Usage) + Send + Sync>;
 
+/// Cheap to clone and safe to share across threads: the throttle, cost tracker
+/// and middleware are reference counted, so clones share them. Each request opens its own
+/// connection, since the minreq transport has no connection pool, keep-alive
+/// reuse or HTTP/2.
+#[derive(Clone)]
 pub struct Client {
     pub api_endpoint: String,
     pub api_key: String,
     pub organization: Option<String>,
     pub proxy: Option<String>,
+    pub retry_policy: Option<RetryPolicy>,
+    pub throttle: Option<Arc<Throttle>>,
+    /// Accumulates the estimated cost of chat, completion and embedding responses.
+    pub cost_tracker: Option<Arc<CostTracker>>,
+    /// Called after every chat, completion and embedding response, e.g. to
+    /// feed metrics counters.
+    pub on_usage: Option<UsageHook>,
+    /// Answers repeated deterministic chat requests, with a temperature of 0
+    /// and a seed, from the cache instead of the API.
+    pub response_cache: Option<Arc<dyn CacheStore>>,
+    /// Shares one upstream call between concurrent identical requests.
+    pub coalescer: Option<Arc<Coalescer>>,
+    /// Limits and concurrency for splitting large embedding requests. The
+    /// API limits apply, one request at a time, when unset.
+    pub embedding_batching: Option<EmbeddingBatching>,
+    /// Screens user messages before chat requests are sent.
+    pub moderation_guard: Option<ModerationGuard>,
+    /// Sends `tools` as the legacy `functions` and `function_call` fields,
+    /// for servers that predate tools, and returns their function calls as
+    /// tool calls. Streamed replies are not converted.
+    pub legacy_functions: Option<bool>,
+    /// Sends a generated `Idempotency-Key` with every POST that has none, so
+    /// that compatible servers recognize retries. Such POSTs are also retried
+    /// after connection errors.
+    pub idempotency_keys: Option<bool>,
+    /// Default timeout in seconds, overridable per request.
+    pub timeout: Option<u64>,
+    /// Sent with every request, before any per-request headers.
+    pub extra_headers: Option<HashMap<String, String>>,
+    pub azure: Option<AzureConfig>,
+    pub middleware: Vec<Arc<dyn Middleware>>,
+    /// Rewrites `max_tokens`/`max_completion_tokens` on chat requests to suit the model.
+    pub translate_max_tokens: Option<bool>,
+    /// Sends `system` messages as `developer` messages to models that expect it.
+    pub translate_system_role: Option<bool>,
+    /// Fails with `APIError::Decode` when a response has fields this crate does
+    /// not model, to catch API drift in CI. Responses are parsed leniently by default.
+    pub strict_responses: Option<bool>,
+    /// Checks structured output replies against the requested schema and
+    /// returns `APIError::SchemaValidation` when they do not match.
+    #[cfg(feature = "schema-validation")]
+    pub validate_structured_output: Option<bool>,
 }
 
 impl Client {
@@ -62,133 +144,400 @@ impl Client {
             api_key,
             organization: None,
             proxy: None,
+            retry_policy: None,
+            throttle: None,
+            cost_tracker: None,
+            on_usage: None,
+            response_cache: None,
+            coalescer: None,
+            embedding_batching: None,
+            moderation_guard: None,
+            legacy_functions: None,
+            idempotency_keys: None,
+            timeout: None,
+            extra_headers: None,
+            azure: None,
+            middleware: Vec::new(),
+            translate_max_tokens: None,
+            translate_system_role: None,
+            strict_responses: None,
+            #[cfg(feature = "schema-validation")]
+            validate_structured_output: None,
         }
     }
 
+    pub fn new_azure(api_key: String, azure: AzureConfig) -> Self {
+        let mut client = Self::new_with_endpoint(String::new(), api_key);
+        client.azure = Some(azure);
+        client
+    }
+
     pub fn new_with_organization(api_key: String, organization: String) -> Self {
-        let endpoint = std::env::var("OPENAI_API_BASE").unwrap_or_else(|_| API_URL_V1.to_owned());
-        Self {
-            api_endpoint: endpoint,
-            api_key,
-            organization: organization.into(),
-            proxy: None,
-        }
+        let mut client = Self::new(api_key);
+        client.organization = organization.into();
+        client
     }
 
     pub fn new_with_proxy(api_key: String, proxy: String) -> Self {
-        let endpoint = std::env::var("OPENAI_API_BASE").unwrap_or_else(|_| API_URL_V1.to_owned());
-        Self {
-            api_endpoint: endpoint,
-            api_key,
-            organization: None,
-            proxy: Some(proxy),
-        }
+        let mut client = Self::new(api_key);
+        client.proxy = Some(proxy);
+        client
+    }
+
+    pub fn with_middleware<M: Middleware + 'static>(mut self, middleware: M) -> Self {
+        self.middleware.push(Arc::new(middleware));
+        self
+    }
+
+    pub fn on_usage<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(&str, &Usage) + Send + Sync + 'static,
+    {
+        self.on_usage = Some(Arc::new(hook));
+        self
     }
 
-    pub fn build_request(&self, request: minreq::Request, is_beta: bool) -> minreq::Request {
-        let mut request = request
-            .with_header("Content-Type", "application/json")
-            .with_header("Authorization", format!("Bearer {}", self.api_key));
+    pub fn build_request(&self, method: Method, path: &str) -> HttpRequest {
+        let mut request = HttpRequest::new(method, self.url(path))
+            .with_header("Content-Type", "application/json");
+        request = match self.azure {
+            Some(_) => request.with_header("api-key", &self.api_key),
+            None => request.with_header("Authorization", format!("Bearer {}", self.api_key)),
+        };
         if let Some(organization) = &self.organization {
             request = request.with_header("openai-organization", organization);
         }
-        if is_beta {
-            request = request.with_header("OpenAI-Beta", "assistants=v1");
+        if Self::is_beta(path) {
+            request = request.with_header("OpenAI-Beta", "assistants=v2");
         }
-        if let Some(proxy) = &self.proxy {
-            request = request.with_proxy(minreq::Proxy::new(proxy).unwrap());
+        if let Some(extra_headers) = &self.extra_headers {
+            request.headers.extend(extra_headers.clone());
         }
+        request.timeout = self.timeout;
         request
     }
 
+    /// The explicit `proxy`, or else the one configured through `HTTPS_PROXY`,
+    /// `HTTP_PROXY` or `ALL_PROXY` for the endpoint's scheme, honoring `NO_PROXY`.
+    pub fn proxy_url(&self) -> Option<String> {
+        if self.proxy.is_some() {
+            return self.proxy.clone();
+        }
+        let url = self.url("");
+        let (scheme, rest) = url.split_once("://")?;
+        let host = rest.split(['/', ':']).next().unwrap_or_default();
+        if Self::is_no_proxy(host) {
+            return None;
+        }
+        let names: &[&str] = if scheme.eq_ignore_ascii_case("https") {
+            &["HTTPS_PROXY", "https_proxy", "ALL_PROXY", "all_proxy"]
+        } else {
+            &["HTTP_PROXY", "http_proxy", "ALL_PROXY", "all_proxy"]
+        };
+        names
+            .iter()
+            .filter_map(|name| std::env::var(name).ok())
+            .find(|value| !value.trim().is_empty())
+    }
+
+    fn is_no_proxy(host: &str) -> bool {
+        let no_proxy = match std::env::var("NO_PROXY").or_else(|_| std::env::var("no_proxy")) {
+            Ok(no_proxy) => no_proxy,
+            Err(_) => return false,
+        };
+        no_proxy.split(',').map(str::trim).any(|entry| {
+            let entry = entry.trim_start_matches('.');
+            entry == "*"
+                || (!entry.is_empty() && (host == entry || host.ends_with(&format!(".{}", entry))))
+        })
+    }
+
+    // minreq only speaks HTTP CONNECT, so SOCKS URLs are rejected up front
+    // instead of being silently sent to the wrong kind of proxy.
+    fn parse_proxy(proxy: &str) -> Result<minreq::Proxy, APIError> {
+        let proxy = proxy.trim().trim_end_matches('/');
+        if proxy.to_ascii_lowercase().starts_with("socks") {
+            return Err(APIError::Connection(format!(
+                "unsupported proxy {}: only HTTP CONNECT proxies are supported",
+                proxy
+            )));
+        }
+        minreq::Proxy::new(proxy)
+            .map_err(|e| APIError::Connection(format!("invalid proxy {}: {}", proxy, e)))
+    }
+
     pub fn post<T: serde::ser::Serialize>(
         &self,
         path: &str,
         params: &T,
-    ) -> Result<Response, APIError> {
-        let url = format!(
+    ) -> Result<HttpResponse, APIError> {
+        self.post_with_options(path, params, &RequestOptions::default())
+    }
+
+    pub fn post_with_options<T: serde::ser::Serialize>(
+        &self,
+        path: &str,
+        params: &T,
+        options: &RequestOptions,
+    ) -> Result<HttpResponse, APIError> {
+        let mut request = self.build_request(Method::Post, path);
+        request.body = Some(serde_json::to_vec(params).unwrap());
+        options.apply(&mut request);
+        self.execute(request)
+    }
+
+    pub fn post_stream<T: serde::ser::Serialize>(
+        &self,
+        path: &str,
+        params: &T,
+        options: &RequestOptions,
+    ) -> Result<StreamingResponse, APIError> {
+        let mut request = self
+            .build_request(Method::Post, path)
+            .with_header("Accept", "text/event-stream");
+        request.body = Some(serde_json::to_vec(params).unwrap());
+        request.stream = true;
+        options.apply(&mut request);
+        self.execute_stream(request)
+    }
+
+    pub fn post_form(&self, path: &str, form: Form) -> Result<HttpResponse, APIError> {
+        let mut request = self
+            .build_request(Method::Post, path)
+            .with_header("Content-Type", form.content_type());
+        request.body = Some(form.into_body());
+        self.execute(request)
+    }
+
+    pub fn get(&self, path: &str) -> Result<HttpResponse, APIError> {
+        self.execute(self.build_request(Method::Get, path))
+    }
+
+    pub fn delete(&self, path: &str) -> Result<HttpResponse, APIError> {
+        self.execute(self.build_request(Method::Delete, path))
+    }
+
+    fn url(&self, path: &str) -> String {
+        if let Some(azure) = &self.azure {
+            return azure.url(path);
+        }
+        format!(
             "{api_endpoint}{path}",
             api_endpoint = self.api_endpoint,
             path = path
-        );
-        let request = self.build_request(minreq::post(url), Self::is_beta(path));
-        let res = request
-            .with_body(serde_json::to_string(params).unwrap())
-            .send();
-        match res {
-            Ok(res) => {
-                if (200..=299).contains(&res.status_code) {
-                    Ok(res)
-                } else {
-                    Err(APIError {
-                        message: format!("{}: {}", res.status_code, res.as_str().unwrap()),
-                    })
-                }
-            }
-            Err(e) => Err(self.new_error(e)),
+        )
+    }
+
+    /// Sends a prepared request through the middleware chain, retrying per `retry_policy`.
+    pub fn execute(&self, request: HttpRequest) -> Result<HttpResponse, APIError> {
+        match &self.coalescer {
+            Some(coalescer) => coalescer.run(&request, || self.send_buffered(&request)),
+            None => self.send_buffered(&request),
         }
     }
 
-    pub fn get(&self, path: &str) -> Result<Response, APIError> {
-        let url = format!(
-            "{api_endpoint}{path}",
-            api_endpoint = self.api_endpoint,
-            path = path
-        );
-        let request = self.build_request(minreq::get(url), Self::is_beta(path));
-        let res = request.send();
-        match res {
-            Ok(res) => {
-                if (200..=299).contains(&res.status_code) {
-                    Ok(res)
-                } else {
-                    Err(APIError {
-                        message: format!("{}: {}", res.status_code, res.as_str().unwrap()),
-                    })
+    fn send_buffered(&self, request: &HttpRequest) -> Result<HttpResponse, APIError> {
+        let mut request = Cow::Borrowed(request);
+        if self.needs_idempotency_key(&request) {
+            request
+                .to_mut()
+                .headers
+                .insert(IDEMPOTENCY_KEY_HEADER.to_owned(), new_idempotency_key());
+        }
+        self.with_retry(Self::is_retry_safe(&request), |_attempt| {
+            #[cfg(feature = "tracing")]
+            let (span, started) = (request_span(&request, _attempt), std::time::Instant::now());
+            #[cfg(feature = "tracing")]
+            let _entered = span.enter();
+            let mut request = HttpRequest::clone(&request);
+            let mut response = match self.run_on_request(&mut request)? {
+                Some(response) => response,
+                None => {
+                    let mut res = self
+                        .transport_request(&request)?
+                        .send()
+                        .map_err(|e| self.new_error(e))?;
+                    HttpResponse {
+                        status_code: res.status_code,
+                        headers: std::mem::take(&mut res.headers),
+                        body: res.into_bytes(),
+                    }
                 }
+            };
+            self.run_on_response(&request, &mut response)?;
+            #[cfg(feature = "tracing")]
+            record_response(&span, response.status_code, &response.headers, started);
+            if response.is_success() {
+                Ok(response)
+            } else {
+                Err(APIError::from_response(&response))
+            }
+        })
+    }
+
+    pub fn execute_stream(&self, mut request: HttpRequest) -> Result<StreamingResponse, APIError> {
+        if self.needs_idempotency_key(&request) {
+            request
+                .headers
+                .insert(IDEMPOTENCY_KEY_HEADER.to_owned(), new_idempotency_key());
+        }
+        self.with_retry(Self::is_retry_safe(&request), |_attempt| {
+            #[cfg(feature = "tracing")]
+            let (span, started) = (request_span(&request, _attempt), std::time::Instant::now());
+            #[cfg(feature = "tracing")]
+            let _entered = span.enter();
+            let mut request = request.clone();
+            let res = match self.run_on_request(&mut request)? {
+                Some(response) => StreamingResponse::from_bytes(
+                    response.status_code,
+                    response.headers,
+                    response.body,
+                ),
+                None => StreamingResponse::from(
+                    self.transport_request(&request)?
+                        .send_lazy()
+                        .map_err(|e| self.new_error(e))?,
+                ),
+            };
+            let success = (200..=299).contains(&res.status_code);
+            let mut body = res.body;
+            let mut response = HttpResponse {
+                status_code: res.status_code,
+                headers: res.headers,
+                body: Vec::new(),
+            };
+            if !success {
+                response.body = body.by_ref().filter_map(Result::ok).collect();
+            }
+            self.run_on_response(&request, &mut response)?;
+            #[cfg(feature = "tracing")]
+            record_response(&span, response.status_code, &response.headers, started);
+            if success {
+                Ok(StreamingResponse {
+                    status_code: response.status_code,
+                    headers: response.headers,
+                    body,
+                })
+            } else {
+                Err(APIError::from_response(&response))
+            }
+        })
+    }
+
+    fn run_on_request(&self, request: &mut HttpRequest) -> Result<Option<HttpResponse>, APIError> {
+        for middleware in &self.middleware {
+            if let Some(response) = middleware.on_request(request)? {
+                return Ok(Some(response));
             }
-            Err(e) => Err(self.new_error(e)),
         }
+        Ok(None)
     }
 
-    pub fn delete(&self, path: &str) -> Result<Response, APIError> {
-        let url = format!(
-            "{api_endpoint}{path}",
-            api_endpoint = self.api_endpoint,
-            path = path
-        );
-        let request = self.build_request(minreq::delete(url), Self::is_beta(path));
-        let res = request.send();
-        match res {
-            Ok(res) => {
-                if (200..=299).contains(&res.status_code) {
-                    Ok(res)
-                } else {
-                    Err(APIError {
-                        message: format!("{}: {}", res.status_code, res.as_str().unwrap()),
-                    })
-                }
+    fn run_on_response(
+        &self,
+        request: &HttpRequest,
+        response: &mut HttpResponse,
+    ) -> Result<(), APIError> {
+        for middleware in self.middleware.iter().rev() {
+            middleware.on_response(request, response)?;
+        }
+        Ok(())
+    }
+
+    fn transport_request(&self, request: &HttpRequest) -> Result<minreq::Request, APIError> {
+        let mut req = match request.method {
+            Method::Get => minreq::get(&request.url),
+            Method::Post => minreq::post(&request.url),
+            Method::Delete => minreq::delete(&request.url),
+        }
+        .with_headers(&request.headers);
+        if let Some(body) = &request.body {
+            req = req.with_body(body.clone());
+        }
+        if let Some(timeout) = request.timeout {
+            req = req.with_timeout(timeout);
+        }
+        if let Some(proxy) = self.proxy_url() {
+            req = req.with_proxy(Self::parse_proxy(&proxy)?);
+        }
+        Ok(req)
+    }
+
+    // The key is added before retries so that every attempt carries the same one.
+    fn needs_idempotency_key(&self, request: &HttpRequest) -> bool {
+        self.idempotency_keys == Some(true)
+            && request.method == Method::Post
+            && request.header(IDEMPOTENCY_KEY_HEADER).is_none()
+    }
+
+    // A POST with an idempotency key can be resent after a broken connection,
+    // as the server will not run it twice.
+    fn is_retry_safe(request: &HttpRequest) -> bool {
+        request.method.is_idempotent() || request.header(IDEMPOTENCY_KEY_HEADER).is_some()
+    }
+
+    fn with_retry<R>(
+        &self,
+        idempotent: bool,
+        mut attempt: impl FnMut(u32) -> Result<R, APIError>,
+    ) -> Result<R, APIError> {
+        let mut retries = 0;
+        loop {
+            let error = match attempt(retries + 1) {
+                Ok(res) => return Ok(res),
+                Err(error) => error,
+            };
+            let policy = match &self.retry_policy {
+                Some(policy) => policy,
+                None => return Err(error),
+            };
+            let retryable = match error {
+                APIError::Connection(_) => idempotent || policy.retry_non_idempotent,
+                _ => error.is_retryable(),
+            };
+            retries += 1;
+            let delay = match (policy.delay_for(retries), error.retry_after()) {
+                (Some(_), Some(wait)) if retryable && wait <= policy.max_retry_after => wait,
+                (Some(delay), None) if retryable => delay,
+                _ => return Err(error),
+            };
+            #[cfg(feature = "tracing")]
+            tracing::warn!(
+                attempt = retries,
+                delay_ms = delay.as_millis() as u64,
+                status_code = error.status_code(),
+                error = %error.message(),
+                "retrying OpenAI request"
+            );
+            if let Some(on_retry) = &policy.on_retry {
+                on_retry(&RetryAttempt {
+                    attempt: retries,
+                    delay,
+                    status_code: error.status_code(),
+                    message: error.message(),
+                });
             }
-            Err(e) => Err(self.new_error(e)),
+            std::thread::sleep(delay);
         }
     }
 
     pub fn completion(&self, req: CompletionRequest) -> Result<CompletionResponse, APIError> {
-        let res = self.post("/completions", &req)?;
-        let r = res.json::<CompletionResponse>();
-        match r {
-            Ok(r) => Ok(r),
-            Err(e) => Err(self.new_error(e)),
-        }
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("openai.completion", model = %req.model).entered();
+        let options = RequestOptions {
+            timeout: req.timeout,
+            extra_headers: req.extra_headers.clone(),
+            idempotency_key: None,
+        };
+        let res = self.post_with_options("/completions", &req, &options)?;
+        let r: CompletionResponse = self.json(res)?;
+        self.record_usage(&r.model, &r.usage);
+        Ok(r)
     }
 
     pub fn edit(&self, req: EditRequest) -> Result<EditResponse, APIError> {
         let res = self.post("/edits", &req)?;
-        let r = res.json::<EditResponse>();
-        match r {
-            Ok(r) => Ok(r),
-            Err(e) => Err(self.new_error(e)),
-        }
+        self.json(res)
     }
 
     pub fn image_generation(
@@ -196,68 +545,80 @@ impl Client {
         req: ImageGenerationRequest,
     ) -> Result<ImageGenerationResponse, APIError> {
         let res = self.post("/images/generations", &req)?;
-        let r = res.json::<ImageGenerationResponse>();
-        match r {
-            Ok(r) => Ok(r),
-            Err(e) => Err(self.new_error(e)),
-        }
+        self.json(res)
     }
 
     pub fn image_edit(&self, req: ImageEditRequest) -> Result<ImageEditResponse, APIError> {
-        let res = self.post("/images/edits", &req)?;
-        let r = res.json::<ImageEditResponse>();
-        match r {
-            Ok(r) => Ok(r),
-            Err(e) => Err(self.new_error(e)),
-        }
+        let mut form = Form::new().file("image", &req.image)?;
+        if let Some(mask) = &req.mask {
+            form = form.file("mask", mask)?;
+        }
+        let form = form
+            .text("prompt", &req.prompt)
+            .text_opt("model", req.model.as_ref())
+            .text_opt("n", req.n)
+            .text_opt("size", req.size.as_ref())
+            .text_opt("response_format", req.response_format.as_ref())
+            .text_opt("user", req.user.as_ref());
+        let res = self.post_form("/images/edits", form)?;
+        self.json(res)
     }
 
     pub fn image_variation(
         &self,
         req: ImageVariationRequest,
     ) -> Result<ImageVariationResponse, APIError> {
-        let res = self.post("/images/variations", &req)?;
-        let r = res.json::<ImageVariationResponse>();
-        match r {
-            Ok(r) => Ok(r),
-            Err(e) => Err(self.new_error(e)),
-        }
+        let form = Form::new()
+            .file("image", &req.image)?
+            .text_opt("model", req.model.as_ref())
+            .text_opt("n", req.n)
+            .text_opt("size", req.size.as_ref())
+            .text_opt("response_format", req.response_format.as_ref())
+            .text_opt("user", req.user.as_ref());
+        let res = self.post_form("/images/variations", form)?;
+        self.json(res)
     }
 
+    /// Creates embeddings, split over several requests per `embedding_batching`
+    /// when there are too many inputs for one.
     pub fn embedding(&self, req: EmbeddingRequest) -> Result<EmbeddingResponse, APIError> {
-        let res = self.post("/embeddings", &req)?;
-        let r = res.json::<EmbeddingResponse>();
-        match r {
-            Ok(r) => Ok(r),
-            Err(e) => Err(self.new_error(e)),
-        }
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("openai.embedding", model = %req.model).entered();
+        let batching = self.embedding_batching.clone().unwrap_or_default();
+        let responses = self
+            .run_concurrently(req.split(&batching), batching.max_concurrent, |req| {
+                let res = self.post("/embeddings", &req)?;
+                self.json::<EmbeddingResponse>(res)
+            })
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()?;
+        let r = EmbeddingResponse::concat(responses)
+            .ok_or_else(|| APIError::Decode("no embedding response".to_owned()))?;
+        let usage = Usage {
+            prompt_tokens: r.usage.prompt_tokens,
+            total_tokens: r.usage.total_tokens,
+            ..Default::default()
+        };
+        self.record_usage(&r.model, &usage);
+        Ok(r)
     }
 
     pub fn file_list(&self) -> Result<FileListResponse, APIError> {
         let res = self.get("/files")?;
-        let r = res.json::<FileListResponse>();
-        match r {
-            Ok(r) => Ok(r),
-            Err(e) => Err(self.new_error(e)),
-        }
+        self.json(res)
     }
 
     pub fn file_upload(&self, req: FileUploadRequest) -> Result<FileUploadResponse, APIError> {
-        let res = self.post("/files", &req)?;
-        let r = res.json::<FileUploadResponse>();
-        match r {
-            Ok(r) => Ok(r),
-            Err(e) => Err(self.new_error(e)),
-        }
+        let form = Form::new()
+            .file("file", &req.file)?
+            .text("purpose", &req.purpose);
+        let res = self.post_form("/files", form)?;
+        self.json(res)
     }
 
     pub fn file_delete(&self, req: FileDeleteRequest) -> Result<FileDeleteResponse, APIError> {
         let res = self.delete(&format!("{}/{}", "/files", req.file_id))?;
-        let r = res.json::<FileDeleteResponse>();
-        match r {
-            Ok(r) => Ok(r),
-            Err(e) => Err(self.new_error(e)),
-        }
+        self.json(res)
     }
 
     pub fn file_retrieve(
@@ -265,11 +626,7 @@ impl Client {
         req: FileRetrieveRequest,
     ) -> Result<FileRetrieveResponse, APIError> {
         let res = self.get(&format!("{}/{}", "/files", req.file_id))?;
-        let r = res.json::<FileRetrieveResponse>();
-        match r {
-            Ok(r) => Ok(r),
-            Err(e) => Err(self.new_error(e)),
-        }
+        self.json(res)
     }
 
     pub fn file_retrieve_content(
@@ -277,34 +634,250 @@ impl Client {
         req: FileRetrieveContentRequest,
     ) -> Result<FileRetrieveContentResponse, APIError> {
         let res = self.get(&format!("{}/{}/content", "/files", req.file_id))?;
-        let r = res.json::<FileRetrieveContentResponse>();
-        match r {
-            Ok(r) => Ok(r),
-            Err(e) => Err(self.new_error(e)),
-        }
+        Ok(FileRetrieveContentResponse {
+            content: res.body,
+            headers: Some(res.headers),
+        })
+    }
+
+    /// Reads the file content as it is downloaded instead of buffering it in memory.
+    pub fn file_retrieve_content_stream(
+        &self,
+        req: FileRetrieveContentRequest,
+    ) -> Result<StreamingResponse, APIError> {
+        let mut request = self.build_request(
+            Method::Get,
+            &format!("{}/{}/content", "/files", req.file_id),
+        );
+        request.stream = true;
+        self.execute_stream(request)
     }
 
     pub fn chat_completion(
         &self,
-        req: ChatCompletionRequest,
+        mut req: ChatCompletionRequest,
     ) -> Result<ChatCompletionResponse, APIError> {
-        let res = self.post("/chat/completions", &req)?;
-        let r = res.json::<ChatCompletionResponse>();
-        match r {
-            Ok(r) => Ok(r),
-            Err(e) => Err(self.new_error(e)),
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("openai.chat_completion", model = %req.model).entered();
+        self.prepare_chat_request(&mut req)?;
+        let cache_key = self
+            .response_cache
+            .as_ref()
+            .and_then(|_| cache::cache_key(&self.url("/chat/completions"), &req));
+        if let (Some(cache), Some(key)) = (&self.response_cache, &cache_key) {
+            if let Some(response) = cache.get(key) {
+                return Ok(response);
+            }
+        }
+        // After the cache, as a cached reply was already screened.
+        self.screen(&req)?;
+        // Dropped on the error paths below, freeing the reserved tokens.
+        let permit = self
+            .throttle
+            .as_ref()
+            .map(|throttle| throttle.reserve(Throttle::estimate_tokens(&req)));
+        let res = self.post_with_options("/chat/completions", &req, &req.request_options())?;
+        let mut r: ChatCompletionResponse = self.json(res)?;
+        if self.legacy_functions == Some(true) {
+            r.upgrade_function_calls();
+        }
+        if let Some(permit) = permit {
+            permit.settle(r.usage.total_tokens.max(0) as u32);
+        }
+        self.record_usage(&r.model, &r.usage);
+        #[cfg(feature = "schema-validation")]
+        if self.validate_structured_output == Some(true) {
+            crate::v1::schema_validation::validate_response(&req, &r)?;
+        }
+        if let (Some(cache), Some(key)) = (&self.response_cache, &cache_key) {
+            cache.put(key, &r);
+        }
+        Ok(r)
+    }
+
+    pub fn chat_completion_stream(
+        &self,
+        req: ChatCompletionRequest,
+    ) -> Result<SseStream<ChatCompletionStreamResponse>, APIError> {
+        #[cfg(feature = "tracing")]
+        let _span =
+            tracing::info_span!("openai.chat_completion", model = %req.model, stream = true)
+                .entered();
+        let mut req = req.stream(true);
+        self.prepare_chat_request(&mut req)?;
+        self.screen(&req)?;
+        let permit = self
+            .throttle
+            .as_ref()
+            .map(|throttle| throttle.reserve(Throttle::estimate_tokens(&req)));
+        let res = self.post_stream("/chat/completions", &req, &req.request_options())?;
+        let stream = SseStream::new(res);
+        Ok(match permit {
+            Some(permit) => stream.with_throttle_permit(permit),
+            None => stream,
+        })
+    }
+
+    /// Sends `requests` through `chat_completion`, with at most `max_concurrent`
+    /// of them in flight at once, each on its own thread.
+    ///
+    /// A failed request does not stop the others; its error takes its place in
+    /// the results, which keep the order of `requests`.
+    pub fn chat_completion_many(
+        &self,
+        requests: Vec<ChatCompletionRequest>,
+        max_concurrent: usize,
+    ) -> ChatCompletionResults {
+        let results =
+            self.run_concurrently(requests, max_concurrent, |req| self.chat_completion(req));
+        let mut usage = Usage::default();
+        for response in results.iter().flatten() {
+            usage.accumulate(&response.usage);
+        }
+        ChatCompletionResults { results, usage }
+    }
+
+    // Calls `f` on each item from up to `max_concurrent` threads, returning
+    // the results in the order of the items.
+    fn run_concurrently<T, R, F>(&self, items: Vec<T>, max_concurrent: usize, f: F) -> Vec<R>
+    where
+        T: Send,
+        R: Send,
+        F: Fn(T) -> R + Sync,
+    {
+        let count = items.len();
+        // A single item, such as an embeddings request that needed no
+        // splitting, runs on the calling thread.
+        if count <= 1 {
+            return items.into_iter().map(f).collect();
+        }
+        let queue = Mutex::new(items.into_iter().enumerate());
+        let mut results: Vec<Option<R>> = (0..count).map(|_| None).collect();
+        std::thread::scope(|scope| {
+            let workers: Vec<_> = (0..max_concurrent.clamp(1, count))
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut done = Vec::new();
+                        loop {
+                            let next = queue.lock().unwrap().next();
+                            match next {
+                                Some((i, item)) => done.push((i, f(item))),
+                                None => return done,
+                            }
+                        }
+                    })
+                })
+                .collect();
+            for worker in workers {
+                let done = worker
+                    .join()
+                    .unwrap_or_else(|panic| std::panic::resume_unwind(panic));
+                for (i, result) in done {
+                    results[i] = Some(result);
+                }
+            }
+        });
+        results.into_iter().flatten().collect()
+    }
+
+    pub(crate) fn record_usage(&self, model: &str, usage: &Usage) {
+        #[cfg(feature = "tracing")]
+        tracing::info!(
+            model,
+            prompt_tokens = usage.prompt_tokens,
+            completion_tokens = usage.completion_tokens,
+            total_tokens = usage.total_tokens,
+            "OpenAI usage"
+        );
+        if let Some(cost_tracker) = &self.cost_tracker {
+            cost_tracker.record(model, usage);
+        }
+        if let Some(on_usage) = &self.on_usage {
+            on_usage(model, usage);
+        }
+    }
+
+    fn prepare_chat_request(&self, req: &mut ChatCompletionRequest) -> Result<(), APIError> {
+        if self.translate_max_tokens == Some(true) {
+            req.translate_max_tokens();
+        }
+        if self.translate_system_role == Some(true) {
+            req.translate_system_role();
+        }
+        if self.legacy_functions == Some(true) {
+            req.downgrade_tools()?;
+        }
+        for tool in req.tools.iter().flatten() {
+            tool.function.validate_strict()?;
         }
+        if let Some(metadata) = &req.empower_metadata {
+            metadata.validate()?;
+        }
+        Ok(())
+    }
+
+    // Runs `moderation_guard` on the new user messages of `req`.
+    fn screen(&self, req: &ChatCompletionRequest) -> Result<(), APIError> {
+        let guard = match &self.moderation_guard {
+            Some(guard) => guard,
+            None => return Ok(()),
+        };
+        let input = match ModerationGuard::input(req) {
+            Some(input) => input,
+            None => return Ok(()),
+        };
+        let verdict = match &guard.classifier {
+            Some(classifier) => classifier(&input)?,
+            None => {
+                let mut moderation = CreateModerationRequest::new(input);
+                moderation.model = guard.model.clone();
+                let res = self.create_moderation(moderation)?;
+                match res.results.iter().find(|result| result.flagged) {
+                    Some(result) => result.verdict(),
+                    None => ModerationVerdict::default(),
+                }
+            }
+        };
+        if verdict.flagged {
+            return Err(APIError::ContentBlocked {
+                categories: verdict.categories,
+                category_scores: verdict.category_scores,
+            });
+        }
+        Ok(())
     }
 
     pub fn audio_transcription(
         &self,
         req: AudioTranscriptionRequest,
     ) -> Result<AudioTranscriptionResponse, APIError> {
-        let res = self.post("/audio/transcriptions", &req)?;
-        let r = res.json::<AudioTranscriptionResponse>();
-        match r {
-            Ok(r) => Ok(r),
-            Err(e) => Err(self.new_error(e)),
+        if req.timestamp_granularities.is_some()
+            && req.response_format != Some(AudioResponseFormat::verbose_json)
+        {
+            return Err(APIError::InvalidRequest(
+                "timestamp_granularities requires the verbose_json response format".into(),
+            ));
+        }
+        let form = Form::new()
+            .file("file", &req.file)?
+            .text("model", &req.model)
+            .text_opt("prompt", req.prompt.as_ref())
+            .text_opt("response_format", req.response_format)
+            .text_opt("temperature", req.temperature)
+            .text_opt("language", req.language.as_ref());
+        let form = req
+            .timestamp_granularities
+            .iter()
+            .flatten()
+            .fold(form, |form, granularity| {
+                form.text("timestamp_granularities[]", granularity)
+            });
+        let res = self.post_form("/audio/transcriptions", form)?;
+        match req.response_format {
+            None | Some(AudioResponseFormat::json) | Some(AudioResponseFormat::verbose_json) => {
+                self.json(res)
+            }
+            Some(format) => Ok(AudioTranscriptionResponse::from_text(res, format)),
         }
     }
 
@@ -312,45 +885,59 @@ impl Client {
         &self,
         req: AudioTranslationRequest,
     ) -> Result<AudioTranslationResponse, APIError> {
-        let res = self.post("/audio/translations", &req)?;
-        let r = res.json::<AudioTranslationResponse>();
-        match r {
-            Ok(r) => Ok(r),
-            Err(e) => Err(self.new_error(e)),
+        let form = Form::new()
+            .file("file", &req.file)?
+            .text("model", &req.model)
+            .text_opt("prompt", req.prompt.as_ref())
+            .text_opt("response_format", req.response_format)
+            .text_opt("temperature", req.temperature);
+        let res = self.post_form("/audio/translations", form)?;
+        match req.response_format {
+            None | Some(AudioResponseFormat::json) | Some(AudioResponseFormat::verbose_json) => {
+                self.json(res)
+            }
+            Some(_) => Ok(AudioTranslationResponse::from_text(res)),
         }
     }
 
     pub fn audio_speech(&self, req: AudioSpeechRequest) -> Result<AudioSpeechResponse, APIError> {
         let res = self.post("/audio/speech", &req)?;
-        let bytes = res.as_bytes();
+        let bytes = &res.body;
         let path = req.output.as_str();
         let path = Path::new(path);
         if let Some(parent) = path.parent() {
-            match create_dir_all(parent) {
-                Ok(_) => {}
-                Err(e) => {
-                    return Err(APIError {
-                        message: e.to_string(),
-                    })
-                }
-            }
-        }
-        match File::create(path) {
-            Ok(mut file) => match file.write_all(bytes) {
-                Ok(_) => {}
-                Err(e) => {
-                    return Err(APIError {
-                        message: e.to_string(),
-                    })
-                }
-            },
-            Err(e) => {
-                return Err(APIError {
-                    message: e.to_string(),
-                })
-            }
-        }
-        Ok(AudioSpeechResponse { result: true })
+            create_dir_all(parent)?;
+        }
+        let mut file = File::create(path)?;
+        file.write_all(bytes)?;
+        Ok(AudioSpeechResponse {
+            result: true,
+            headers: Some(res.headers),
+        })
+    }
+
+    /// Returns the generated audio in memory instead of writing it to `req.output`.
+    pub fn audio_speech_bytes(&self, req: AudioSpeechRequest) -> Result<Vec<u8>, APIError> {
+        let res = self.post("/audio/speech", &req)?;
+        Ok(res.body)
+    }
+
+    /// Returns the audio as it is generated, to start playback before it is complete.
+    pub fn audio_speech_stream(
+        &self,
+        req: AudioSpeechRequest,
+    ) -> Result<StreamingResponse, APIError> {
+        let mut request = self.build_request(Method::Post, "/audio/speech");
+        request.body = Some(serde_json::to_vec(&req).unwrap());
+        request.stream = true;
+        self.execute_stream(request)
+    }
+
+    /// Returns the audio in chunks as it is generated. See `SpeechStream`.
+    pub fn audio_speech_chunks(&self, req: AudioSpeechRequest) -> Result<SpeechStream, APIError> {
+        let format = req.response_format.unwrap_or(SpeechResponseFormat::mp3);
+        let res = self.audio_speech_stream(req)?;
+        Ok(SpeechStream::new(res, format))
     }
 
     pub fn create_fine_tuning_job(
@@ -358,22 +945,14 @@ impl Client {
         req: CreateFineTuningJobRequest,
     ) -> Result<FineTuningJobObject, APIError> {
         let res = self.post("/fine_tuning/jobs", &req)?;
-        let r = res.json::<FineTuningJobObject>();
-        match r {
-            Ok(r) => Ok(r),
-            Err(e) => Err(self.new_error(e)),
-        }
+        self.json(res)
     }
 
     pub fn list_fine_tuning_jobs(
         &self,
     ) -> Result<FineTuningPagination<FineTuningJobObject>, APIError> {
         let res = self.get("/fine_tuning/jobs")?;
-        let r = res.json::<FineTuningPagination<FineTuningJobObject>>();
-        match r {
-            Ok(r) => Ok(r),
-            Err(e) => Err(self.new_error(e)),
-        }
+        self.json(res)
     }
 
     pub fn list_fine_tuning_job_events(
@@ -384,11 +963,7 @@ impl Client {
             "/fine_tuning/jobs/{}/events",
             req.fine_tuning_job_id
         ))?;
-        let r = res.json::<FineTuningPagination<FineTuningJobEvent>>();
-        match r {
-            Ok(r) => Ok(r),
-            Err(e) => Err(self.new_error(e)),
-        }
+        self.json(res)
     }
 
     pub fn retrieve_fine_tuning_job(
@@ -396,11 +971,7 @@ impl Client {
         req: RetrieveFineTuningJobRequest,
     ) -> Result<FineTuningJobObject, APIError> {
         let res = self.get(&format!("/fine_tuning/jobs/{}", req.fine_tuning_job_id))?;
-        let r = res.json::<FineTuningJobObject>();
-        match r {
-            Ok(r) => Ok(r),
-            Err(e) => Err(self.new_error(e)),
-        }
+        self.json(res)
     }
 
     pub fn cancel_fine_tuning_job(
@@ -411,11 +982,7 @@ impl Client {
             &format!("/fine_tuning/jobs/{}/cancel", req.fine_tuning_job_id),
             &req,
         )?;
-        let r = res.json::<FineTuningJobObject>();
-        match r {
-            Ok(r) => Ok(r),
-            Err(e) => Err(self.new_error(e)),
-        }
+        self.json(res)
     }
 
     pub fn create_moderation(
@@ -423,29 +990,33 @@ impl Client {
         req: CreateModerationRequest,
     ) -> Result<CreateModerationResponse, APIError> {
         let res = self.post("/moderations", &req)?;
-        let r = res.json::<CreateModerationResponse>();
-        match r {
-            Ok(r) => Ok(r),
-            Err(e) => Err(self.new_error(e)),
-        }
+        self.json(res)
+    }
+
+    pub fn list_models(&self) -> Result<ListModel, APIError> {
+        let res = self.get("/models")?;
+        self.json(res)
+    }
+
+    pub fn retrieve_model(&self, model_id: String) -> Result<ModelObject, APIError> {
+        let res = self.get(&format!("/models/{}", model_id))?;
+        self.json(res)
+    }
+
+    /// Deletes a fine-tuned model owned by the organization.
+    pub fn delete_model(&self, model_id: String) -> Result<DeletionStatus, APIError> {
+        let res = self.delete(&format!("/models/{}", model_id))?;
+        self.json(res)
     }
 
     pub fn create_assistant(&self, req: AssistantRequest) -> Result<AssistantObject, APIError> {
         let res = self.post("/assistants", &req)?;
-        let r = res.json::<AssistantObject>();
-        match r {
-            Ok(r) => Ok(r),
-            Err(e) => Err(self.new_error(e)),
-        }
+        self.json(res)
     }
 
     pub fn retrieve_assistant(&self, assistant_id: String) -> Result<AssistantObject, APIError> {
         let res = self.get(&format!("/assistants/{}", assistant_id))?;
-        let r = res.json::<AssistantObject>();
-        match r {
-            Ok(r) => Ok(r),
-            Err(e) => Err(self.new_error(e)),
-        }
+        self.json(res)
     }
 
     pub fn modify_assistant(
@@ -454,20 +1025,12 @@ impl Client {
         req: AssistantRequest,
     ) -> Result<AssistantObject, APIError> {
         let res = self.post(&format!("/assistants/{}", assistant_id), &req)?;
-        let r = res.json::<AssistantObject>();
-        match r {
-            Ok(r) => Ok(r),
-            Err(e) => Err(self.new_error(e)),
-        }
+        self.json(res)
     }
 
     pub fn delete_assistant(&self, assistant_id: String) -> Result<DeletionStatus, APIError> {
         let res = self.delete(&format!("/assistants/{}", assistant_id))?;
-        let r = res.json::<DeletionStatus>();
-        match r {
-            Ok(r) => Ok(r),
-            Err(e) => Err(self.new_error(e)),
-        }
+        self.json(res)
     }
 
     pub fn list_assistant(
@@ -480,52 +1043,56 @@ impl Client {
         let mut url = "/assistants".to_owned();
         url = Self::query_params(limit, order, after, before, url);
         let res = self.get(&url)?;
-        let r = res.json::<ListAssistant>();
-        match r {
-            Ok(r) => Ok(r),
-            Err(e) => Err(self.new_error(e)),
-        }
+        self.json(res)
     }
 
+    #[deprecated(
+        since = "4.0.8",
+        note = "Assistants v2 has no assistant files; attach them with `tool_resources`"
+    )]
+    #[allow(deprecated)]
     pub fn create_assistant_file(
         &self,
         assistant_id: String,
         req: AssistantFileRequest,
     ) -> Result<AssistantFileObject, APIError> {
         let res = self.post(&format!("/assistants/{}/files", assistant_id), &req)?;
-        let r = res.json::<AssistantFileObject>();
-        match r {
-            Ok(r) => Ok(r),
-            Err(e) => Err(self.new_error(e)),
-        }
+        self.json(res)
     }
 
+    #[deprecated(
+        since = "4.0.8",
+        note = "Assistants v2 has no assistant files; attach them with `tool_resources`"
+    )]
+    #[allow(deprecated)]
     pub fn retrieve_assistant_file(
         &self,
         assistant_id: String,
         file_id: String,
     ) -> Result<AssistantFileObject, APIError> {
         let res = self.get(&format!("/assistants/{}/files/{}", assistant_id, file_id))?;
-        let r = res.json::<AssistantFileObject>();
-        match r {
-            Ok(r) => Ok(r),
-            Err(e) => Err(self.new_error(e)),
-        }
+        self.json(res)
     }
 
+    #[deprecated(
+        since = "4.0.8",
+        note = "Assistants v2 has no assistant files; attach them with `tool_resources`"
+    )]
+    #[allow(deprecated)]
     pub fn delete_assistant_file(
         &self,
         assistant_id: String,
         file_id: String,
     ) -> Result<DeletionStatus, APIError> {
         let res = self.delete(&format!("/assistants/{}/files/{}", assistant_id, file_id))?;
-        let r = res.json::<DeletionStatus>();
-        match r {
-            Ok(r) => Ok(r),
-            Err(e) => Err(self.new_error(e)),
-        }
+        self.json(res)
     }
 
+    #[deprecated(
+        since = "4.0.8",
+        note = "Assistants v2 has no assistant files; attach them with `tool_resources`"
+    )]
+    #[allow(deprecated)]
     pub fn list_assistant_file(
         &self,
         assistant_id: String,
@@ -537,29 +1104,17 @@ impl Client {
         let mut url = format!("/assistants/{}/files", assistant_id);
         url = Self::query_params(limit, order, after, before, url);
         let res = self.get(&url)?;
-        let r = res.json::<ListAssistantFile>();
-        match r {
-            Ok(r) => Ok(r),
-            Err(e) => Err(self.new_error(e)),
-        }
+        self.json(res)
     }
 
     pub fn create_thread(&self, req: CreateThreadRequest) -> Result<ThreadObject, APIError> {
         let res = self.post("/threads", &req)?;
-        let r = res.json::<ThreadObject>();
-        match r {
-            Ok(r) => Ok(r),
-            Err(e) => Err(self.new_error(e)),
-        }
+        self.json(res)
     }
 
     pub fn retrieve_thread(&self, thread_id: String) -> Result<ThreadObject, APIError> {
         let res = self.get(&format!("/threads/{}", thread_id))?;
-        let r = res.json::<ThreadObject>();
-        match r {
-            Ok(r) => Ok(r),
-            Err(e) => Err(self.new_error(e)),
-        }
+        self.json(res)
     }
 
     pub fn modify_thread(
@@ -568,20 +1123,12 @@ impl Client {
         req: ModifyThreadRequest,
     ) -> Result<ThreadObject, APIError> {
         let res = self.post(&format!("/threads/{}", thread_id), &req)?;
-        let r = res.json::<ThreadObject>();
-        match r {
-            Ok(r) => Ok(r),
-            Err(e) => Err(self.new_error(e)),
-        }
+        self.json(res)
     }
 
     pub fn delete_thread(&self, thread_id: String) -> Result<DeletionStatus, APIError> {
         let res = self.delete(&format!("/threads/{}", thread_id))?;
-        let r = res.json::<DeletionStatus>();
-        match r {
-            Ok(r) => Ok(r),
-            Err(e) => Err(self.new_error(e)),
-        }
+        self.json(res)
     }
 
     pub fn create_message(
@@ -590,11 +1137,7 @@ impl Client {
         req: CreateMessageRequest,
     ) -> Result<MessageObject, APIError> {
         let res = self.post(&format!("/threads/{}/messages", thread_id), &req)?;
-        let r = res.json::<MessageObject>();
-        match r {
-            Ok(r) => Ok(r),
-            Err(e) => Err(self.new_error(e)),
-        }
+        self.json(res)
     }
 
     pub fn retrieve_message(
@@ -603,11 +1146,7 @@ impl Client {
         message_id: String,
     ) -> Result<MessageObject, APIError> {
         let res = self.get(&format!("/threads/{}/messages/{}", thread_id, message_id))?;
-        let r = res.json::<MessageObject>();
-        match r {
-            Ok(r) => Ok(r),
-            Err(e) => Err(self.new_error(e)),
-        }
+        self.json(res)
     }
 
     pub fn modify_message(
@@ -620,22 +1159,19 @@ impl Client {
             &format!("/threads/{}/messages/{}", thread_id, message_id),
             &req,
         )?;
-        let r = res.json::<MessageObject>();
-        match r {
-            Ok(r) => Ok(r),
-            Err(e) => Err(self.new_error(e)),
-        }
+        self.json(res)
     }
 
     pub fn list_messages(&self, thread_id: String) -> Result<ListMessage, APIError> {
         let res = self.get(&format!("/threads/{}/messages", thread_id))?;
-        let r = res.json::<ListMessage>();
-        match r {
-            Ok(r) => Ok(r),
-            Err(e) => Err(self.new_error(e)),
-        }
+        self.json(res)
     }
 
+    #[deprecated(
+        since = "4.0.8",
+        note = "Assistants v2 has no message files; see `MessageObject::attachments`"
+    )]
+    #[allow(deprecated)]
     pub fn retrieve_message_file(
         &self,
         thread_id: String,
@@ -646,13 +1182,14 @@ impl Client {
             "/threads/{}/messages/{}/files/{}",
             thread_id, message_id, file_id
         ))?;
-        let r = res.json::<MessageFileObject>();
-        match r {
-            Ok(r) => Ok(r),
-            Err(e) => Err(self.new_error(e)),
-        }
+        self.json(res)
     }
 
+    #[deprecated(
+        since = "4.0.8",
+        note = "Assistants v2 has no message files; see `MessageObject::attachments`"
+    )]
+    #[allow(deprecated)]
     pub fn list_message_file(
         &self,
         thread_id: String,
@@ -665,11 +1202,7 @@ impl Client {
         let mut url = format!("/threads/{}/messages/{}/files", thread_id, message_id);
         url = Self::query_params(limit, order, after, before, url);
         let res = self.get(&url)?;
-        let r = res.json::<ListMessageFile>();
-        match r {
-            Ok(r) => Ok(r),
-            Err(e) => Err(self.new_error(e)),
-        }
+        self.json(res)
     }
 
     pub fn create_run(
@@ -678,20 +1211,12 @@ impl Client {
         req: CreateRunRequest,
     ) -> Result<RunObject, APIError> {
         let res = self.post(&format!("/threads/{}/runs", thread_id), &req)?;
-        let r = res.json::<RunObject>();
-        match r {
-            Ok(r) => Ok(r),
-            Err(e) => Err(self.new_error(e)),
-        }
+        self.json(res)
     }
 
     pub fn retrieve_run(&self, thread_id: String, run_id: String) -> Result<RunObject, APIError> {
         let res = self.get(&format!("/threads/{}/runs/{}", thread_id, run_id))?;
-        let r = res.json::<RunObject>();
-        match r {
-            Ok(r) => Ok(r),
-            Err(e) => Err(self.new_error(e)),
-        }
+        self.json(res)
     }
 
     pub fn modify_run(
@@ -701,11 +1226,7 @@ impl Client {
         req: ModifyRunRequest,
     ) -> Result<RunObject, APIError> {
         let res = self.post(&format!("/threads/{}/runs/{}", thread_id, run_id), &req)?;
-        let r = res.json::<RunObject>();
-        match r {
-            Ok(r) => Ok(r),
-            Err(e) => Err(self.new_error(e)),
-        }
+        self.json(res)
     }
 
     pub fn list_run(
@@ -719,11 +1240,7 @@ impl Client {
         let mut url = format!("/threads/{}/runs", thread_id);
         url = Self::query_params(limit, order, after, before, url);
         let res = self.get(&url)?;
-        let r = res.json::<ListRun>();
-        match r {
-            Ok(r) => Ok(r),
-            Err(e) => Err(self.new_error(e)),
-        }
+        self.json(res)
     }
 
     pub fn cancel_run(&self, thread_id: String, run_id: String) -> Result<RunObject, APIError> {
@@ -732,23 +1249,162 @@ impl Client {
             &format!("/threads/{}/runs/{}/cancel", thread_id, run_id),
             &empty_req,
         )?;
-        let r = res.json::<RunObject>();
-        match r {
-            Ok(r) => Ok(r),
-            Err(e) => Err(self.new_error(e)),
+        self.json(res)
+    }
+
+    pub fn submit_tool_outputs(
+        &self,
+        thread_id: String,
+        run_id: String,
+        req: SubmitToolOutputsRequest,
+    ) -> Result<RunObject, APIError> {
+        let res = self.post(
+            &format!("/threads/{}/runs/{}/submit_tool_outputs", thread_id, run_id),
+            &req,
+        )?;
+        self.json(res)
+    }
+
+    /// Creates a run and streams its events as they happen.
+    pub fn create_run_stream(
+        &self,
+        thread_id: String,
+        req: CreateRunRequest,
+    ) -> Result<RunEventStream, APIError> {
+        let res = self.post_stream(
+            &format!("/threads/{}/runs", thread_id),
+            &req.stream(true),
+            &RequestOptions::default(),
+        )?;
+        Ok(RunEventStream::new(res))
+    }
+
+    /// Submits tool outputs to a run that requires action and streams the
+    /// events of the resumed run.
+    pub fn submit_tool_outputs_stream(
+        &self,
+        thread_id: String,
+        run_id: String,
+        req: SubmitToolOutputsRequest,
+    ) -> Result<RunEventStream, APIError> {
+        let res = self.post_stream(
+            &format!("/threads/{}/runs/{}/submit_tool_outputs", thread_id, run_id),
+            &req.stream(true),
+            &RequestOptions::default(),
+        )?;
+        Ok(RunEventStream::new(res))
+    }
+
+    /// Streams a run until it stops, passing every event to `on_event`. When
+    /// the run requires action, `on_tool_calls` computes the outputs, which are
+    /// submitted and streamed in turn. Returns the run as last reported.
+    pub fn create_run_and_stream<E, F>(
+        &self,
+        thread_id: String,
+        req: CreateRunRequest,
+        mut on_event: E,
+        mut on_tool_calls: F,
+    ) -> Result<RunObject, APIError>
+    where
+        E: FnMut(&AssistantStreamEvent),
+        F: FnMut(&[ToolCall]) -> Result<Vec<ToolOutput>, APIError>,
+    {
+        let mut events = self.create_run_stream(thread_id, req)?;
+        loop {
+            let mut last_run = None;
+            for event in events {
+                let event = event?;
+                on_event(&event);
+                match event {
+                    AssistantStreamEvent::Run { run, .. } => last_run = Some(run),
+                    AssistantStreamEvent::Error(detail) => {
+                        return Err(APIError::Connection(format!(
+                            "run stream failed: {}",
+                            detail.message
+                        )))
+                    }
+                    _ => {}
+                }
+            }
+            let mut run = last_run.ok_or_else(|| {
+                APIError::Decode("the run stream ended without a run event".to_owned())
+            })?;
+            let action = match run.required_action.take() {
+                Some(action) if !run.is_terminal() => action,
+                _ => return Ok(run),
+            };
+            let outputs = on_tool_calls(&action.submit_tool_outputs.tool_calls)?;
+            events = self.submit_tool_outputs_stream(
+                run.thread_id,
+                run.id,
+                SubmitToolOutputsRequest::new(outputs),
+            )?;
         }
     }
 
+    /// Creates a run and waits for it to finish, see [`Client::poll_run`].
+    pub fn create_run_and_poll<F>(
+        &self,
+        thread_id: String,
+        req: CreateRunRequest,
+        on_tool_calls: F,
+    ) -> Result<RunObject, APIError>
+    where
+        F: FnMut(&[ToolCall]) -> Result<Vec<ToolOutput>, APIError>,
+    {
+        let run = self.create_run(thread_id, req)?;
+        self.poll_run(run, on_tool_calls)
+    }
+
+    /// Polls a run with increasing delays until it reaches a terminal status.
+    /// When the run requires action, `on_tool_calls` computes the outputs that
+    /// are submitted before polling resumes.
+    pub fn poll_run<F>(
+        &self,
+        mut run: RunObject,
+        mut on_tool_calls: F,
+    ) -> Result<RunObject, APIError>
+    where
+        F: FnMut(&[ToolCall]) -> Result<Vec<ToolOutput>, APIError>,
+    {
+        let mut delay = RUN_POLL_MIN_DELAY;
+        while !run.is_terminal() {
+            if let Some(action) = run.required_action.take() {
+                let outputs = on_tool_calls(&action.submit_tool_outputs.tool_calls)?;
+                run = self.submit_tool_outputs(
+                    run.thread_id,
+                    run.id,
+                    SubmitToolOutputsRequest::new(outputs),
+                )?;
+                delay = RUN_POLL_MIN_DELAY;
+                continue;
+            }
+            std::thread::sleep(delay);
+            delay = (delay * 2).min(RUN_POLL_MAX_DELAY);
+            run = self.retrieve_run(run.thread_id, run.id)?;
+        }
+        Ok(run)
+    }
+
     pub fn create_thread_and_run(
         &self,
         req: CreateThreadAndRunRequest,
     ) -> Result<RunObject, APIError> {
         let res = self.post("/threads/runs", &req)?;
-        let r = res.json::<RunObject>();
-        match r {
-            Ok(r) => Ok(r),
-            Err(e) => Err(self.new_error(e)),
-        }
+        self.json(res)
+    }
+
+    /// Creates a thread and a run on it, streaming the events of both.
+    pub fn create_thread_and_run_stream(
+        &self,
+        req: CreateThreadAndRunRequest,
+    ) -> Result<RunEventStream, APIError> {
+        let res = self.post_stream(
+            "/threads/runs",
+            &req.stream(true),
+            &RequestOptions::default(),
+        )?;
+        Ok(RunEventStream::new(res))
     }
 
     pub fn retrieve_run_step(
@@ -761,11 +1417,7 @@ impl Client {
             "/threads/{}/runs/{}/steps/{}",
             thread_id, run_id, step_id
         ))?;
-        let r = res.json::<RunStepObject>();
-        match r {
-            Ok(r) => Ok(r),
-            Err(e) => Err(self.new_error(e)),
-        }
+        self.json(res)
     }
 
     pub fn list_run_step(
@@ -780,21 +1432,177 @@ impl Client {
         let mut url = format!("/threads/{}/runs/{}/steps", thread_id, run_id);
         url = Self::query_params(limit, order, after, before, url);
         let res = self.get(&url)?;
-        let r = res.json::<ListRunStep>();
-        match r {
-            Ok(r) => Ok(r),
-            Err(e) => Err(self.new_error(e)),
-        }
+        self.json(res)
+    }
+
+    fn json<T: DeserializeOwned + Serialize + ResponseHeaders>(
+        &self,
+        res: HttpResponse,
+    ) -> Result<T, APIError> {
+        let mut r = if self.strict_responses == Some(true) {
+            common::from_slice_strict::<T>(&res.body)?
+        } else {
+            common::from_slice::<T>(&res.body)?
+        };
+        r.set_headers(res.headers);
+        Ok(r)
     }
 
     fn new_error(&self, err: minreq::Error) -> APIError {
-        APIError {
-            message: err.to_string(),
-        }
+        APIError::from(err)
+    }
+
+    pub fn create_vector_store(
+        &self,
+        req: CreateVectorStoreRequest,
+    ) -> Result<VectorStoreObject, APIError> {
+        let res = self.post("/vector_stores", &req)?;
+        self.json(res)
+    }
+
+    pub fn retrieve_vector_store(
+        &self,
+        vector_store_id: String,
+    ) -> Result<VectorStoreObject, APIError> {
+        let res = self.get(&format!("/vector_stores/{}", vector_store_id))?;
+        self.json(res)
+    }
+
+    pub fn modify_vector_store(
+        &self,
+        vector_store_id: String,
+        req: ModifyVectorStoreRequest,
+    ) -> Result<VectorStoreObject, APIError> {
+        let res = self.post(&format!("/vector_stores/{}", vector_store_id), &req)?;
+        self.json(res)
+    }
+
+    pub fn delete_vector_store(&self, vector_store_id: String) -> Result<DeletionStatus, APIError> {
+        let res = self.delete(&format!("/vector_stores/{}", vector_store_id))?;
+        self.json(res)
+    }
+
+    pub fn list_vector_store(
+        &self,
+        limit: Option<i64>,
+        order: Option<String>,
+        after: Option<String>,
+        before: Option<String>,
+    ) -> Result<ListVectorStore, APIError> {
+        let mut url = "/vector_stores".to_owned();
+        url = Self::query_params(limit, order, after, before, url);
+        let res = self.get(&url)?;
+        self.json(res)
+    }
+
+    pub fn create_vector_store_file(
+        &self,
+        vector_store_id: String,
+        req: CreateVectorStoreFileRequest,
+    ) -> Result<VectorStoreFileObject, APIError> {
+        let res = self.post(&format!("/vector_stores/{}/files", vector_store_id), &req)?;
+        self.json(res)
+    }
+
+    pub fn retrieve_vector_store_file(
+        &self,
+        vector_store_id: String,
+        file_id: String,
+    ) -> Result<VectorStoreFileObject, APIError> {
+        let res = self.get(&format!(
+            "/vector_stores/{}/files/{}",
+            vector_store_id, file_id
+        ))?;
+        self.json(res)
+    }
+
+    pub fn delete_vector_store_file(
+        &self,
+        vector_store_id: String,
+        file_id: String,
+    ) -> Result<DeletionStatus, APIError> {
+        let res = self.delete(&format!(
+            "/vector_stores/{}/files/{}",
+            vector_store_id, file_id
+        ))?;
+        self.json(res)
+    }
+
+    pub fn list_vector_store_file(
+        &self,
+        vector_store_id: String,
+        limit: Option<i64>,
+        order: Option<String>,
+        after: Option<String>,
+        before: Option<String>,
+    ) -> Result<ListVectorStoreFile, APIError> {
+        let mut url = format!("/vector_stores/{}/files", vector_store_id);
+        url = Self::query_params(limit, order, after, before, url);
+        let res = self.get(&url)?;
+        self.json(res)
+    }
+
+    pub fn create_vector_store_file_batch(
+        &self,
+        vector_store_id: String,
+        req: CreateVectorStoreFileBatchRequest,
+    ) -> Result<VectorStoreFileBatchObject, APIError> {
+        let res = self.post(
+            &format!("/vector_stores/{}/file_batches", vector_store_id),
+            &req,
+        )?;
+        self.json(res)
+    }
+
+    pub fn retrieve_vector_store_file_batch(
+        &self,
+        vector_store_id: String,
+        batch_id: String,
+    ) -> Result<VectorStoreFileBatchObject, APIError> {
+        let res = self.get(&format!(
+            "/vector_stores/{}/file_batches/{}",
+            vector_store_id, batch_id
+        ))?;
+        self.json(res)
+    }
+
+    pub fn cancel_vector_store_file_batch(
+        &self,
+        vector_store_id: String,
+        batch_id: String,
+    ) -> Result<VectorStoreFileBatchObject, APIError> {
+        let res = self.post(
+            &format!(
+                "/vector_stores/{}/file_batches/{}/cancel",
+                vector_store_id, batch_id
+            ),
+            &serde_json::json!({}),
+        )?;
+        self.json(res)
+    }
+
+    pub fn list_vector_store_file_batch_files(
+        &self,
+        vector_store_id: String,
+        batch_id: String,
+        limit: Option<i64>,
+        order: Option<String>,
+        after: Option<String>,
+        before: Option<String>,
+    ) -> Result<ListVectorStoreFile, APIError> {
+        let mut url = format!(
+            "/vector_stores/{}/file_batches/{}/files",
+            vector_store_id, batch_id
+        );
+        url = Self::query_params(limit, order, after, before, url);
+        let res = self.get(&url)?;
+        self.json(res)
     }
 
     fn is_beta(path: &str) -> bool {
-        path.starts_with("/assistants") || path.starts_with("/threads")
+        path.starts_with("/assistants")
+            || path.starts_with("/threads")
+            || path.starts_with("/vector_stores")
     }
 
     fn query_params(
@@ -823,3 +1631,62 @@ impl Client {
         url
     }
 }
+
+impl_builder_methods!(
+    Client,
+    retry_policy: RetryPolicy,
+    throttle: Arc<Throttle>,
+    cost_tracker: Arc<CostTracker>,
+    response_cache: Arc<dyn CacheStore>,
+    coalescer: Arc<Coalescer>,
+    timeout: u64,
+    proxy: String,
+    extra_headers: HashMap<String, String>,
+    azure: AzureConfig,
+    translate_max_tokens: bool,
+    translate_system_role: bool,
+    strict_responses: bool,
+    idempotency_keys: bool,
+    embedding_batching: EmbeddingBatching,
+    moderation_guard: ModerationGuard,
+    legacy_functions: bool
+);
+
+#[cfg(feature = "schema-validation")]
+impl_builder_methods!(Client, validate_structured_output: bool);
+
+// One span per attempt, so retries show up as sibling spans under the call.
+#[cfg(feature = "tracing")]
+fn request_span(request: &HttpRequest, attempt: u32) -> tracing::Span {
+    let path = request
+        .url
+        .split_once("://")
+        .and_then(|(_, rest)| rest.find('/').map(|i| &rest[i..]))
+        .unwrap_or_default();
+    let endpoint = path.split('?').next().unwrap_or_default();
+    tracing::info_span!(
+        "openai.request",
+        method = request.method.as_str(),
+        endpoint,
+        attempt,
+        status_code = tracing::field::Empty,
+        latency_ms = tracing::field::Empty,
+        request_id = tracing::field::Empty,
+    )
+}
+
+#[cfg(feature = "tracing")]
+fn record_response(
+    span: &tracing::Span,
+    status_code: i32,
+    headers: &HashMap<String, String>,
+    started: std::time::Instant,
+) {
+    let latency_ms = started.elapsed().as_millis() as u64;
+    span.record("status_code", status_code);
+    span.record("latency_ms", latency_ms);
+    if let Some(request_id) = headers.get(crate::v1::common::REQUEST_ID_HEADER) {
+        span.record("request_id", request_id.as_str());
+    }
+    tracing::debug!(status_code, latency_ms, "OpenAI response");
+}
@@ -1,7 +1,36 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-use crate::impl_builder_methods;
+use crate::v1::chat_completion::Function;
+use crate::{impl_builder_methods, impl_response_headers};
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AssistantTool {
+    CodeInterpreter,
+    FileSearch,
+    Function { function: Function },
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq)]
+pub struct ToolResources {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code_interpreter: Option<CodeInterpreterResources>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_search: Option<FileSearchResources>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq)]
+pub struct CodeInterpreterResources {
+    #[serde(default)]
+    pub file_ids: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq)]
+pub struct FileSearchResources {
+    #[serde(default)]
+    pub vector_store_ids: Vec<String>,
+}
 
 #[derive(Debug, Serialize, Clone)]
 pub struct AssistantRequest {
@@ -13,9 +42,13 @@ pub struct AssistantRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub instructions: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub tools: Option<Vec<HashMap<String, String>>>,
+    pub tools: Option<Vec<AssistantTool>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_resources: Option<ToolResources>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f64>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub file_ids: Option<Vec<String>>,
+    pub top_p: Option<f64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub metadata: Option<HashMap<String, String>>,
 }
@@ -28,7 +61,9 @@ impl AssistantRequest {
             description: None,
             instructions: None,
             tools: None,
-            file_ids: None,
+            tool_resources: None,
+            temperature: None,
+            top_p: None,
             metadata: None,
         }
     }
@@ -39,12 +74,15 @@ impl_builder_methods!(
     name: String,
     description: String,
     instructions: String,
-    tools: Vec<HashMap<String, String>>,
-    file_ids: Vec<String>,
+    tools: Vec<AssistantTool>,
+    tool_resources: ToolResources,
+    temperature: f64,
+    top_p: f64,
     metadata: HashMap<String, String>
 );
 
 #[derive(Debug, Deserialize, Serialize)]
+#[non_exhaustive]
 pub struct AssistantObject {
     pub id: String,
     pub object: String,
@@ -56,39 +94,79 @@ pub struct AssistantObject {
     pub model: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub instructions: Option<String>,
-    pub tools: Vec<HashMap<String, String>>,
-    pub file_ids: Vec<String>,
+    pub tools: Vec<AssistantTool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_resources: Option<ToolResources>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f64>,
     pub metadata: HashMap<String, String>,
+    #[serde(skip)]
+    pub headers: Option<HashMap<String, String>>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
+#[non_exhaustive]
 pub struct DeletionStatus {
     pub id: String,
     pub object: String,
     pub deleted: bool,
+    #[serde(skip)]
+    pub headers: Option<HashMap<String, String>>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
+#[non_exhaustive]
 pub struct ListAssistant {
     pub object: String,
     pub data: Vec<AssistantObject>,
+    #[serde(skip)]
+    pub headers: Option<HashMap<String, String>>,
 }
 
+#[deprecated(
+    since = "4.0.8",
+    note = "Assistants v2 has no assistant files; attach them with `tool_resources`"
+)]
 #[derive(Debug, Serialize, Clone)]
 pub struct AssistantFileRequest {
     pub file_id: String,
 }
 
+#[deprecated(
+    since = "4.0.8",
+    note = "Assistants v2 has no assistant files; attach them with `tool_resources`"
+)]
 #[derive(Debug, Deserialize, Serialize)]
+#[non_exhaustive]
 pub struct AssistantFileObject {
     pub id: String,
     pub object: String,
     pub created_at: i64,
     pub assistant_id: String,
+    #[serde(skip)]
+    pub headers: Option<HashMap<String, String>>,
 }
 
+#[deprecated(
+    since = "4.0.8",
+    note = "Assistants v2 has no assistant files; attach them with `tool_resources`"
+)]
 #[derive(Debug, Deserialize, Serialize)]
+#[non_exhaustive]
+#[allow(deprecated)]
 pub struct ListAssistantFile {
     pub object: String,
     pub data: Vec<AssistantFileObject>,
+    #[serde(skip)]
+    pub headers: Option<HashMap<String, String>>,
 }
+
+impl_response_headers!(
+    AssistantObject,
+    DeletionStatus,
+    ListAssistant,
+    AssistantFileObject,
+    ListAssistantFile,
+);
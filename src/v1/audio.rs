@@ -1,21 +1,72 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
 
-use crate::impl_builder_methods;
+use crate::v1::error::APIError;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::v1::middleware::HttpResponse;
+use crate::v1::stream::{ByteStream, StreamingResponse};
+use crate::{impl_builder_methods, impl_response_headers};
 
 pub const WHISPER_1: &str = "whisper-1";
 
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[allow(non_camel_case_types)]
+pub enum AudioResponseFormat {
+    json,
+    text,
+    srt,
+    verbose_json,
+    vtt,
+}
+
+impl fmt::Display for AudioResponseFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let format = match self {
+            AudioResponseFormat::json => "json",
+            AudioResponseFormat::text => "text",
+            AudioResponseFormat::srt => "srt",
+            AudioResponseFormat::verbose_json => "verbose_json",
+            AudioResponseFormat::vtt => "vtt",
+        };
+        f.write_str(format)
+    }
+}
+
+/// Which timestamps a `verbose_json` transcription includes.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[allow(non_camel_case_types)]
+pub enum TimestampGranularity {
+    word,
+    segment,
+}
+
+impl fmt::Display for TimestampGranularity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            TimestampGranularity::word => "word",
+            TimestampGranularity::segment => "segment",
+        })
+    }
+}
+
 #[derive(Debug, Serialize, Clone)]
 pub struct AudioTranscriptionRequest {
+    /// Path of the audio file to upload.
     pub file: String,
     pub model: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub prompt: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub response_format: Option<String>,
+    pub response_format: Option<AudioResponseFormat>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub temperature: Option<f32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub language: Option<String>,
+    /// Only allowed with `verbose_json`. Segments are returned by default;
+    /// word timestamps cost extra latency.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timestamp_granularities: Option<Vec<TimestampGranularity>>,
 }
 
 impl AudioTranscriptionRequest {
@@ -27,31 +78,181 @@ impl AudioTranscriptionRequest {
             response_format: None,
             temperature: None,
             language: None,
+            timestamp_granularities: None,
         }
     }
+
+    /// A `verbose_json` request for the given timestamps.
+    pub fn verbose(file: String, model: String, granularities: &[TimestampGranularity]) -> Self {
+        Self::new(file, model)
+            .response_format(AudioResponseFormat::verbose_json)
+            .timestamp_granularities(granularities.to_vec())
+    }
 }
 
 impl_builder_methods!(
     AudioTranscriptionRequest,
     prompt: String,
-    response_format: String,
+    response_format: AudioResponseFormat,
     temperature: f32,
-    language: String
+    language: String,
+    timestamp_granularities: Vec<TimestampGranularity>
 );
 
 #[derive(Debug, Deserialize, Serialize)]
+#[non_exhaustive]
 pub struct AudioTranscriptionResponse {
     pub text: String,
+    // Only present with `verbose_json`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub segments: Option<Vec<TranscriptionSegment>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub words: Option<Vec<TranscriptionWord>>,
+    /// The format `text` is in, when it is not JSON.
+    #[serde(skip)]
+    pub text_format: Option<AudioResponseFormat>,
+    #[serde(skip)]
+    pub headers: Option<HashMap<String, String>>,
+}
+
+impl AudioTranscriptionResponse {
+    /// Wraps a `text`, `srt` or `vtt` body, which is returned as-is.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn from_text(res: HttpResponse, format: AudioResponseFormat) -> Self {
+        Self {
+            text: res.body_str().into_owned(),
+            language: None,
+            duration: None,
+            segments: None,
+            words: None,
+            text_format: Some(format),
+            headers: Some(res.headers),
+        }
+    }
+
+    /// The transcript as SubRip subtitles: the `srt` body as returned, or
+    /// else rendered from the segments of a `verbose_json` one.
+    pub fn to_srt(&self) -> Option<String> {
+        match self.text_format {
+            Some(AudioResponseFormat::srt) => Some(self.text.clone()),
+            _ => self.segments.as_deref().map(render_srt),
+        }
+    }
+
+    /// The transcript as WebVTT subtitles: the `vtt` body as returned, or
+    /// else rendered from the segments of a `verbose_json` one.
+    pub fn to_vtt(&self) -> Option<String> {
+        match self.text_format {
+            Some(AudioResponseFormat::vtt) => Some(self.text.clone()),
+            _ => self.segments.as_deref().map(render_vtt),
+        }
+    }
+}
+
+/// Renders segments as SubRip (`.srt`) subtitles, one cue per segment.
+pub fn render_srt(segments: &[TranscriptionSegment]) -> String {
+    let mut srt = String::new();
+    for (i, segment) in segments.iter().enumerate() {
+        srt.push_str(&format!(
+            "{}\n{} --> {}\n{}\n\n",
+            i + 1,
+            timestamp(segment.start, ','),
+            timestamp(segment.end, ','),
+            segment.text.trim()
+        ));
+    }
+    srt
+}
+
+/// Renders segments as WebVTT (`.vtt`) subtitles, one cue per segment.
+pub fn render_vtt(segments: &[TranscriptionSegment]) -> String {
+    let mut vtt = String::from("WEBVTT\n\n");
+    for segment in segments {
+        vtt.push_str(&format!(
+            "{} --> {}\n{}\n\n",
+            timestamp(segment.start, '.'),
+            timestamp(segment.end, '.'),
+            segment.text.trim()
+        ));
+    }
+    vtt
+}
+
+// `HH:MM:SS` followed by `separator` and milliseconds.
+fn timestamp(seconds: f32, separator: char) -> String {
+    let millis = (seconds.max(0.0) as f64 * 1000.0).round() as u64;
+    format!(
+        "{:02}:{:02}:{:02}{}{:03}",
+        millis / 3_600_000,
+        millis / 60_000 % 60,
+        millis / 1000 % 60,
+        separator,
+        millis % 1000
+    )
+}
+
+/// A stretch of a `verbose_json` transcription. Times are in seconds.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct TranscriptionSegment {
+    pub id: i32,
+    #[serde(default)]
+    pub seek: i32,
+    pub start: f32,
+    pub end: f32,
+    pub text: String,
+    #[serde(default)]
+    pub tokens: Vec<i32>,
+    #[serde(default)]
+    pub temperature: f32,
+    #[serde(default)]
+    pub avg_logprob: f32,
+    #[serde(default)]
+    pub compression_ratio: f32,
+    #[serde(default)]
+    pub no_speech_prob: f32,
 }
 
+impl TranscriptionSegment {
+    /// Average probability of the segment's tokens, from `avg_logprob`.
+    pub fn confidence(&self) -> f32 {
+        self.avg_logprob.exp()
+    }
+}
+
+/// A word of a `verbose_json` transcription requested with word timestamps.
+/// Times are in seconds.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct TranscriptionWord {
+    pub word: String,
+    pub start: f32,
+    pub end: f32,
+    /// Sent by Whisper servers such as faster-whisper, but not by OpenAI.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub probability: Option<f32>,
+}
+
+impl TranscriptionWord {
+    pub fn confidence(&self) -> Option<f32> {
+        self.probability
+    }
+}
+
+pub type Segment = TranscriptionSegment;
+pub type Word = TranscriptionWord;
+
 #[derive(Debug, Serialize, Clone)]
 pub struct AudioTranslationRequest {
+    /// Path of the audio file to upload.
     pub file: String,
     pub model: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub prompt: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub response_format: Option<String>,
+    pub response_format: Option<AudioResponseFormat>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub temperature: Option<f32>,
 }
@@ -71,13 +272,26 @@ impl AudioTranslationRequest {
 impl_builder_methods!(
     AudioTranslationRequest,
     prompt: String,
-    response_format: String,
+    response_format: AudioResponseFormat,
     temperature: f32
 );
 
 #[derive(Debug, Deserialize, Serialize)]
+#[non_exhaustive]
 pub struct AudioTranslationResponse {
     pub text: String,
+    #[serde(skip)]
+    pub headers: Option<HashMap<String, String>>,
+}
+
+impl AudioTranslationResponse {
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn from_text(res: HttpResponse) -> Self {
+        Self {
+            text: res.body_str().into_owned(),
+            headers: Some(res.headers),
+        }
+    }
 }
 
 pub const TTS_1: &str = "tts-1";
@@ -90,11 +304,42 @@ pub const VOICE_ONYX: &str = "onyx";
 pub const VOICE_NOVA: &str = "nova";
 pub const VOICE_SHIMMER: &str = "shimmer";
 
+/// `pcm` speech is raw 16-bit signed little-endian mono samples at this rate.
+pub const PCM_SAMPLE_RATE: u32 = 24_000;
+
+/// Chunk size of a `SpeechStream` by default: 100ms of `pcm` audio.
+pub const DEFAULT_SPEECH_CHUNK_SIZE: usize = 4_800;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[allow(non_camel_case_types)]
+pub enum SpeechResponseFormat {
+    mp3,
+    opus,
+    aac,
+    flac,
+    wav,
+    pcm,
+}
+
+impl SpeechResponseFormat {
+    /// Whether audio in this format can be played from its first chunk on:
+    /// `pcm` has no container, and `opus` is built for low latency.
+    pub fn is_streamable(&self) -> bool {
+        matches!(self, Self::pcm | Self::opus)
+    }
+}
+
 #[derive(Debug, Serialize, Clone)]
 pub struct AudioSpeechRequest {
     pub model: String,
     pub input: String,
     pub voice: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_format: Option<SpeechResponseFormat>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub speed: Option<f32>,
+    /// File that `Client::audio_speech` writes the audio to.
+    #[serde(skip)]
     pub output: String,
 }
 
@@ -104,14 +349,80 @@ impl AudioSpeechRequest {
             model,
             input,
             voice,
+            response_format: None,
+            speed: None,
             output,
         }
     }
 }
 
-impl_builder_methods!(AudioSpeechRequest,);
+impl_builder_methods!(
+    AudioSpeechRequest,
+    response_format: SpeechResponseFormat,
+    speed: f32
+);
 
 #[derive(Debug, Deserialize, Serialize)]
+#[non_exhaustive]
 pub struct AudioSpeechResponse {
     pub result: bool,
+    #[serde(skip)]
+    pub headers: Option<HashMap<String, String>>,
+}
+
+/// Speech audio in chunks of `chunk_size` bytes, yielded as the server
+/// generates it so that playback can start before synthesis completes. The
+/// last chunk may be shorter.
+///
+/// `pcm` chunks always hold whole samples. Use `pcm` or `opus` to start
+/// playback early; other formats may need the whole file to be decoded.
+pub struct SpeechStream {
+    body: ByteStream,
+    format: SpeechResponseFormat,
+    chunk_size: usize,
+    pub headers: Option<HashMap<String, String>>,
+}
+
+impl SpeechStream {
+    pub fn new(response: StreamingResponse, format: SpeechResponseFormat) -> Self {
+        Self {
+            body: response.body,
+            format,
+            chunk_size: DEFAULT_SPEECH_CHUNK_SIZE,
+            headers: Some(response.headers),
+        }
+    }
+
+    /// Sets the chunk size, rounded to whole samples for `pcm`. Smaller
+    /// chunks arrive sooner.
+    pub fn chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = match self.format {
+            SpeechResponseFormat::pcm => (chunk_size - chunk_size % 2).max(2),
+            _ => chunk_size.max(1),
+        };
+        self
+    }
+}
+
+impl Iterator for SpeechStream {
+    type Item = Result<Vec<u8>, APIError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut chunk = Vec::with_capacity(self.chunk_size);
+        while chunk.len() < self.chunk_size {
+            match self.body.next() {
+                Some(Ok(byte)) => chunk.push(byte),
+                Some(Err(e)) => return Some(Err(e)),
+                None => break,
+            }
+        }
+        (!chunk.is_empty()).then_some(Ok(chunk))
+    }
 }
+
+impl_response_headers!(
+    AudioTranscriptionResponse,
+    AudioTranslationResponse,
+    AudioSpeechResponse,
+    SpeechStream
+);
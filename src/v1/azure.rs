@@ -0,0 +1,59 @@
+/// Routes requests to an Azure OpenAI resource instead of api.openai.com.
+#[derive(Debug, Clone)]
+pub struct AzureConfig {
+    pub resource_name: String,
+    pub deployment_id: String,
+    pub api_version: String,
+    /// Overrides `https://{resource_name}.openai.azure.com`, e.g. for private endpoints.
+    pub endpoint: Option<String>,
+}
+
+impl AzureConfig {
+    pub fn new(resource_name: String, deployment_id: String, api_version: String) -> Self {
+        Self {
+            resource_name,
+            deployment_id,
+            api_version,
+            endpoint: None,
+        }
+    }
+
+    pub fn endpoint(mut self, endpoint: String) -> Self {
+        self.endpoint = Some(endpoint);
+        self
+    }
+
+    /// Builds the Azure URL for an OpenAI API path such as `/chat/completions`.
+    pub fn url(&self, path: &str) -> String {
+        let base = match &self.endpoint {
+            Some(endpoint) => endpoint.trim_end_matches('/').to_owned(),
+            None => format!("https://{}.openai.azure.com", self.resource_name),
+        };
+        let separator = if path.contains('?') { '&' } else { '?' };
+        if Self::is_deployment_path(path) {
+            format!(
+                "{}/openai/deployments/{}{}{}api-version={}",
+                base, self.deployment_id, path, separator, self.api_version
+            )
+        } else {
+            format!(
+                "{}/openai{}{}api-version={}",
+                base, path, separator, self.api_version
+            )
+        }
+    }
+
+    // Inference endpoints live under the deployment; management endpoints such as
+    // files, fine-tuning and assistants are scoped to the resource.
+    fn is_deployment_path(path: &str) -> bool {
+        [
+            "/chat/completions",
+            "/completions",
+            "/embeddings",
+            "/audio/",
+            "/images/",
+        ]
+        .iter()
+        .any(|prefix| path.starts_with(prefix))
+    }
+}
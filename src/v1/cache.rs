@@ -0,0 +1,117 @@
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+
+use crate::v1::chat_completion::{ChatCompletionRequest, ChatCompletionResponse};
+
+/// Where `Client` keeps chat responses for `response_cache`. Implement it to
+/// share the cache between processes, e.g. in files or Redis.
+pub trait CacheStore: Send + Sync {
+    fn get(&self, key: &str) -> Option<ChatCompletionResponse>;
+
+    fn put(&self, key: &str, response: &ChatCompletionResponse);
+}
+
+/// The key a chat request sent to `url` is cached under, or `None` when
+/// its reply is not deterministic: only requests with a temperature of 0 and
+/// a seed are cached, and never streams.
+pub fn cache_key(url: &str, req: &ChatCompletionRequest) -> Option<String> {
+    if req.temperature != Some(0.0) || req.seed.is_none() || req.stream == Some(true) {
+        return None;
+    }
+    // Going through `Value` sorts the keys of maps such as `logit_bias`.
+    let body = serde_json::to_value(req).ok()?;
+    // SHA-256 rather than `DefaultHasher`, whose output may change between
+    // Rust releases and would invalidate shared stores.
+    let mut hasher = Sha256::new();
+    hasher.update(url.as_bytes());
+    hasher.update(b"\n");
+    hasher.update(body.to_string().as_bytes());
+    Some(
+        hasher
+            .finalize()
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect(),
+    )
+}
+
+#[derive(Debug, Default)]
+struct State {
+    tick: u64,
+    entries: HashMap<String, (u64, ChatCompletionResponse)>,
+    // Entries by the tick they were last used at, oldest first.
+    recency: BTreeMap<u64, String>,
+}
+
+/// An in-memory cache keeping the `capacity` most recently used responses.
+///
+/// ```no_run
+/// use std::sync::Arc;
+/// use openai_api_rs::v1::api::Client;
+/// use openai_api_rs::v1::cache::LruCache;
+///
+/// let client = Client::new("sk-...".to_owned()).response_cache(Arc::new(LruCache::new(1000)));
+/// ```
+#[derive(Debug)]
+pub struct LruCache {
+    pub capacity: usize,
+    state: Mutex<State>,
+}
+
+impl LruCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            state: Mutex::new(State::default()),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.state.lock().unwrap().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn clear(&self) {
+        *self.state.lock().unwrap() = State::default();
+    }
+}
+
+impl CacheStore for LruCache {
+    fn get(&self, key: &str) -> Option<ChatCompletionResponse> {
+        let mut state = self.state.lock().unwrap();
+        state.tick += 1;
+        let tick = state.tick;
+        let (used, response) = state.entries.get_mut(key)?;
+        let previous = std::mem::replace(used, tick);
+        let response = response.clone();
+        state.recency.remove(&previous);
+        state.recency.insert(tick, key.to_owned());
+        Some(response)
+    }
+
+    fn put(&self, key: &str, response: &ChatCompletionResponse) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut state = self.state.lock().unwrap();
+        state.tick += 1;
+        let tick = state.tick;
+        if let Some((previous, _)) = state
+            .entries
+            .insert(key.to_owned(), (tick, response.clone()))
+        {
+            state.recency.remove(&previous);
+        }
+        state.recency.insert(tick, key.to_owned());
+        while state.entries.len() > self.capacity {
+            match state.recency.pop_first() {
+                Some((_, oldest)) => state.entries.remove(&oldest),
+                None => break,
+            };
+        }
+    }
+}
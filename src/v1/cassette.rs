@@ -0,0 +1,402 @@
+use base64::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::v1::error::APIError;
+use crate::v1::middleware::{HttpRequest, HttpResponse, Method, Middleware};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CassetteMode {
+    /// Sends every request and saves the interactions, replacing the file.
+    Record,
+    /// Answers from the file only; a request that was not recorded fails.
+    Replay,
+    /// Answers from the file when possible and records the rest.
+    RecordMissing,
+}
+
+/// A recorded request and the response it received.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Interaction {
+    pub key: String,
+    pub method: String,
+    pub url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_body: Option<String>,
+    pub status_code: i32,
+    pub headers: HashMap<String, String>,
+    /// The body as text, or base64 when it is not UTF-8, such as audio.
+    pub body: String,
+    #[serde(default)]
+    pub body_base64: bool,
+}
+
+impl Interaction {
+    fn response(&self) -> Result<HttpResponse, APIError> {
+        let body = if self.body_base64 {
+            BASE64_STANDARD
+                .decode(&self.body)
+                .map_err(|e| APIError::Decode(e.to_string()))?
+        } else {
+            self.body.clone().into_bytes()
+        };
+        Ok(HttpResponse {
+            status_code: self.status_code,
+            headers: self.headers.clone(),
+            body,
+        })
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CassetteFile {
+    interactions: Vec<Interaction>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    file: CassetteFile,
+    // How many recordings of each key were replayed so far.
+    replayed: HashMap<String, usize>,
+}
+
+/// Middleware recording API interactions to a JSON file and replaying them,
+/// so tests run deterministically and offline.
+///
+/// Requests are matched by a SHA-256 of the method, the URL path and the
+/// body, see `key`; the host and headers, including the API key, are not
+/// part of the key and are not saved, nor are the `set-cookie` and
+/// `openai-organization` response headers. Identical requests are replayed in the order they were
+/// recorded, the last one repeating. While recording a stream, the whole
+/// body is read before it is handed to the caller, and proxy settings are
+/// not applied.
+///
+/// ```no_run
+/// use openai_api_rs::v1::api::Client;
+/// use openai_api_rs::v1::cassette::{Cassette, CassetteMode};
+///
+/// let cassette = Cassette::open("tests/cassettes/chat.json", CassetteMode::RecordMissing)?;
+/// let client = Client::new(std::env::var("OPENAI_API_KEY").unwrap_or_default())
+///     .with_middleware(cassette);
+/// # Ok::<(), openai_api_rs::v1::error::APIError>(())
+/// ```
+#[derive(Debug)]
+pub struct Cassette {
+    path: PathBuf,
+    mode: CassetteMode,
+    state: Mutex<State>,
+}
+
+impl Cassette {
+    pub fn open<P: AsRef<Path>>(path: P, mode: CassetteMode) -> Result<Self, APIError> {
+        let path = path.as_ref().to_path_buf();
+        let file = match mode {
+            CassetteMode::Record => CassetteFile::default(),
+            CassetteMode::RecordMissing if !path.exists() => CassetteFile::default(),
+            _ => serde_json::from_slice(&fs::read(&path)?)
+                .map_err(|e| APIError::Decode(format!("{}: {}", path.display(), e)))?,
+        };
+        Ok(Self {
+            path,
+            mode,
+            state: Mutex::new(State {
+                file,
+                replayed: HashMap::new(),
+            }),
+        })
+    }
+
+    pub fn interactions(&self) -> Vec<Interaction> {
+        self.state.lock().unwrap().file.interactions.clone()
+    }
+
+    /// The key a request is recorded under: the SHA-256 of its method, path
+    /// and body, so it stays the same across Rust versions and platforms.
+    /// JSON bodies are hashed with their keys sorted and multipart bodies
+    /// with a fixed boundary, as both change from run to run.
+    pub fn key(request: &HttpRequest) -> String {
+        let body = canonical_body(request);
+        let mut hasher = Sha256::new();
+        // Length prefixes keep e.g. "/a" + "b" apart from "/ab" + "".
+        for part in [
+            request.method.as_str().as_bytes(),
+            url_path(&request.url).as_bytes(),
+            &body,
+        ] {
+            hasher.update((part.len() as u64).to_le_bytes());
+            hasher.update(part);
+        }
+        hasher
+            .finalize()
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect()
+    }
+
+    fn replay(&self, key: &str) -> Option<Result<HttpResponse, APIError>> {
+        let mut state = self.state.lock().unwrap();
+        let recorded: Vec<&Interaction> = state
+            .file
+            .interactions
+            .iter()
+            .filter(|interaction| interaction.key == key)
+            .collect();
+        let index = state.replayed.get(key).copied().unwrap_or(0);
+        let interaction = recorded.get(index.min(recorded.len().checked_sub(1)?))?;
+        let response = interaction.response();
+        *state.replayed.entry(key.to_owned()).or_default() += 1;
+        Some(response)
+    }
+
+    fn record(&self, request: &HttpRequest, response: &HttpResponse) -> Result<(), APIError> {
+        let (body, body_base64) = match String::from_utf8(response.body.clone()) {
+            Ok(body) => (body, false),
+            Err(_) => (BASE64_STANDARD.encode(&response.body), true),
+        };
+        let mut state = self.state.lock().unwrap();
+        state.file.interactions.push(Interaction {
+            key: Self::key(request),
+            method: request.method.as_str().to_owned(),
+            url: url_path(&request.url).to_owned(),
+            request_body: request.body_str().map(|body| body.into_owned()),
+            status_code: response.status_code,
+            headers: response
+                .headers
+                .iter()
+                .filter(|(name, _)| !is_private_header(name))
+                .map(|(name, value)| (name.clone(), value.clone()))
+                .collect(),
+            body,
+            body_base64,
+        });
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let json =
+            serde_json::to_vec_pretty(&state.file).map_err(|e| APIError::Decode(e.to_string()))?;
+        fs::write(&self.path, json)?;
+        Ok(())
+    }
+
+    fn records(&self) -> bool {
+        self.mode != CassetteMode::Replay
+    }
+}
+
+impl Middleware for Cassette {
+    fn on_request(&self, request: &mut HttpRequest) -> Result<Option<HttpResponse>, APIError> {
+        let key = Self::key(request);
+        if self.mode != CassetteMode::Record {
+            if let Some(response) = self.replay(&key) {
+                return response.map(Some);
+            }
+        }
+        if !self.records() {
+            return Err(APIError::InvalidRequest(format!(
+                "no recorded interaction for {} {} in {}",
+                request.method.as_str(),
+                url_path(&request.url),
+                self.path.display()
+            )));
+        }
+        if !request.stream {
+            return Ok(None);
+        }
+        // Streamed bodies never reach `on_response`, so read them here.
+        let response = send(request)?;
+        self.record(request, &response)?;
+        Ok(Some(response))
+    }
+
+    fn on_response(
+        &self,
+        request: &HttpRequest,
+        response: &mut HttpResponse,
+    ) -> Result<(), APIError> {
+        if !self.records() || request.stream {
+            return Ok(());
+        }
+        let key = Self::key(request);
+        let already_recorded = self.state.lock().unwrap().replayed.contains_key(&key);
+        if !already_recorded {
+            self.record(request, response)?;
+        }
+        Ok(())
+    }
+}
+
+fn url_path(url: &str) -> &str {
+    url.split_once("://")
+        .and_then(|(_, rest)| rest.find('/').map(|i| &rest[i..]))
+        .unwrap_or(url)
+}
+
+// The body of `request` in a form that does not vary between runs. Maps such
+// as `metadata` serialize in random order, and every multipart form has a
+// random boundary.
+fn canonical_body(request: &HttpRequest) -> Cow<'_, [u8]> {
+    let body = request.body.as_deref().unwrap_or_default();
+    if let Ok(value) = serde_json::from_slice::<Value>(body) {
+        // `Value` keeps object keys sorted.
+        return Cow::Owned(value.to_string().into_bytes());
+    }
+    let boundary = request
+        .header("content-type")
+        .filter(|content_type| content_type.starts_with("multipart/"))
+        .and_then(|content_type| content_type.split_once("boundary="))
+        .map(|(_, boundary)| boundary.trim_matches('"'))
+        .filter(|boundary| !boundary.is_empty());
+    match boundary {
+        Some(boundary) => Cow::Owned(replace_bytes(body, boundary.as_bytes(), b"boundary")),
+        None => Cow::Borrowed(body),
+    }
+}
+
+fn replace_bytes(haystack: &[u8], from: &[u8], to: &[u8]) -> Vec<u8> {
+    let mut replaced = Vec::with_capacity(haystack.len());
+    let mut rest = haystack;
+    while let Some(i) = rest.windows(from.len()).position(|window| window == from) {
+        replaced.extend_from_slice(&rest[..i]);
+        replaced.extend_from_slice(to);
+        rest = &rest[i + from.len()..];
+    }
+    replaced.extend_from_slice(rest);
+    replaced
+}
+
+/// Response headers that identify the account and are left out of cassettes.
+fn is_private_header(name: &str) -> bool {
+    name.eq_ignore_ascii_case("set-cookie") || name.eq_ignore_ascii_case("openai-organization")
+}
+
+fn send(request: &HttpRequest) -> Result<HttpResponse, APIError> {
+    let mut req = match request.method {
+        Method::Get => minreq::get(&request.url),
+        Method::Post => minreq::post(&request.url),
+        Method::Delete => minreq::delete(&request.url),
+    }
+    .with_headers(&request.headers);
+    if let Some(body) = &request.body {
+        req = req.with_body(body.clone());
+    }
+    if let Some(timeout) = request.timeout {
+        req = req.with_timeout(timeout);
+    }
+    let mut res = req.send()?;
+    Ok(HttpResponse {
+        status_code: res.status_code,
+        headers: std::mem::take(&mut res.headers),
+        body: res.into_bytes(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::v1::chat_completion::{
+        ChatCompletionMessage, ChatCompletionRequest, Content, MessageRole,
+    };
+    use crate::v1::file::FileUploadRequest;
+    use crate::v1::mock::{MockResponse, MockServer};
+    use crate::v1::multipart::Form;
+    use serde_json::json;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("openai-api-rs-{}-{}", std::process::id(), name))
+    }
+
+    fn form_request(form: Form) -> HttpRequest {
+        let mut request = HttpRequest::new(Method::Post, "https://api.openai.com/v1/files".into())
+            .with_header("Content-Type", form.content_type());
+        request.body = Some(form.into_body());
+        request
+    }
+
+    #[test]
+    fn key_ignores_json_key_order() {
+        let mut a = HttpRequest::new(Method::Post, "https://api.openai.com/v1/chat".into());
+        a.body = Some(br#"{"metadata":{"a":"1","b":"2"},"model":"m"}"#.to_vec());
+        let mut b = a.clone();
+        b.body = Some(br#"{"model":"m","metadata":{"b":"2","a":"1"}}"#.to_vec());
+        assert_eq!(Cassette::key(&a), Cassette::key(&b));
+        b.body = Some(br#"{"model":"m","metadata":{"b":"3","a":"1"}}"#.to_vec());
+        assert_ne!(Cassette::key(&a), Cassette::key(&b));
+    }
+
+    #[test]
+    fn key_ignores_multipart_boundary() {
+        let form = || Form::new().text("purpose", "fine-tune");
+        let a = form_request(form().bytes("file", "a.jsonl", b"{}\n"));
+        let b = form_request(form().bytes("file", "a.jsonl", b"{}\n"));
+        assert_ne!(a.body, b.body);
+        assert_eq!(Cassette::key(&a), Cassette::key(&b));
+        let c = form_request(form().bytes("file", "a.jsonl", b"[]\n"));
+        assert_ne!(Cassette::key(&a), Cassette::key(&c));
+    }
+
+    #[test]
+    fn replays_uploads_and_metadata() {
+        let server = MockServer::start();
+        server.mock(
+            "POST",
+            "/files",
+            MockResponse::json(
+                200,
+                &json!({
+                    "id": "file-1",
+                    "object": "file",
+                    "bytes": 3,
+                    "created_at": 0,
+                    "filename": "train.jsonl",
+                    "purpose": "fine-tune",
+                }),
+            ),
+        );
+        server.mock("POST", "/chat/completions", MockResponse::chat("hi"));
+        let upload = temp_path("train.jsonl");
+        fs::write(&upload, "{}\n").unwrap();
+        let cassette = temp_path("cassette.json");
+        let file_request =
+            || FileUploadRequest::new(upload.display().to_string(), "fine-tune".to_owned());
+        let chat_request = || {
+            ChatCompletionRequest::new(
+                "gpt-4o".to_owned(),
+                vec![ChatCompletionMessage::new(
+                    MessageRole::user,
+                    Content::from("hello"),
+                )],
+            )
+            .metadata(HashMap::from([
+                ("user".to_owned(), "u1".to_owned()),
+                ("session".to_owned(), "s1".to_owned()),
+            ]))
+        };
+
+        let client = server
+            .client()
+            .with_middleware(Cassette::open(&cassette, CassetteMode::Record).unwrap());
+        client.file_upload(file_request()).unwrap();
+        client.chat_completion(chat_request()).unwrap();
+
+        let client = server
+            .client()
+            .with_middleware(Cassette::open(&cassette, CassetteMode::Replay).unwrap());
+        let file = client.file_upload(file_request());
+        let chat = client.chat_completion(chat_request());
+        fs::remove_file(&upload).unwrap();
+        fs::remove_file(&cassette).unwrap();
+
+        assert_eq!(file.unwrap().id, "file-1");
+        assert_eq!(
+            chat.unwrap().choices[0].message.content.as_deref(),
+            Some("hi")
+        );
+        assert_eq!(server.requests().len(), 2);
+    }
+}
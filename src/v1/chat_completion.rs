@@ -1,12 +1,23 @@
-use serde::de::{self, SeqAccess, Visitor};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde::de::{self, DeserializeOwned, SeqAccess, Visitor};
 use serde::ser::{SerializeMap, SerializeSeq};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde_json::Value;
+use std::borrow::Cow;
 use std::collections::{BTreeMap, HashMap};
 use std::fmt;
+use std::path::Path;
 
-use crate::impl_builder_methods;
-use crate::v1::{common, pyo3::LoraRequest};
+use crate::v1::common::{self, StopSequences};
+use crate::v1::error::APIError;
+use crate::v1::json_repair::{repair_json, strip_code_fence};
+use crate::v1::middleware::RequestOptions;
+#[cfg(feature = "vllm")]
+use crate::v1::pyo3::{LoraRequest, PromptAdapterRequest};
+use crate::v1::raw_output::RawOutput;
+use crate::v1::thinking::{split_thinking, ThinkingSplit};
+use crate::{impl_builder_methods, impl_response_headers};
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
 pub enum ToolChoiceType {
@@ -16,10 +27,29 @@ pub enum ToolChoiceType {
     ToolChoice { tool: Tool },
 }
 
+/// The legacy `function_call` request field: `"none"`, `"auto"` or
+/// `{"name": ...}` to force a function.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum FunctionCallType {
+    Mode(FunctionCallMode),
+    Function { name: String },
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[allow(non_camel_case_types)]
+pub enum FunctionCallMode {
+    none,
+    auto,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct EmpowerMetadata {
     pub id: String,
+    #[cfg(feature = "vllm")]
     pub lora_request: Option<LoraRequest>,
+    #[cfg(feature = "vllm")]
+    pub prompt_adapter_request: Option<PromptAdapterRequest>,
 
     pub use_beam_search: Option<bool>,
     pub best_of: Option<i32>,
@@ -27,9 +57,15 @@ pub struct EmpowerMetadata {
     pub tools_only: Option<bool>,
     pub tools_enabled: Option<bool>,
 
-    pub conversation_json_schema: Option<String>,
-    pub tools_json_schema: Option<String>,
+    // Sent as JSON strings, as the server expects; either form is accepted.
+    #[serde(default, with = "json_string")]
+    pub conversation_json_schema: Option<Value>,
+    #[serde(default, with = "json_string")]
+    pub tools_json_schema: Option<Value>,
     pub num_cached_prefix_messages: Option<usize>,
+    /// An already tokenized prompt, used instead of the messages so that
+    /// neither the chat template nor the tokenizer runs.
+    pub prompt_token_ids: Option<Vec<u32>>,
 
     // Debug flags
     pub logprobs: Option<usize>,
@@ -37,6 +73,108 @@ pub struct EmpowerMetadata {
     pub skip_chat_template: bool,
 }
 
+impl EmpowerMetadata {
+    pub fn new<S: Into<String>>(id: S) -> Self {
+        Self {
+            id: id.into(),
+            ..Self::default()
+        }
+    }
+
+    pub fn ignore_eos(mut self, ignore_eos: bool) -> Self {
+        self.ignore_eos = ignore_eos;
+        self
+    }
+
+    pub fn skip_chat_template(mut self, skip_chat_template: bool) -> Self {
+        self.skip_chat_template = skip_chat_template;
+        self
+    }
+
+    /// Checks that the JSON schemas are schemas: an object or a boolean, with
+    /// a `type` naming JSON types.
+    pub fn validate(&self) -> Result<(), APIError> {
+        for (field, schema) in [
+            ("conversation_json_schema", &self.conversation_json_schema),
+            ("tools_json_schema", &self.tools_json_schema),
+        ] {
+            if let Some(schema) = schema {
+                validate_schema(schema)
+                    .map_err(|reason| APIError::InvalidRequest(format!("{}: {}", field, reason)))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl_builder_methods!(
+    EmpowerMetadata,
+    use_beam_search: bool,
+    best_of: i32,
+    tools_only: bool,
+    tools_enabled: bool,
+    conversation_json_schema: Value,
+    tools_json_schema: Value,
+    num_cached_prefix_messages: usize,
+    prompt_token_ids: Vec<u32>,
+    logprobs: usize
+);
+
+#[cfg(feature = "vllm")]
+impl_builder_methods!(
+    EmpowerMetadata,
+    lora_request: LoraRequest,
+    prompt_adapter_request: PromptAdapterRequest
+);
+
+fn validate_schema(schema: &Value) -> Result<(), String> {
+    const TYPES: &[&str] = &[
+        "string", "number", "integer", "boolean", "object", "array", "null",
+    ];
+    let schema = match schema {
+        Value::Bool(_) => return Ok(()),
+        Value::Object(schema) => schema,
+        other => return Err(format!("expected a schema object, got {}", other)),
+    };
+    let valid_type = |t: &Value| t.as_str().is_some_and(|t| TYPES.contains(&t));
+    match schema.get("type") {
+        None => Ok(()),
+        Some(Value::Array(types)) if types.iter().all(valid_type) => Ok(()),
+        Some(t) if valid_type(t) => Ok(()),
+        Some(t) => Err(format!("unknown type {}", t)),
+    }
+}
+
+// (De)serializes a JSON value embedded in a string, also accepting it inline.
+mod json_string {
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+    use serde_json::Value;
+
+    pub fn serialize<S>(value: &Option<Value>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match value {
+            Some(value) => serializer.serialize_str(&value.to_string()),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<Value>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        match Option::<Value>::deserialize(deserializer)? {
+            Some(Value::String(text)) => serde_json::from_str(&text)
+                .map(Some)
+                .map_err(D::Error::custom),
+            Some(Value::Null) | None => Ok(None),
+            Some(value) => Ok(Some(value)),
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ChatCompletionRequest {
     pub model: String,
@@ -56,20 +194,46 @@ pub struct ChatCompletionRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub max_tokens: Option<i64>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_completion_tokens: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reasoning_effort: Option<ReasoningEffort>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub presence_penalty: Option<f64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub frequency_penalty: Option<f64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub logit_bias: Option<HashMap<String, i32>>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub logprobs: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_logprobs: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub user: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub service_tier: Option<ServiceTier>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub store: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<HashMap<String, String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub seed: Option<i64>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub prediction: Option<Prediction>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub modalities: Option<Vec<Modality>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub audio: Option<AudioOutputConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub tools: Option<Vec<Tool>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(serialize_with = "serialize_tool_choice")]
     pub tool_choice: Option<ToolChoiceType>,
+    /// The legacy form of `tools`, for servers that predate them.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub functions: Option<Vec<Function>>,
+    /// The legacy form of `tool_choice`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub function_call: Option<FunctionCallType>,
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub prettify_tools: Option<bool>,
@@ -81,8 +245,25 @@ pub struct ChatCompletionRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub include_thinking: Option<bool>,
 
+    // vLLM sampling extensions, accepted by its OpenAI-compatible server.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop_token_ids: Option<Vec<u32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bad_words: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include_stop_str_in_output: Option<bool>,
+
     #[serde(skip_serializing_if = "Option::is_none")]
     pub empower_metadata: Option<EmpowerMetadata>,
+    /// Request timeout in seconds, overriding `Client::timeout`. Not sent to the API.
+    #[serde(skip)]
+    pub timeout: Option<u64>,
+    /// Extra HTTP headers for this request only. Not sent in the body.
+    #[serde(skip)]
+    pub extra_headers: Option<HashMap<String, String>>,
+    /// Sent as the `Idempotency-Key` header, the same on every retry.
+    #[serde(skip)]
+    pub idempotency_key: Option<String>,
 }
 
 impl ChatCompletionRequest {
@@ -97,22 +278,158 @@ impl ChatCompletionRequest {
             response_format: None,
             stop: None,
             max_tokens: None,
+            max_completion_tokens: None,
+            reasoning_effort: None,
             presence_penalty: None,
             frequency_penalty: None,
             logit_bias: None,
+            logprobs: None,
+            top_logprobs: None,
             user: None,
+            service_tier: None,
+            store: None,
+            metadata: None,
             seed: None,
+            prediction: None,
+            modalities: None,
+            audio: None,
             tools: None,
             tool_choice: None,
+            functions: None,
+            function_call: None,
             prettify_tools: None,
             structure_output_decoding_mode: None,
             use_raw_output: None,
             include_thinking: None,
+            stop_token_ids: None,
+            bad_words: None,
+            include_stop_str_in_output: None,
             empower_metadata: None,
+            timeout: None,
+            extra_headers: None,
+            idempotency_key: None,
         }
     }
 }
 
+impl ChatCompletionRequest {
+    pub fn request_options(&self) -> RequestOptions {
+        RequestOptions {
+            timeout: self.timeout,
+            extra_headers: self.extra_headers.clone(),
+            idempotency_key: self.idempotency_key.clone(),
+        }
+    }
+
+    /// The completion token limit, whichever of the two fields carries it.
+    pub fn completion_token_limit(&self) -> Option<i64> {
+        self.max_completion_tokens.or(self.max_tokens)
+    }
+
+    /// Moves the token limit into the field the model accepts: reasoning models
+    /// reject `max_tokens`, while older models and servers only know `max_tokens`.
+    pub fn translate_max_tokens(&mut self) {
+        if is_reasoning_model(&self.model) {
+            if self.max_completion_tokens.is_none() {
+                self.max_completion_tokens = self.max_tokens;
+            }
+            self.max_tokens = None;
+        } else if self.max_tokens.is_none() {
+            self.max_tokens = self.max_completion_tokens.take();
+        }
+    }
+
+    /// Rewrites `system` messages as `developer` messages for reasoning models,
+    /// which use `developer` in place of `system`.
+    pub fn translate_system_role(&mut self) {
+        if !is_reasoning_model(&self.model) {
+            return;
+        }
+        for message in &mut self.messages {
+            if message.role == MessageRole::system {
+                message.role = MessageRole::developer;
+            }
+        }
+    }
+
+    /// Rewrites `tools`, `tool_choice` and tool call messages into the legacy
+    /// `functions`, `function_call` and `function` messages, for servers
+    /// that predate tools. Fails when an assistant message has several tool
+    /// calls, which the legacy form cannot express.
+    pub fn downgrade_tools(&mut self) -> Result<(), APIError> {
+        if let Some(tools) = self.tools.take() {
+            let functions = self.functions.get_or_insert_with(Vec::new);
+            functions.extend(tools.into_iter().map(|tool| Function {
+                strict: None,
+                ..tool.function
+            }));
+        }
+        let function_call = match self.tool_choice.take() {
+            None => None,
+            Some(ToolChoiceType::None) => Some(FunctionCallType::Mode(FunctionCallMode::none)),
+            Some(ToolChoiceType::Auto) => Some(FunctionCallType::Mode(FunctionCallMode::auto)),
+            // Calling some function can only be forced when there is one.
+            Some(ToolChoiceType::Any) => match self.functions.as_deref() {
+                Some([function]) => Some(FunctionCallType::Function {
+                    name: function.name.clone(),
+                }),
+                _ => Some(FunctionCallType::Mode(FunctionCallMode::auto)),
+            },
+            Some(ToolChoiceType::ToolChoice { tool }) => Some(FunctionCallType::Function {
+                name: tool.function.name,
+            }),
+        };
+        if function_call.is_some() {
+            self.function_call = function_call;
+        }
+
+        let mut names = HashMap::new();
+        for message in &mut self.messages {
+            if let Some(mut tool_calls) = message.tool_calls.take() {
+                if tool_calls.len() > 1 {
+                    return Err(APIError::InvalidRequest(
+                        "legacy function calling allows one call per message".into(),
+                    ));
+                }
+                if let Some(call) = tool_calls.pop() {
+                    names.insert(call.id, call.function.name.clone());
+                    message.function_call = Some(call.function);
+                }
+            }
+            if message.role == MessageRole::tool {
+                let id = message.tool_call_id.take().unwrap_or_default();
+                message.role = MessageRole::function;
+                if message.name.is_none() {
+                    message.name = names.get(&id).cloned().flatten();
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The JSON schema replies must follow, from a `json_schema` response format
+    /// or Empower's `conversation_json_schema`.
+    pub fn output_schema(&self) -> Option<Value> {
+        let from_response_format = self
+            .response_format
+            .as_ref()
+            .filter(|format| format.get("type").and_then(Value::as_str) == Some("json_schema"))
+            .and_then(|format| format.get("json_schema")?.get("schema").cloned());
+        from_response_format.or_else(|| {
+            self.empower_metadata
+                .as_ref()?
+                .conversation_json_schema
+                .clone()
+        })
+    }
+}
+
+fn is_reasoning_model(model: &str) -> bool {
+    ["o1", "o3", "o4", "gpt-5"]
+        .iter()
+        .any(|prefix| model.starts_with(prefix))
+}
+
 impl_builder_methods!(
     ChatCompletionRequest,
     temperature: f64,
@@ -122,20 +439,116 @@ impl_builder_methods!(
     stream: bool,
     stop: Vec<String>,
     max_tokens: i64,
+    max_completion_tokens: i64,
+    reasoning_effort: ReasoningEffort,
     presence_penalty: f64,
     frequency_penalty: f64,
     logit_bias: HashMap<String, i32>,
+    logprobs: bool,
+    top_logprobs: u8,
     user: String,
+    service_tier: ServiceTier,
+    store: bool,
+    metadata: HashMap<String, String>,
     seed: i64,
+    prediction: Prediction,
+    modalities: Vec<Modality>,
+    audio: AudioOutputConfig,
     tools: Vec<Tool>,
-    tool_choice: ToolChoiceType
+    tool_choice: ToolChoiceType,
+    functions: Vec<Function>,
+    function_call: FunctionCallType,
+    prettify_tools: bool,
+    structure_output_decoding_mode: String,
+    use_raw_output: bool,
+    include_thinking: bool,
+    stop_token_ids: Vec<u32>,
+    bad_words: Vec<String>,
+    include_stop_str_in_output: bool,
+    empower_metadata: EmpowerMetadata,
+    timeout: u64,
+    extra_headers: HashMap<String, String>,
+    idempotency_key: String
 );
 
+impl ChatCompletionRequest {
+    pub fn stop_sequences(mut self, stop: StopSequences) -> Self {
+        self.stop = Some(stop.into());
+        self
+    }
+}
+
+/// Known output, such as a file being lightly edited, that the model can reuse
+/// to respond faster.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Prediction {
+    Content { content: Content },
+}
+
+/// Capacity pool that serves the request; echoed back on the response.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[allow(non_camel_case_types)]
+pub enum ServiceTier {
+    auto,
+    default,
+    flex,
+    scale,
+    priority,
+    /// A tier added to the API after this release, so that responses naming
+    /// it still parse. Not meant to be sent.
+    #[serde(other)]
+    unknown,
+}
+
+/// How much the o-series reasoning models think before answering.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[allow(non_camel_case_types)]
+pub enum ReasoningEffort {
+    low,
+    medium,
+    high,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[allow(non_camel_case_types)]
+pub enum Modality {
+    text,
+    audio,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[allow(non_camel_case_types)]
+pub enum AudioFormat {
+    wav,
+    mp3,
+    flac,
+    opus,
+    pcm16,
+}
+
+/// Voice and encoding of spoken output when `modalities` includes `audio`.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct AudioOutputConfig {
+    pub voice: String,
+    pub format: AudioFormat,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct ChatCompletionAudio {
+    pub id: String,
+    /// Base64 encoded audio in the requested format.
+    pub data: String,
+    pub transcript: String,
+    pub expires_at: i64,
+}
+
 #[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
 #[allow(non_camel_case_types)]
 pub enum MessageRole {
     user,
     system,
+    developer,
     assistant,
     function,
     tool,
@@ -146,14 +559,135 @@ pub enum MessageRole {
 pub enum StructuredContent {
     Text { text: String },
     ImageUrl { image_url: ImageUrlType },
+    InputAudio { input_audio: InputAudio },
+}
+
+impl StructuredContent {
+    pub fn text<S: Into<String>>(text: S) -> Self {
+        Self::Text { text: text.into() }
+    }
+
+    pub fn as_text(&self) -> Option<&str> {
+        match self {
+            Self::Text { text } => Some(text),
+            _ => None,
+        }
+    }
+
+    /// An image given by URL or `data:` URI.
+    pub fn image_url<S: Into<String>>(url: S) -> Self {
+        Self::ImageUrl {
+            image_url: ImageUrlType::new(url),
+        }
+    }
+
+    /// An image sent inline as a base64 `data:` URI.
+    pub fn image_from_bytes(bytes: &[u8], mime_type: &str) -> Self {
+        Self::image_url(format!(
+            "data:{};base64,{}",
+            mime_type,
+            STANDARD.encode(bytes)
+        ))
+    }
+
+    /// The image file at `path`, sent inline. The MIME type is guessed from
+    /// the extension, or else from the file's signature.
+    pub fn image_from_path<P: AsRef<Path>>(path: P) -> Result<Self, APIError> {
+        let path = path.as_ref();
+        let bytes =
+            std::fs::read(path).map_err(|e| APIError::Io(format!("{}: {}", path.display(), e)))?;
+        let mime_type = image_mime_type(path, &bytes).ok_or_else(|| {
+            APIError::InvalidRequest(format!(
+                "{}: not a PNG, JPEG, GIF or WebP image",
+                path.display()
+            ))
+        })?;
+        Ok(Self::image_from_bytes(&bytes, mime_type))
+    }
+}
+
+fn image_mime_type(path: &Path, bytes: &[u8]) -> Option<&'static str> {
+    let extension = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_ascii_lowercase());
+    match extension.as_deref() {
+        Some("png") => Some("image/png"),
+        Some("jpg") | Some("jpeg") => Some("image/jpeg"),
+        Some("gif") => Some("image/gif"),
+        Some("webp") => Some("image/webp"),
+        _ if bytes.starts_with(b"\x89PNG") => Some("image/png"),
+        _ if bytes.starts_with(&[0xff, 0xd8, 0xff]) => Some("image/jpeg"),
+        _ if bytes.starts_with(b"GIF8") => Some("image/gif"),
+        _ if bytes.starts_with(b"RIFF") && bytes.get(8..12) == Some(b"WEBP") => Some("image/webp"),
+        _ => None,
+    }
 }
 
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct InputAudio {
+    /// Base64 encoded audio.
+    pub data: String,
+    pub format: InputAudioFormat,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[allow(non_camel_case_types)]
+pub enum InputAudioFormat {
+    wav,
+    mp3,
+}
+
+/// The content of a message: plain text, or a list of parts mixing text with
+/// images and audio. Both serialize as the API expects.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Content {
     PlainText(String),
     Structured(Vec<StructuredContent>),
 }
 
+impl Content {
+    /// The text of the content, with the text parts of structured content
+    /// joined by newlines and other parts left out.
+    pub fn text(&self) -> Cow<'_, str> {
+        match self {
+            Content::PlainText(text) => Cow::Borrowed(text),
+            Content::Structured(parts) => Cow::Owned(
+                parts
+                    .iter()
+                    .filter_map(StructuredContent::as_text)
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            ),
+        }
+    }
+
+    /// The content as parts, plain text becoming a single text part.
+    pub fn into_parts(self) -> Vec<StructuredContent> {
+        match self {
+            Content::PlainText(text) => vec![StructuredContent::text(text)],
+            Content::Structured(parts) => parts,
+        }
+    }
+}
+
+impl From<String> for Content {
+    fn from(text: String) -> Self {
+        Content::PlainText(text)
+    }
+}
+
+impl From<&str> for Content {
+    fn from(text: &str) -> Self {
+        Content::PlainText(text.to_owned())
+    }
+}
+
+impl From<Vec<StructuredContent>> for Content {
+    fn from(parts: Vec<StructuredContent>) -> Self {
+        Content::Structured(parts)
+    }
+}
+
 impl<'de> Deserialize<'de> for Content {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -211,6 +745,10 @@ impl Serialize for Content {
     }
 }
 
+#[deprecated(
+    since = "4.0.8",
+    note = "use `StructuredContent`, whose variants carry the part type"
+)]
 #[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
 #[allow(non_camel_case_types)]
 pub enum ContentType {
@@ -222,10 +760,37 @@ pub enum ContentType {
 #[allow(non_camel_case_types)]
 pub struct ImageUrlType {
     pub url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<ImageDetail>,
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+impl ImageUrlType {
+    pub fn new<S: Into<String>>(url: S) -> Self {
+        Self {
+            url: url.into(),
+            detail: None,
+        }
+    }
+}
+
+impl_builder_methods!(ImageUrlType, detail: ImageDetail);
+
+/// How closely the model looks at an image: `low` costs a fixed 85 tokens,
+/// `high` adds tokens for every 512px tile, `auto` picks by image size.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
 #[allow(non_camel_case_types)]
+pub enum ImageDetail {
+    low,
+    high,
+    auto,
+}
+
+#[deprecated(
+    since = "4.0.8",
+    note = "use `StructuredContent::Text` or `StructuredContent::ImageUrl`"
+)]
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+#[allow(non_camel_case_types, deprecated)]
 pub struct ImageUrl {
     pub r#type: ContentType,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -234,44 +799,690 @@ pub struct ImageUrl {
     pub image_url: Option<ImageUrlType>,
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+/// Fails on an `image_url` part without an image.
+#[allow(deprecated)]
+impl TryFrom<ImageUrl> for StructuredContent {
+    type Error = APIError;
+
+    fn try_from(part: ImageUrl) -> Result<Self, APIError> {
+        match part.r#type {
+            ContentType::text => Ok(Self::text(part.text.unwrap_or_default())),
+            ContentType::image_url => match part.image_url {
+                Some(image_url) => Ok(Self::ImageUrl { image_url }),
+                None => Err(APIError::InvalidRequest(
+                    "image_url part without an image_url".to_owned(),
+                )),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
 pub struct ChatCompletionMessage {
     pub role: MessageRole,
     pub content: Option<Content>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub tool_calls: Option<Vec<ToolCall>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tool_call_id: Option<String>,
+    /// The legacy form of `tool_calls`, one call per message.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub function_call: Option<ToolCallFunction>,
 }
 
+impl ChatCompletionMessage {
+    pub fn new(role: MessageRole, content: Content) -> Self {
+        Self {
+            role,
+            content: Some(content),
+            name: None,
+            tool_calls: None,
+            tool_call_id: None,
+            function_call: None,
+        }
+    }
+
+    pub fn user<S: Into<String>>(text: S) -> Self {
+        Self::new(MessageRole::user, Content::PlainText(text.into()))
+    }
+
+    pub fn system<S: Into<String>>(text: S) -> Self {
+        Self::new(MessageRole::system, Content::PlainText(text.into()))
+    }
+
+    pub fn developer<S: Into<String>>(text: S) -> Self {
+        Self::new(MessageRole::developer, Content::PlainText(text.into()))
+    }
+
+    pub fn assistant<S: Into<String>>(text: S) -> Self {
+        Self::new(MessageRole::assistant, Content::PlainText(text.into()))
+    }
+
+    /// An assistant turn that called tools, to precede the matching `tool` messages.
+    pub fn assistant_with_tool_calls(tool_calls: Vec<ToolCall>) -> Self {
+        Self {
+            role: MessageRole::assistant,
+            content: None,
+            name: None,
+            tool_calls: Some(tool_calls),
+            tool_call_id: None,
+            function_call: None,
+        }
+    }
+
+    pub fn tool<I: Into<String>, S: Into<String>>(tool_call_id: I, content: S) -> Self {
+        Self {
+            tool_call_id: Some(tool_call_id.into()),
+            ..Self::new(MessageRole::tool, Content::PlainText(content.into()))
+        }
+    }
+
+    /// A user message asking about one image, given by URL or `data:` URI.
+    pub fn user_with_image<S: Into<String>, U: Into<String>>(text: S, image_url: U) -> Self {
+        Self::user_with_images(text, vec![image_url.into()])
+    }
+
+    pub fn user_with_images<S: Into<String>>(text: S, image_urls: Vec<String>) -> Self {
+        let mut parts = vec![StructuredContent::text(text)];
+        parts.extend(image_urls.into_iter().map(StructuredContent::image_url));
+        Self::new(MessageRole::user, Content::Structured(parts))
+    }
+}
+
+impl_builder_methods!(
+    ChatCompletionMessage,
+    name: String,
+    tool_calls: Vec<ToolCall>,
+    tool_call_id: String
+);
+
 #[derive(Debug, Deserialize, Serialize, Clone)]
+#[non_exhaustive]
 pub struct ChatCompletionMessageForResponse {
     pub role: MessageRole,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub content: Option<String>,
+    /// Reasoning returned separately by servers with a reasoning parser, such
+    /// as vLLM and DeepSeek.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reasoning_content: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub audio: Option<ChatCompletionAudio>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub function_call: Option<ToolCallFunction>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tool_calls: Option<Vec<ToolCall>>,
+    /// Fields this crate does not model, such as server-specific extensions,
+    /// kept so they survive re-serialization.
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
+}
+
+impl ChatCompletionMessageForResponse {
+    pub fn new(role: MessageRole, content: Option<String>) -> Self {
+        Self {
+            role,
+            content,
+            reasoning_content: None,
+            audio: None,
+            name: None,
+            function_call: None,
+            tool_calls: None,
+            #[cfg(feature = "extra-fields")]
+            extra: HashMap::new(),
+        }
+    }
+
+    /// Separates the reasoning from the answer, from `reasoning_content` when the
+    /// server sends it and otherwise from thinking tags in the content, as
+    /// returned with `include_thinking`.
+    pub fn split_thinking(&self) -> ThinkingSplit {
+        let content = self.content.as_deref().unwrap_or_default();
+        match &self.reasoning_content {
+            Some(reasoning) => ThinkingSplit {
+                thinking: Some(reasoning.clone()),
+                content: content.to_owned(),
+            },
+            None => split_thinking(content),
+        }
+    }
+
+    /// Decodes the content of a `use_raw_output` reply into thinking, text and
+    /// tool calls.
+    pub fn parse_raw_output(&self) -> RawOutput {
+        RawOutput::parse(self.content.as_deref().unwrap_or_default())
+    }
+}
+
+// Turns a reply into a message that can be sent back as conversation history.
+impl From<ChatCompletionMessageForResponse> for ChatCompletionMessage {
+    fn from(message: ChatCompletionMessageForResponse) -> Self {
+        Self {
+            role: message.role,
+            content: message.content.map(Content::PlainText),
+            name: message.name,
+            tool_calls: message.tool_calls,
+            tool_call_id: None,
+            function_call: message.function_call,
+        }
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
+#[non_exhaustive]
 pub struct ChatCompletionChoice {
     pub index: i64,
     pub message: ChatCompletionMessageForResponse,
     pub finish_reason: Option<FinishReason>,
     pub finish_details: Option<FinishDetails>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub logprobs: Option<ChoiceLogprobs>,
+    /// Fields this crate does not model, such as server-specific extensions,
+    /// kept so they survive re-serialization.
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
+}
+
+impl ChatCompletionChoice {
+    pub fn new(
+        index: i64,
+        message: ChatCompletionMessageForResponse,
+        finish_reason: Option<FinishReason>,
+    ) -> Self {
+        Self {
+            index,
+            message,
+            finish_reason,
+            finish_details: None,
+            logprobs: None,
+            #[cfg(feature = "extra-fields")]
+            extra: HashMap::new(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ChoiceLogprobs {
+    pub content: Option<Vec<LogprobsContent>>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct LogprobsContent {
+    pub token: String,
+    pub logprob: f64,
+    pub bytes: Option<Vec<u8>>,
+    #[serde(default)]
+    pub top_logprobs: Vec<TopLogprob>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct TopLogprob {
+    pub token: String,
+    pub logprob: f64,
+    pub bytes: Option<Vec<u8>>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
+#[non_exhaustive]
 pub struct ChatCompletionResponse {
     pub id: String,
+    #[serde(default)]
+    pub object: String,
+    #[serde(default)]
+    pub created: i64,
     pub model: String,
     pub choices: Vec<ChatCompletionChoice>,
     pub usage: common::Usage,
     pub system_fingerprint: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub service_tier: Option<ServiceTier>,
+    #[serde(skip)]
+    pub headers: Option<HashMap<String, String>>,
+    /// Fields this crate does not model, such as server-specific extensions,
+    /// kept so they survive re-serialization.
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
+}
+
+impl ChatCompletionResponse {
+    /// A response as the API would send it, e.g. for fakes of `OpenAIClient`
+    /// or servers that do not speak the chat completions API.
+    pub fn new(
+        id: String,
+        model: String,
+        choices: Vec<ChatCompletionChoice>,
+        usage: common::Usage,
+    ) -> Self {
+        Self {
+            id,
+            object: "chat.completion".to_owned(),
+            created: 0,
+            model,
+            choices,
+            usage,
+            system_fingerprint: None,
+            service_tier: None,
+            headers: None,
+            #[cfg(feature = "extra-fields")]
+            extra: HashMap::new(),
+        }
+    }
+
+    /// Turns legacy `function_call` replies into tool calls, so that callers
+    /// can use one code path for both. The calls get IDs that
+    /// `ChatCompletionRequest::downgrade_tools` maps back when they are sent
+    /// as history.
+    pub fn upgrade_function_calls(&mut self) {
+        for choice in &mut self.choices {
+            let message = &mut choice.message;
+            if message.tool_calls.is_some() {
+                continue;
+            }
+            if let Some(function) = message.function_call.take() {
+                message.tool_calls = Some(vec![ToolCall {
+                    id: format!("call_{}", choice.index),
+                    r#type: "function".to_owned(),
+                    function,
+                }]);
+                choice.finish_reason = Some(FinishReason::tool_calls);
+            }
+        }
+    }
+}
+
+/// The outcome of `Client::chat_completion_many`, one result per request in
+/// the order the requests were given.
+#[derive(Debug)]
+pub struct ChatCompletionResults {
+    pub results: Vec<Result<ChatCompletionResponse, APIError>>,
+    /// Usage summed over the successful responses.
+    pub usage: common::Usage,
+}
+
+impl ChatCompletionResults {
+    /// The failed requests, by their index in the input.
+    pub fn errors(&self) -> impl Iterator<Item = (usize, &APIError)> {
+        self.results
+            .iter()
+            .enumerate()
+            .filter_map(|(i, result)| result.as_ref().err().map(|e| (i, e)))
+    }
+
+    pub fn is_all_ok(&self) -> bool {
+        self.results.iter().all(Result::is_ok)
+    }
+}
+
+/// A `ChatCompletionResponse` whose strings borrow from the JSON they were
+/// parsed from where possible, for reading large files of stored responses
+/// without an allocation per message. Strings containing escapes are still
+/// allocated. Logprobs, audio and `finish_details` are skipped.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ChatCompletionResponseRef<'a> {
+    #[serde(borrow)]
+    pub id: Cow<'a, str>,
+    #[serde(default)]
+    pub created: i64,
+    #[serde(borrow)]
+    pub model: Cow<'a, str>,
+    #[serde(borrow)]
+    pub choices: Vec<ChatCompletionChoiceRef<'a>>,
+    pub usage: common::Usage,
+    #[serde(borrow, default, deserialize_with = "borrow_str")]
+    pub system_fingerprint: Option<Cow<'a, str>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub service_tier: Option<ServiceTier>,
+}
+
+impl<'a> ChatCompletionResponseRef<'a> {
+    pub fn from_slice(body: &'a [u8]) -> Result<Self, APIError> {
+        serde_json::from_slice(body).map_err(|e| APIError::Decode(e.to_string()))
+    }
+
+    pub fn into_owned(self) -> ChatCompletionResponse {
+        let mut response = ChatCompletionResponse::new(
+            self.id.into_owned(),
+            self.model.into_owned(),
+            self.choices
+                .into_iter()
+                .map(ChatCompletionChoiceRef::into_owned)
+                .collect(),
+            self.usage,
+        );
+        response.created = self.created;
+        response.system_fingerprint = self.system_fingerprint.map(Cow::into_owned);
+        response.service_tier = self.service_tier;
+        response
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ChatCompletionChoiceRef<'a> {
+    pub index: i64,
+    #[serde(borrow)]
+    pub message: ChatCompletionMessageRef<'a>,
+    pub finish_reason: Option<FinishReason>,
+}
+
+impl ChatCompletionChoiceRef<'_> {
+    pub fn into_owned(self) -> ChatCompletionChoice {
+        ChatCompletionChoice::new(self.index, self.message.into_owned(), self.finish_reason)
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ChatCompletionMessageRef<'a> {
+    pub role: MessageRole,
+    #[serde(
+        borrow,
+        default,
+        deserialize_with = "borrow_str",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub content: Option<Cow<'a, str>>,
+    #[serde(
+        borrow,
+        default,
+        deserialize_with = "borrow_str",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub reasoning_content: Option<Cow<'a, str>>,
+    #[serde(
+        borrow,
+        default,
+        deserialize_with = "borrow_str",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub name: Option<Cow<'a, str>>,
+    #[serde(borrow, skip_serializing_if = "Option::is_none")]
+    pub tool_calls: Option<Vec<ToolCallRef<'a>>>,
+}
+
+impl ChatCompletionMessageRef<'_> {
+    pub fn into_owned(self) -> ChatCompletionMessageForResponse {
+        let mut message =
+            ChatCompletionMessageForResponse::new(self.role, self.content.map(Cow::into_owned));
+        message.reasoning_content = self.reasoning_content.map(Cow::into_owned);
+        message.name = self.name.map(Cow::into_owned);
+        message.tool_calls = self
+            .tool_calls
+            .map(|calls| calls.into_iter().map(ToolCallRef::into_owned).collect());
+        message
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct ToolCallRef<'a> {
+    #[serde(borrow)]
+    pub id: Cow<'a, str>,
+    #[serde(borrow)]
+    pub r#type: Cow<'a, str>,
+    #[serde(borrow)]
+    pub function: ToolCallFunctionRef<'a>,
+}
+
+impl ToolCallRef<'_> {
+    pub fn into_owned(self) -> ToolCall {
+        ToolCall {
+            id: self.id.into_owned(),
+            r#type: self.r#type.into_owned(),
+            function: ToolCallFunction {
+                name: self.function.name.map(Cow::into_owned),
+                arguments: self.function.arguments.map(Cow::into_owned),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct ToolCallFunctionRef<'a> {
+    #[serde(
+        borrow,
+        default,
+        deserialize_with = "borrow_str",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub name: Option<Cow<'a, str>>,
+    // Arguments are JSON encoded in a string, so they nearly always contain
+    // escaped quotes and end up allocated.
+    #[serde(
+        borrow,
+        default,
+        deserialize_with = "borrow_str",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub arguments: Option<Cow<'a, str>>,
+}
+
+impl ToolCallFunctionRef<'_> {
+    /// Deserializes the arguments into `T`, like `ToolCallFunction::parse_arguments`.
+    pub fn parse_arguments<T: DeserializeOwned>(&self) -> Result<T, ToolArgumentsError> {
+        parse_arguments(self.arguments.as_deref().unwrap_or_default(), false)
+    }
+}
+
+// `Option<Cow<str>>` always allocates under serde's derive; only a bare `Cow`
+// field marked `borrow` borrows.
+fn borrow_str<'de: 'a, 'a, D>(deserializer: D) -> Result<Option<Cow<'a, str>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    struct Borrowed<'a>(#[serde(borrow)] Cow<'a, str>);
+    Ok(Option::<Borrowed>::deserialize(deserializer)?.map(|borrowed| borrowed.0))
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[non_exhaustive]
+pub struct ChatCompletionStreamResponse {
+    pub id: String,
+    #[serde(default)]
+    pub object: String,
+    #[serde(default)]
+    pub created: i64,
+    pub model: String,
+    pub choices: Vec<ChatCompletionStreamChoice>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub usage: Option<common::Usage>,
+    pub system_fingerprint: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub service_tier: Option<ServiceTier>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ChatCompletionStreamChoice {
+    pub index: i64,
+    pub delta: Delta,
+    pub finish_reason: Option<FinishReason>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub logprobs: Option<ChoiceLogprobs>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct Delta {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub role: Option<MessageRole>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reasoning_content: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_calls: Option<Vec<ToolCallDelta>>,
+}
+
+// Fragment of a tool call; `id`, `type` and the function name only arrive on the
+// first chunk for a given `index`, later chunks carry pieces of `arguments`.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ToolCallDelta {
+    pub index: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub r#type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub function: Option<ToolCallFunction>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct PendingToolCall {
+    index: i64,
+    id: String,
+    r#type: String,
+    name: String,
+    arguments: String,
+    emitted: bool,
+}
+
+impl PendingToolCall {
+    fn is_complete(&self) -> bool {
+        !self.name.is_empty()
+            && matches!(
+                serde_json::from_str::<Value>(&self.arguments),
+                Ok(Value::Object(_))
+            )
+    }
+
+    fn to_tool_call(&self) -> ToolCall {
+        ToolCall {
+            id: self.id.clone(),
+            r#type: if self.r#type.is_empty() {
+                "function".to_owned()
+            } else {
+                self.r#type.clone()
+            },
+            function: ToolCallFunction {
+                name: Some(self.name.clone()),
+                arguments: Some(self.arguments.clone()),
+            },
+        }
+    }
+}
+
+/// Rebuilds a single streamed choice from its deltas.
+#[derive(Debug, Clone, Default)]
+pub struct StreamAggregator {
+    pub role: Option<MessageRole>,
+    pub content: Option<String>,
+    pub reasoning_content: Option<String>,
+    pub finish_reason: Option<FinishReason>,
+    tool_calls: Vec<PendingToolCall>,
+}
+
+impl StreamAggregator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Merges one chunk's choice and returns the tool calls whose arguments became
+    /// well-formed JSON with this chunk. Any calls still pending when the choice
+    /// finishes are returned as-is.
+    pub fn push(&mut self, choice: &ChatCompletionStreamChoice) -> Vec<ToolCall> {
+        let delta = &choice.delta;
+        if let Some(role) = &delta.role {
+            self.role = Some(role.clone());
+        }
+        if let Some(content) = &delta.content {
+            self.content
+                .get_or_insert_with(String::new)
+                .push_str(content);
+        }
+        if let Some(reasoning) = &delta.reasoning_content {
+            self.reasoning_content
+                .get_or_insert_with(String::new)
+                .push_str(reasoning);
+        }
+        if let Some(fragments) = &delta.tool_calls {
+            for fragment in fragments {
+                self.merge_tool_call(fragment);
+            }
+        }
+        if choice.finish_reason.is_some() {
+            self.finish_reason = choice.finish_reason.clone();
+        }
+
+        let finished = self.finish_reason.is_some();
+        let mut ready = Vec::new();
+        for pending in self.tool_calls.iter_mut() {
+            if !pending.emitted && (finished || pending.is_complete()) {
+                pending.emitted = true;
+                ready.push(pending.to_tool_call());
+            }
+        }
+        ready
+    }
+
+    pub fn tool_calls(&self) -> Vec<ToolCall> {
+        self.tool_calls
+            .iter()
+            .map(PendingToolCall::to_tool_call)
+            .collect()
+    }
+
+    pub fn into_message(self) -> ChatCompletionMessageForResponse {
+        let tool_calls = self.tool_calls();
+        let mut message = ChatCompletionMessageForResponse::new(
+            self.role.unwrap_or(MessageRole::assistant),
+            self.content,
+        );
+        message.reasoning_content = self.reasoning_content;
+        if !tool_calls.is_empty() {
+            message.tool_calls = Some(tool_calls);
+        }
+        message
+    }
+
+    fn merge_tool_call(&mut self, fragment: &ToolCallDelta) {
+        // Servers that resend the id on every fragment are matched on it; otherwise
+        // fragments belong to the most recent call with the same index.
+        let position = match fragment.id.as_deref().filter(|id| !id.is_empty()) {
+            Some(id) => self.tool_calls.iter().position(|c| c.id == id).or_else(|| {
+                self.tool_calls
+                    .iter()
+                    .rposition(|c| c.index == fragment.index && c.id.is_empty())
+            }),
+            None => self
+                .tool_calls
+                .iter()
+                .rposition(|c| c.index == fragment.index),
+        };
+        let pending = match position {
+            Some(position) => &mut self.tool_calls[position],
+            None => {
+                self.tool_calls.push(PendingToolCall {
+                    index: fragment.index,
+                    ..Default::default()
+                });
+                self.tool_calls.last_mut().unwrap()
+            }
+        };
+        if let Some(id) = &fragment.id {
+            if pending.id.is_empty() {
+                pending.id = id.clone();
+            }
+        }
+        if let Some(r#type) = &fragment.r#type {
+            if pending.r#type.is_empty() {
+                pending.r#type = r#type.clone();
+            }
+        }
+        if let Some(function) = &fragment.function {
+            if let Some(name) = &function.name {
+                if pending.name.is_empty() {
+                    pending.name = name.clone();
+                }
+            }
+            if let Some(arguments) = &function.arguments {
+                pending.arguments.push_str(arguments);
+            }
+        }
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
@@ -280,6 +1491,68 @@ pub struct Function {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
     pub parameters: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub strict: Option<bool>,
+}
+
+impl Function {
+    /// Checks that a strict function's schema follows the structured outputs
+    /// rules: every object sets `additionalProperties: false` and lists all of
+    /// its properties as required.
+    pub fn validate_strict(&self) -> Result<(), APIError> {
+        if self.strict != Some(true) {
+            return Ok(());
+        }
+        validate_strict_schema(&self.parameters, "parameters").map_err(|reason| {
+            APIError::InvalidRequest(format!("strict function {}: {}", self.name, reason))
+        })
+    }
+}
+
+fn validate_strict_schema(schema: &Value, path: &str) -> Result<(), String> {
+    let schema = match schema.as_object() {
+        Some(schema) => schema,
+        None => return Ok(()),
+    };
+    if let Some(properties) = schema.get("properties").and_then(Value::as_object) {
+        if schema.get("additionalProperties") != Some(&Value::Bool(false)) {
+            return Err(format!("{} must set additionalProperties to false", path));
+        }
+        let required: Vec<&str> = schema
+            .get("required")
+            .and_then(Value::as_array)
+            .map(|r| r.iter().filter_map(Value::as_str).collect())
+            .unwrap_or_default();
+        if let Some(missing) = properties.keys().find(|k| !required.contains(&k.as_str())) {
+            return Err(format!("{}.{} must be listed as required", path, missing));
+        }
+        for (name, property) in properties {
+            validate_strict_schema(property, &format!("{}.{}", path, name))?;
+        }
+    } else if schema.get("type").and_then(Value::as_str) == Some("object")
+        && schema.get("additionalProperties") != Some(&Value::Bool(false))
+    {
+        return Err(format!("{} must set additionalProperties to false", path));
+    }
+    if let Some(items) = schema.get("items") {
+        validate_strict_schema(items, &format!("{}[]", path))?;
+    }
+    for key in ["anyOf", "$defs", "definitions"] {
+        match schema.get(key) {
+            Some(Value::Array(variants)) => {
+                for (i, variant) in variants.iter().enumerate() {
+                    validate_strict_schema(variant, &format!("{}.{}[{}]", path, key, i))?;
+                }
+            }
+            Some(Value::Object(defs)) => {
+                for (name, def) in defs {
+                    validate_strict_schema(def, &format!("{}.{}.{}", path, key, name))?;
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
@@ -287,19 +1560,24 @@ pub struct Function {
 pub enum JSONSchemaType {
     Object,
     Number,
+    Integer,
     String,
     Array,
     Null,
     Boolean,
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq, Eq)]
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq)]
 pub struct JSONSchemaDefine {
-    #[serde(rename = "type")]
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
     pub schema_type: Option<JSONSchemaType>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        rename = "enum",
+        alias = "enum_values",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub enum_values: Option<Vec<String>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub properties: Option<BTreeMap<String, Box<JSONSchemaDefine>>>,
@@ -307,9 +1585,44 @@ pub struct JSONSchemaDefine {
     pub required: Option<Vec<String>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub items: Option<Box<JSONSchemaDefine>>,
+    #[serde(
+        rename = "additionalProperties",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub additional_properties: Option<AdditionalProperties>,
+    #[serde(rename = "anyOf", skip_serializing_if = "Option::is_none")]
+    pub any_of: Option<Vec<JSONSchemaDefine>>,
+    #[serde(rename = "oneOf", skip_serializing_if = "Option::is_none")]
+    pub one_of: Option<Vec<JSONSchemaDefine>>,
+    /// A string format such as `date-time`, `email` or `uuid`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub minimum: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub maximum: Option<f64>,
+    #[serde(rename = "minItems", skip_serializing_if = "Option::is_none")]
+    pub min_items: Option<u64>,
+    #[serde(rename = "maxItems", skip_serializing_if = "Option::is_none")]
+    pub max_items: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default: Option<Value>,
+    /// A reference to a shared definition, e.g. `#/$defs/address`.
+    #[serde(rename = "$ref", skip_serializing_if = "Option::is_none")]
+    pub reference: Option<String>,
+    #[serde(rename = "$defs", skip_serializing_if = "Option::is_none")]
+    pub defs: Option<BTreeMap<String, JSONSchemaDefine>>,
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+/// `additionalProperties` is either a flag or the schema extra properties must match.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum AdditionalProperties {
+    Allowed(bool),
+    Schema(Box<JSONSchemaDefine>),
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
 pub struct FunctionParameters {
     #[serde(rename = "type")]
     pub schema_type: JSONSchemaType,
@@ -326,6 +1639,7 @@ pub enum FinishReason {
     length,
     content_filter,
     tool_calls,
+    function_call,
     null,
 }
 
@@ -336,14 +1650,14 @@ pub struct FinishDetails {
     pub stop: String,
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
 pub struct ToolCall {
     pub id: String,
     pub r#type: String,
     pub function: ToolCallFunction,
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
 pub struct ToolCallFunction {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
@@ -351,6 +1665,98 @@ pub struct ToolCallFunction {
     pub arguments: Option<String>,
 }
 
+impl ToolCallFunction {
+    /// Deserializes the arguments into `T`.
+    ///
+    /// Tolerates what models tend to produce around the JSON: empty arguments for
+    /// functions without parameters, markdown code fences, and an object that was
+    /// encoded a second time as a JSON string. The error is meant to be sent back
+    /// to the model so it can call the tool again.
+    pub fn parse_arguments<T: DeserializeOwned>(&self) -> Result<T, ToolArgumentsError> {
+        parse_arguments(self.arguments.as_deref().unwrap_or_default(), false)
+    }
+
+    /// Like `parse_arguments`, but when the arguments are not valid JSON it
+    /// retries after `repair_json`, e.g. on output that was cut off.
+    pub fn parse_arguments_lenient<T: DeserializeOwned>(&self) -> Result<T, ToolArgumentsError> {
+        parse_arguments(self.arguments.as_deref().unwrap_or_default(), true)
+    }
+}
+
+fn parse_arguments<T: DeserializeOwned>(
+    arguments: &str,
+    repair: bool,
+) -> Result<T, ToolArgumentsError> {
+    let arguments = strip_code_fence(arguments);
+    let mut value = if arguments.is_empty() {
+        Value::Object(Default::default())
+    } else {
+        match serde_json::from_str(arguments) {
+            Ok(value) => value,
+            Err(e) if !repair => return Err(ToolArgumentsError::Syntax(e.to_string())),
+            Err(e) => serde_json::from_str(&repair_json(arguments))
+                .map_err(|_| ToolArgumentsError::Syntax(e.to_string()))?,
+        }
+    };
+    if let Value::String(inner) = &value {
+        if let Ok(inner @ (Value::Object(_) | Value::Array(_))) =
+            serde_json::from_str(strip_code_fence(inner))
+        {
+            value = inner;
+        }
+    }
+    serde_json::from_value(value).map_err(|e| ToolArgumentsError::from_serde(&e))
+}
+
+/// Why the arguments of a tool call could not be deserialized.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ToolArgumentsError {
+    /// The arguments are not valid JSON.
+    Syntax(String),
+    /// A required field is absent.
+    MissingField(String),
+    /// A field has the wrong type or an unexpected value.
+    InvalidValue(String),
+}
+
+impl ToolArgumentsError {
+    fn from_serde(err: &serde_json::Error) -> Self {
+        let message = err.to_string();
+        let field = message
+            .strip_prefix("missing field `")
+            .and_then(|rest| rest.split_once('`'))
+            .map(|(field, _)| field.to_owned());
+        match field {
+            Some(field) => ToolArgumentsError::MissingField(field),
+            None => ToolArgumentsError::InvalidValue(message),
+        }
+    }
+}
+
+impl fmt::Display for ToolArgumentsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ToolArgumentsError::Syntax(message) => {
+                write!(f, "arguments are not valid JSON: {}", message)
+            }
+            ToolArgumentsError::MissingField(field) => {
+                write!(f, "missing required argument `{}`", field)
+            }
+            ToolArgumentsError::InvalidValue(message) => {
+                write!(f, "invalid argument: {}", message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ToolArgumentsError {}
+
+impl From<ToolArgumentsError> for APIError {
+    fn from(err: ToolArgumentsError) -> Self {
+        APIError::Decode(err.to_string())
+    }
+}
+
 fn serialize_tool_choice<S>(
     value: &Option<ToolChoiceType>,
     serializer: S,
@@ -383,3 +1789,160 @@ pub struct Tool {
 pub enum ToolType {
     Function,
 }
+
+impl_response_headers!(ChatCompletionResponse);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn merge(aggregator: &mut StreamAggregator, fragment: Value) {
+        aggregator.merge_tool_call(&serde_json::from_value(fragment).unwrap());
+    }
+
+    fn summary(aggregator: &StreamAggregator) -> Vec<(String, String, String)> {
+        aggregator
+            .tool_calls()
+            .into_iter()
+            .map(|call| {
+                (
+                    call.id,
+                    call.function.name.unwrap(),
+                    call.function.arguments.unwrap(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn appends_fragments_to_the_call_at_their_index() {
+        let mut aggregator = StreamAggregator::new();
+        merge(
+            &mut aggregator,
+            json!({ "index": 0, "id": "a", "type": "function", "function": { "name": "f", "arguments": "" } }),
+        );
+        merge(
+            &mut aggregator,
+            json!({ "index": 1, "id": "b", "function": { "name": "g", "arguments": "{" } }),
+        );
+        merge(
+            &mut aggregator,
+            json!({ "index": 0, "function": { "arguments": "{\"x\":" } }),
+        );
+        merge(
+            &mut aggregator,
+            json!({ "index": 0, "function": { "arguments": "1}" } }),
+        );
+        merge(
+            &mut aggregator,
+            json!({ "index": 1, "function": { "arguments": "}" } }),
+        );
+        assert_eq!(
+            summary(&aggregator),
+            vec![
+                ("a".to_owned(), "f".to_owned(), "{\"x\":1}".to_owned()),
+                ("b".to_owned(), "g".to_owned(), "{}".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn starts_a_new_call_when_an_index_is_reused_with_a_new_id() {
+        let mut aggregator = StreamAggregator::new();
+        merge(
+            &mut aggregator,
+            json!({ "index": 0, "id": "a", "function": { "name": "f", "arguments": "{}" } }),
+        );
+        merge(
+            &mut aggregator,
+            json!({ "index": 0, "id": "b", "function": { "name": "g", "arguments": "{\"y\"" } }),
+        );
+        merge(
+            &mut aggregator,
+            json!({ "index": 0, "function": { "arguments": ":2}" } }),
+        );
+        assert_eq!(
+            summary(&aggregator),
+            vec![
+                ("a".to_owned(), "f".to_owned(), "{}".to_owned()),
+                ("b".to_owned(), "g".to_owned(), "{\"y\":2}".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn matches_fragments_that_repeat_their_id() {
+        let mut aggregator = StreamAggregator::new();
+        merge(
+            &mut aggregator,
+            json!({ "index": 0, "id": "a", "function": { "name": "f", "arguments": "{\"x\"" } }),
+        );
+        merge(
+            &mut aggregator,
+            json!({ "index": 0, "id": "b", "function": { "name": "g", "arguments": "{" } }),
+        );
+        merge(
+            &mut aggregator,
+            json!({ "index": 0, "id": "a", "function": { "name": "f", "arguments": ":1}" } }),
+        );
+        merge(
+            &mut aggregator,
+            json!({ "index": 0, "id": "b", "function": { "arguments": "}" } }),
+        );
+        assert_eq!(
+            summary(&aggregator),
+            vec![
+                ("a".to_owned(), "f".to_owned(), "{\"x\":1}".to_owned()),
+                ("b".to_owned(), "g".to_owned(), "{}".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn fills_in_an_id_sent_after_the_first_fragment() {
+        let mut aggregator = StreamAggregator::new();
+        merge(
+            &mut aggregator,
+            json!({ "index": 0, "function": { "name": "f", "arguments": "{" } }),
+        );
+        merge(
+            &mut aggregator,
+            json!({ "index": 0, "id": "a", "function": { "arguments": "}" } }),
+        );
+        assert_eq!(
+            summary(&aggregator),
+            vec![("a".to_owned(), "f".to_owned(), "{}".to_owned())]
+        );
+    }
+
+    #[test]
+    fn push_emits_each_call_once_when_its_arguments_complete() {
+        let mut aggregator = StreamAggregator::new();
+        let choice = |tool_calls: Value, finish_reason: Value| -> ChatCompletionStreamChoice {
+            serde_json::from_value(json!({
+                "index": 0,
+                "delta": { "tool_calls": tool_calls },
+                "finish_reason": finish_reason,
+            }))
+            .unwrap()
+        };
+        let ready = aggregator.push(&choice(
+            json!([
+                { "index": 0, "id": "a", "function": { "name": "f", "arguments": "{}" } },
+                { "index": 1, "id": "b", "function": { "name": "g", "arguments": "{" } },
+            ]),
+            Value::Null,
+        ));
+        assert_eq!(
+            ready.iter().map(|c| c.id.as_str()).collect::<Vec<_>>(),
+            ["a"]
+        );
+        let ready = aggregator.push(&choice(json!([]), json!("tool_calls")));
+        assert_eq!(
+            ready.iter().map(|c| c.id.as_str()).collect::<Vec<_>>(),
+            ["b"]
+        );
+        assert_eq!(ready[0].function.arguments.as_deref(), Some("{"));
+    }
+}
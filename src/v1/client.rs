@@ -0,0 +1,76 @@
+#[cfg(not(target_arch = "wasm32"))]
+use crate::v1::api::Client;
+use crate::v1::chat_completion::{
+    ChatCompletionRequest, ChatCompletionResponse, ChatCompletionStreamResponse,
+};
+use crate::v1::completion::{CompletionRequest, CompletionResponse};
+use crate::v1::embedding::{EmbeddingRequest, EmbeddingResponse};
+use crate::v1::error::APIError;
+use crate::v1::model::ListModel;
+use crate::v1::moderation::{CreateModerationRequest, CreateModerationResponse};
+use crate::v1::stream::SseStream;
+
+/// The main operations of `Client`, for code that should also run against a
+/// fake in tests. Object safe, so it can be held as `&dyn OpenAIClient` or
+/// `Box<dyn OpenAIClient>`.
+///
+/// Like `Client`, the methods block. A fake stream can be built with
+/// `SseStream::new(StreamingResponse::from_bytes(..))` over canned SSE text.
+pub trait OpenAIClient {
+    fn chat_completion(
+        &self,
+        req: ChatCompletionRequest,
+    ) -> Result<ChatCompletionResponse, APIError>;
+
+    fn chat_completion_stream(
+        &self,
+        req: ChatCompletionRequest,
+    ) -> Result<SseStream<ChatCompletionStreamResponse>, APIError>;
+
+    fn completion(&self, req: CompletionRequest) -> Result<CompletionResponse, APIError>;
+
+    fn embedding(&self, req: EmbeddingRequest) -> Result<EmbeddingResponse, APIError>;
+
+    fn create_moderation(
+        &self,
+        req: CreateModerationRequest,
+    ) -> Result<CreateModerationResponse, APIError>;
+
+    fn list_models(&self) -> Result<ListModel, APIError>;
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl OpenAIClient for Client {
+    fn chat_completion(
+        &self,
+        req: ChatCompletionRequest,
+    ) -> Result<ChatCompletionResponse, APIError> {
+        Client::chat_completion(self, req)
+    }
+
+    fn chat_completion_stream(
+        &self,
+        req: ChatCompletionRequest,
+    ) -> Result<SseStream<ChatCompletionStreamResponse>, APIError> {
+        Client::chat_completion_stream(self, req)
+    }
+
+    fn completion(&self, req: CompletionRequest) -> Result<CompletionResponse, APIError> {
+        Client::completion(self, req)
+    }
+
+    fn embedding(&self, req: EmbeddingRequest) -> Result<EmbeddingResponse, APIError> {
+        Client::embedding(self, req)
+    }
+
+    fn create_moderation(
+        &self,
+        req: CreateModerationRequest,
+    ) -> Result<CreateModerationResponse, APIError> {
+        Client::create_moderation(self, req)
+    }
+
+    fn list_models(&self) -> Result<ListModel, APIError> {
+        Client::list_models(self)
+    }
+}
@@ -0,0 +1,112 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Condvar, Mutex};
+
+use crate::v1::error::APIError;
+use crate::v1::middleware::{HttpRequest, HttpResponse, Method};
+
+type Key = (Method, String, Vec<(String, String)>, Option<Vec<u8>>);
+
+#[derive(Debug, Default)]
+struct Call {
+    result: Mutex<Option<Result<HttpResponse, APIError>>>,
+    done: Condvar,
+}
+
+/// Shares one upstream call between concurrent callers sending byte-identical
+/// requests: same method, URL, headers and body. Callers arriving while the
+/// first one waits for the server get a copy of its response or error.
+///
+/// Only buffered requests are coalesced, never streams. Meant for read-heavy
+/// deterministic workloads, where identical requests have interchangeable
+/// answers.
+///
+/// ```no_run
+/// use std::sync::Arc;
+/// use openai_api_rs::v1::api::Client;
+/// use openai_api_rs::v1::coalesce::Coalescer;
+///
+/// let client = Client::new("sk-...".to_owned()).coalescer(Arc::new(Coalescer::new()));
+/// ```
+#[derive(Debug, Default)]
+pub struct Coalescer {
+    calls: Mutex<HashMap<Key, Arc<Call>>>,
+}
+
+impl Coalescer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of distinct requests currently being sent.
+    pub fn in_flight(&self) -> usize {
+        self.calls.lock().unwrap().len()
+    }
+
+    /// Runs `send` for `request` unless an identical request is already in
+    /// flight, in which case its result is awaited and returned instead.
+    pub fn run<F>(&self, request: &HttpRequest, send: F) -> Result<HttpResponse, APIError>
+    where
+        F: FnOnce() -> Result<HttpResponse, APIError>,
+    {
+        let mut headers: Vec<(String, String)> = request
+            .headers
+            .iter()
+            .map(|(name, value)| (name.to_ascii_lowercase(), value.clone()))
+            .collect();
+        headers.sort();
+        let key = (
+            request.method,
+            request.url.clone(),
+            headers,
+            request.body.clone(),
+        );
+        let (call, leader) = {
+            let mut calls = self.calls.lock().unwrap();
+            match calls.get(&key) {
+                Some(call) => (call.clone(), false),
+                None => {
+                    let call = Arc::new(Call::default());
+                    calls.insert(key.clone(), call.clone());
+                    (call, true)
+                }
+            }
+        };
+        if !leader {
+            let mut result = call.result.lock().unwrap();
+            while result.is_none() {
+                result = call.done.wait(result).unwrap();
+            }
+            return result.clone().unwrap();
+        }
+        let guard = Publish {
+            coalescer: self,
+            key,
+            call: &call,
+        };
+        let result = send();
+        *call.result.lock().unwrap() = Some(result.clone());
+        drop(guard);
+        result
+    }
+}
+
+// Ends the call even when `send` panics, so that waiting callers get an error
+// instead of blocking forever.
+struct Publish<'a> {
+    coalescer: &'a Coalescer,
+    key: Key,
+    call: &'a Call,
+}
+
+impl Drop for Publish<'_> {
+    fn drop(&mut self) {
+        self.coalescer.calls.lock().unwrap().remove(&self.key);
+        let mut result = self.call.result.lock().unwrap_or_else(|e| e.into_inner());
+        if result.is_none() {
+            *result = Some(Err(APIError::Connection(
+                "the coalesced request was abandoned".to_owned(),
+            )));
+        }
+        self.call.done.notify_all();
+    }
+}
@@ -1,10 +1,444 @@
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+use crate::v1::error::APIError;
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+#[non_exhaustive]
 pub struct Usage {
     pub prompt_tokens: i32,
     pub completion_tokens: i32,
     pub total_tokens: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompt_tokens_details: Option<PromptTokensDetails>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub completion_tokens_details: Option<CompletionTokensDetails>,
+}
+
+impl Usage {
+    pub fn new(prompt_tokens: i32, completion_tokens: i32) -> Self {
+        Self {
+            prompt_tokens,
+            completion_tokens,
+            total_tokens: prompt_tokens + completion_tokens,
+            prompt_tokens_details: None,
+            completion_tokens_details: None,
+        }
+    }
+
+    /// Adds `other`'s token counts to these, e.g. to total several responses.
+    pub fn accumulate(&mut self, other: &Usage) {
+        fn sum(a: &mut Option<i32>, b: Option<i32>) {
+            if let Some(b) = b {
+                *a = Some(a.unwrap_or(0) + b);
+            }
+        }
+        self.prompt_tokens += other.prompt_tokens;
+        self.completion_tokens += other.completion_tokens;
+        self.total_tokens += other.total_tokens;
+        if let Some(other) = &other.prompt_tokens_details {
+            let details = self
+                .prompt_tokens_details
+                .get_or_insert_with(Default::default);
+            sum(&mut details.cached_tokens, other.cached_tokens);
+            sum(&mut details.audio_tokens, other.audio_tokens);
+        }
+        if let Some(other) = &other.completion_tokens_details {
+            let details = self
+                .completion_tokens_details
+                .get_or_insert_with(Default::default);
+            sum(&mut details.reasoning_tokens, other.reasoning_tokens);
+            sum(&mut details.audio_tokens, other.audio_tokens);
+            sum(
+                &mut details.accepted_prediction_tokens,
+                other.accepted_prediction_tokens,
+            );
+            sum(
+                &mut details.rejected_prediction_tokens,
+                other.rejected_prediction_tokens,
+            );
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+#[non_exhaustive]
+pub struct PromptTokensDetails {
+    /// Prompt tokens served from the prompt cache.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cached_tokens: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub audio_tokens: Option<i32>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+#[non_exhaustive]
+pub struct CompletionTokensDetails {
+    /// Tokens spent on hidden reasoning; billed as completion tokens.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reasoning_tokens: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub audio_tokens: Option<i32>,
+    /// Predicted tokens that appeared in the completion.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub accepted_prediction_tokens: Option<i32>,
+    /// Predicted tokens that did not appear in the completion; still billed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rejected_prediction_tokens: Option<i32>,
+}
+
+/// Rate limit state reported by the `x-ratelimit-*` response headers.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RateLimitInfo {
+    pub limit_requests: Option<u64>,
+    pub limit_tokens: Option<u64>,
+    pub remaining_requests: Option<u64>,
+    pub remaining_tokens: Option<u64>,
+    pub reset_requests: Option<Duration>,
+    pub reset_tokens: Option<Duration>,
+}
+
+impl RateLimitInfo {
+    /// Returns `None` when the response carries none of the rate limit headers.
+    pub fn from_headers(headers: &HashMap<String, String>) -> Option<Self> {
+        let number = |name: &str| headers.get(name).and_then(|v| v.trim().parse().ok());
+        let duration = |name: &str| headers.get(name).and_then(|v| parse_reset_duration(v));
+        let info = Self {
+            limit_requests: number("x-ratelimit-limit-requests"),
+            limit_tokens: number("x-ratelimit-limit-tokens"),
+            remaining_requests: number("x-ratelimit-remaining-requests"),
+            remaining_tokens: number("x-ratelimit-remaining-tokens"),
+            reset_requests: duration("x-ratelimit-reset-requests"),
+            reset_tokens: duration("x-ratelimit-reset-tokens"),
+        };
+        if info == Self::default() {
+            None
+        } else {
+            Some(info)
+        }
+    }
+}
+
+/// How long the server asks to wait before retrying: `retry-after-ms`, then
+/// `Retry-After` in seconds or as an HTTP date, then for 429 responses the
+/// reset time of whichever rate limit is exhausted.
+pub fn retry_after(status_code: i32, headers: &HashMap<String, String>) -> Option<Duration> {
+    if let Some(millis) = headers
+        .get("retry-after-ms")
+        .and_then(|v| v.trim().parse::<f64>().ok())
+    {
+        return Duration::try_from_secs_f64(millis / 1000.0).ok();
+    }
+    if let Some(value) = headers.get("retry-after") {
+        let value = value.trim();
+        return match value.parse::<f64>() {
+            Ok(seconds) => Duration::try_from_secs_f64(seconds).ok(),
+            Err(_) => {
+                let at = parse_http_date(value)?;
+                Some(at.duration_since(SystemTime::now()).unwrap_or_default())
+            }
+        };
+    }
+    if status_code != 429 {
+        return None;
+    }
+    let info = RateLimitInfo::from_headers(headers)?;
+    [
+        (info.remaining_requests, info.reset_requests),
+        (info.remaining_tokens, info.reset_tokens),
+    ]
+    .into_iter()
+    .filter(|(remaining, _)| *remaining == Some(0))
+    .filter_map(|(_, reset)| reset)
+    .max()
+}
+
+// Parses the IMF-fixdate form of HTTP dates, e.g. "Sun, 06 Nov 1994 08:49:37 GMT".
+fn parse_http_date(value: &str) -> Option<SystemTime> {
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+    let mut parts = value.split_once(", ")?.1.split(' ');
+    let day: i64 = parts.next()?.parse().ok()?;
+    let month = parts.next()?;
+    let month = MONTHS.iter().position(|m| *m == month)? as i64 + 1;
+    let year: i64 = parts.next()?.parse().ok()?;
+    let mut time = parts.next()?.split(':').map(|n| n.parse::<i64>().ok());
+    let (hour, minute, second) = (time.next()??, time.next()??, time.next()??);
+    if parts.next()? != "GMT" {
+        return None;
+    }
+    // Days since 1970-01-01 in the proleptic Gregorian calendar.
+    let (y, m) = if month <= 2 {
+        (year - 1, month + 9)
+    } else {
+        (year, month - 3)
+    };
+    let era = y.div_euclid(400);
+    let year_of_era = y - era * 400;
+    let day_of_year = (153 * m + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146097 + day_of_era - 719468;
+    let seconds = days * 86400 + hour * 3600 + minute * 60 + second;
+    UNIX_EPOCH.checked_add(Duration::from_secs(u64::try_from(seconds).ok()?))
+}
+
+// Reset headers use Go-style durations such as "1s", "6m0s", "1h2m3.5s" or "20ms".
+fn parse_reset_duration(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<f64>() {
+        return Duration::try_from_secs_f64(seconds).ok();
+    }
+    let mut total = 0f64;
+    let mut rest = value;
+    while !rest.is_empty() {
+        let number_len = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(rest.len());
+        let number: f64 = rest[..number_len].parse().ok()?;
+        rest = &rest[number_len..];
+        let unit_len = rest
+            .find(|c: char| c.is_ascii_digit() || c == '.')
+            .unwrap_or(rest.len());
+        let scale = match &rest[..unit_len] {
+            "h" => 3600.0,
+            "m" => 60.0,
+            "s" => 1.0,
+            "ms" => 1e-3,
+            "us" | "µs" => 1e-6,
+            "ns" => 1e-9,
+            _ => return None,
+        };
+        total += number * scale;
+        rest = &rest[unit_len..];
+    }
+    Duration::try_from_secs_f64(total).ok()
+}
+
+/// Most stop sequences OpenAI accepts in one request.
+pub const MAX_STOP_SEQUENCES: usize = 4;
+
+/// Up to `MAX_STOP_SEQUENCES` non-empty stop sequences, as OpenAI requires.
+/// Pass them to a request's `stop_sequences`; `stop` takes any list, e.g. for
+/// servers that allow more.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StopSequences(Vec<String>);
+
+impl StopSequences {
+    pub fn new<I, S>(sequences: I) -> Result<Self, APIError>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let mut stop = Self::default();
+        for sequence in sequences {
+            stop.push(sequence)?;
+        }
+        Ok(stop)
+    }
+
+    pub fn push<S: Into<String>>(&mut self, sequence: S) -> Result<(), APIError> {
+        let sequence = sequence.into();
+        if sequence.is_empty() {
+            return Err(APIError::InvalidRequest(
+                "stop sequences must not be empty".into(),
+            ));
+        }
+        if self.0.len() == MAX_STOP_SEQUENCES {
+            return Err(APIError::InvalidRequest(format!(
+                "at most {} stop sequences are allowed",
+                MAX_STOP_SEQUENCES
+            )));
+        }
+        self.0.push(sequence);
+        Ok(())
+    }
+
+    pub fn as_slice(&self) -> &[String] {
+        &self.0
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl TryFrom<Vec<String>> for StopSequences {
+    type Error = APIError;
+
+    fn try_from(sequences: Vec<String>) -> Result<Self, APIError> {
+        Self::new(sequences)
+    }
+}
+
+impl From<StopSequences> for Vec<String> {
+    fn from(stop: StopSequences) -> Self {
+        stop.0
+    }
+}
+
+/// Builds a `logit_bias` map, keyed by token ID, from biases given for
+/// strings. Every token `encode` splits a string into gets its bias, from
+/// -100 (never emitted) to 100 (always emitted).
+///
+/// `encode` must use the tokenizer of the model the request goes to, e.g.
+/// `Tokenizer::encode` from the `vllm` feature for vLLM served models, or a
+/// tiktoken port for OpenAI ones. Tokenizers usually give a word with a
+/// leading space its own token, so bias `" word"` as well as `"word"` to
+/// keep the model from writing it mid-sentence. Biasing a word made of
+/// several tokens affects every word sharing them.
+///
+/// ```
+/// use openai_api_rs::v1::common::logit_bias_from_strings;
+///
+/// let bias = logit_bias_from_strings([("word", -100), (" word", -100)], |text| {
+///     Ok(if text.starts_with(' ') { vec![1234] } else { vec![5678] })
+/// })?;
+/// assert_eq!(bias.get("1234"), Some(&-100));
+/// # Ok::<(), openai_api_rs::v1::error::APIError>(())
+/// ```
+pub fn logit_bias_from_strings<I, S, F>(
+    biases: I,
+    mut encode: F,
+) -> Result<HashMap<String, i32>, APIError>
+where
+    I: IntoIterator<Item = (S, i32)>,
+    S: AsRef<str>,
+    F: FnMut(&str) -> Result<Vec<u32>, APIError>,
+{
+    let mut logit_bias = HashMap::new();
+    for (text, bias) in biases {
+        let text = text.as_ref();
+        if !(-100..=100).contains(&bias) {
+            return Err(APIError::InvalidRequest(format!(
+                "logit bias for {:?} must be between -100 and 100, not {}",
+                text, bias
+            )));
+        }
+        let tokens = encode(text)?;
+        if tokens.is_empty() {
+            return Err(APIError::InvalidRequest(format!(
+                "{:?} encodes to no tokens",
+                text
+            )));
+        }
+        for token in tokens {
+            logit_bias.insert(token.to_string(), bias);
+        }
+    }
+    Ok(logit_bias)
+}
+
+/// Parses a JSON response body, as the client does for every response and
+/// stream event. Also useful to reparse stored responses in bulk.
+///
+/// With the `simd-json` feature, parsing uses SIMD instructions. simd-json
+/// parses in place, so `body` is copied into a scratch buffer first.
+pub fn from_slice<T: DeserializeOwned>(body: &[u8]) -> Result<T, APIError> {
+    #[cfg(feature = "simd-json")]
+    {
+        simd_json::serde::from_slice(&mut body.to_vec())
+            .map_err(|e| APIError::Decode(e.to_string()))
+    }
+    #[cfg(not(feature = "simd-json"))]
+    {
+        serde_json::from_slice(body).map_err(|e| APIError::Decode(e.to_string()))
+    }
+}
+
+/// Like `from_slice`, but fails on fields `T` does not model, as
+/// `#[serde(deny_unknown_fields)]` would.
+///
+/// Unknown fields are found by comparing the body to `T` serialized back,
+/// so `null` values are not reported: they cannot be told apart from an
+/// absent optional field.
+/// With the `extra-fields` feature, fields kept in an `extra` map are not
+/// reported either.
+pub fn from_slice_strict<T: DeserializeOwned + Serialize>(body: &[u8]) -> Result<T, APIError> {
+    let value: T = from_slice(body)?;
+    let raw: Value = serde_json::from_slice(body).map_err(|e| APIError::Decode(e.to_string()))?;
+    let known = serde_json::to_value(&value).map_err(|e| APIError::Decode(e.to_string()))?;
+    let mut unknown = Vec::new();
+    unknown_fields(&raw, &known, "", &mut unknown);
+    if !unknown.is_empty() {
+        return Err(APIError::Decode(format!(
+            "unknown field(s) in {}: {}",
+            std::any::type_name::<T>(),
+            unknown.join(", ")
+        )));
+    }
+    Ok(value)
+}
+
+fn unknown_fields(raw: &Value, known: &Value, path: &str, unknown: &mut Vec<String>) {
+    match (raw, known) {
+        (Value::Object(raw), Value::Object(known)) => {
+            for (key, value) in raw {
+                let field = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", path, key)
+                };
+                match known.get(key) {
+                    Some(known) => unknown_fields(value, known, &field, unknown),
+                    None if !value.is_null() => unknown.push(field),
+                    None => {}
+                }
+            }
+        }
+        (Value::Array(raw), Value::Array(known)) => {
+            for (i, (raw, known)) in raw.iter().zip(known).enumerate() {
+                unknown_fields(raw, known, &format!("{}[{}]", path, i), unknown);
+            }
+        }
+        _ => {}
+    }
+}
+
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Implemented by response types that keep the HTTP headers they were received with.
+pub trait ResponseHeaders {
+    fn headers(&self) -> Option<&HashMap<String, String>>;
+
+    fn set_headers(&mut self, headers: HashMap<String, String>);
+
+    fn rate_limit(&self) -> Option<RateLimitInfo> {
+        self.headers().and_then(RateLimitInfo::from_headers)
+    }
+
+    /// The server-assigned `x-request-id`, to quote when contacting support.
+    fn request_id(&self) -> Option<&str> {
+        self.headers()
+            .and_then(|headers| headers.get(REQUEST_ID_HEADER))
+            .map(String::as_str)
+    }
+}
+
+#[macro_export]
+macro_rules! impl_response_headers {
+    ($($response:ident),* $(,)?) => {
+        $(
+            // Deprecated responses keep their headers until they are removed.
+            #[allow(deprecated)]
+            impl $crate::v1::common::ResponseHeaders for $response {
+                fn headers(&self) -> Option<&std::collections::HashMap<String, String>> {
+                    self.headers.as_ref()
+                }
+
+                fn set_headers(&mut self, headers: std::collections::HashMap<String, String>) {
+                    self.headers = Some(headers);
+                }
+            }
+        )*
+    };
 }
 
 #[macro_export]
@@ -52,3 +486,167 @@ pub const DALL_E_3: &str = "dall-e-3";
 pub const TEXT_EMBEDDING_3_SMALL: &str = "text-embedding-3-small";
 pub const TEXT_EMBEDDING_3_LARGE: &str = "text-embedding-3-large";
 pub const TEXT_EMBEDDING_ADA_002: &str = "text-embedding-ada-002";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    fn unix(seconds: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(seconds)
+    }
+
+    #[test]
+    fn parses_http_dates() {
+        assert_eq!(
+            parse_http_date("Sun, 06 Nov 1994 08:49:37 GMT"),
+            Some(unix(784111777))
+        );
+        assert_eq!(
+            parse_http_date("Thu, 01 Jan 1970 00:00:00 GMT"),
+            Some(unix(0))
+        );
+        assert_eq!(
+            parse_http_date("Tue, 29 Feb 2000 12:00:00 GMT"),
+            Some(unix(951825600))
+        );
+        assert_eq!(
+            parse_http_date("Mon, 01 Mar 2100 00:00:00 GMT"),
+            Some(unix(4107542400))
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_http_dates() {
+        assert_eq!(parse_http_date("06 Nov 1994 08:49:37 GMT"), None);
+        assert_eq!(parse_http_date("Sun, 06 Foo 1994 08:49:37 GMT"), None);
+        assert_eq!(parse_http_date("Sun, 06 Nov 1994 08:49 GMT"), None);
+        assert_eq!(parse_http_date("Sun, 06 Nov 1994 08:49:37 PST"), None);
+        assert_eq!(parse_http_date("Sun, 06 Nov 1994 08:49:37"), None);
+        assert_eq!(parse_http_date("Wed, 31 Dec 1969 23:59:59 GMT"), None);
+    }
+
+    #[test]
+    fn retry_after_reads_seconds_and_milliseconds() {
+        assert_eq!(
+            retry_after(503, &headers(&[("retry-after", " 2 ")])),
+            Some(Duration::from_secs(2))
+        );
+        assert_eq!(
+            retry_after(503, &headers(&[("retry-after", "0.5")])),
+            Some(Duration::from_millis(500))
+        );
+        assert_eq!(
+            retry_after(
+                429,
+                &headers(&[("retry-after-ms", "1500"), ("retry-after", "9")])
+            ),
+            Some(Duration::from_millis(1500))
+        );
+        assert_eq!(retry_after(503, &headers(&[("retry-after", "-1")])), None);
+    }
+
+    #[test]
+    fn retry_after_reads_http_dates() {
+        let past = headers(&[("retry-after", "Sun, 06 Nov 1994 08:49:37 GMT")]);
+        assert_eq!(retry_after(503, &past), Some(Duration::ZERO));
+        let future = headers(&[("retry-after", "Mon, 01 Mar 2100 00:00:00 GMT")]);
+        let before = unix(4107542400).duration_since(SystemTime::now()).unwrap();
+        let wait = retry_after(503, &future).unwrap();
+        assert!(
+            wait <= before && before - wait < Duration::from_secs(5),
+            "{:?}",
+            wait
+        );
+        assert_eq!(retry_after(503, &headers(&[("retry-after", "soon")])), None);
+    }
+
+    #[test]
+    fn retry_after_falls_back_to_exhausted_rate_limits_on_429() {
+        let limits = headers(&[
+            ("x-ratelimit-remaining-requests", "0"),
+            ("x-ratelimit-reset-requests", "1m30s"),
+            ("x-ratelimit-remaining-tokens", "0"),
+            ("x-ratelimit-reset-tokens", "250ms"),
+        ]);
+        assert_eq!(retry_after(429, &limits), Some(Duration::from_secs(90)));
+        assert_eq!(retry_after(500, &limits), None);
+        let tokens_left = headers(&[
+            ("x-ratelimit-remaining-requests", "0"),
+            ("x-ratelimit-reset-requests", "6m0s"),
+            ("x-ratelimit-remaining-tokens", "10"),
+            ("x-ratelimit-reset-tokens", "1h"),
+        ]);
+        assert_eq!(
+            retry_after(429, &tokens_left),
+            Some(Duration::from_secs(360))
+        );
+        assert_eq!(retry_after(429, &HashMap::new()), None);
+    }
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    struct Outer {
+        a: i64,
+        inner: Option<Inner>,
+        #[serde(default)]
+        items: Vec<Inner>,
+    }
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    struct Inner {
+        b: String,
+    }
+
+    fn strict_error(body: &str) -> String {
+        match from_slice_strict::<Outer>(body.as_bytes()) {
+            Err(APIError::Decode(message)) => message,
+            other => panic!("expected a decode error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn strict_parsing_accepts_modelled_fields() {
+        let outer: Outer =
+            from_slice_strict(br#"{"a": 1, "inner": {"b": "x"}, "items": [{"b": "y"}]}"#).unwrap();
+        assert_eq!(
+            outer,
+            Outer {
+                a: 1,
+                inner: Some(Inner { b: "x".to_owned() }),
+                items: vec![Inner { b: "y".to_owned() }],
+            }
+        );
+        from_slice_strict::<Outer>(br#"{"a": 1}"#).unwrap();
+    }
+
+    #[test]
+    fn strict_parsing_reports_unknown_fields_with_their_path() {
+        let message = strict_error(
+            r#"{"a": 1, "c": true, "inner": {"b": "x", "d": 2}, "items": [{"b": "y"}, {"b": "z", "e": []}]}"#,
+        );
+        assert!(message.starts_with("unknown field(s) in "), "{}", message);
+        assert!(
+            message.ends_with("Outer: c, inner.d, items[1].e"),
+            "{}",
+            message
+        );
+    }
+
+    #[test]
+    fn strict_parsing_ignores_unknown_null_fields() {
+        from_slice_strict::<Outer>(br#"{"a": 1, "c": null, "inner": {"b": "x", "d": null}}"#)
+            .unwrap();
+    }
+
+    #[test]
+    fn strict_parsing_reports_invalid_bodies() {
+        assert!(!strict_error(r#"{"a": "one"}"#).starts_with("unknown field"));
+        assert!(!strict_error("{").starts_with("unknown field"));
+    }
+}
@@ -2,8 +2,8 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::option::Option;
 
-use crate::impl_builder_methods;
-use crate::v1::common;
+use crate::v1::common::{self, StopSequences};
+use crate::{impl_builder_methods, impl_response_headers};
 
 pub const GPT3_TEXT_DAVINCI_003: &str = "text-davinci-003";
 pub const GPT3_TEXT_DAVINCI_002: &str = "text-davinci-002";
@@ -50,6 +50,12 @@ pub struct CompletionRequest {
     pub logit_bias: Option<HashMap<String, i32>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub user: Option<String>,
+    /// Request timeout in seconds, overriding `Client::timeout`. Not sent to the API.
+    #[serde(skip)]
+    pub timeout: Option<u64>,
+    /// Extra HTTP headers for this request only. Not sent in the body.
+    #[serde(skip)]
+    pub extra_headers: Option<HashMap<String, String>>,
 }
 
 impl CompletionRequest {
@@ -71,6 +77,8 @@ impl CompletionRequest {
             best_of: None,
             logit_bias: None,
             user: None,
+            timeout: None,
+            extra_headers: None,
         }
     }
 }
@@ -90,9 +98,18 @@ impl_builder_methods!(
     frequency_penalty: f32,
     best_of: i32,
     logit_bias: HashMap<String, i32>,
-    user: String
+    user: String,
+    timeout: u64,
+    extra_headers: HashMap<String, String>
 );
 
+impl CompletionRequest {
+    pub fn stop_sequences(mut self, stop: StopSequences) -> Self {
+        self.stop = Some(stop.into());
+        self
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct CompletionChoice {
     pub text: String,
@@ -110,6 +127,7 @@ pub struct LogprobResult {
 }
 
 #[derive(Debug, Deserialize, Serialize)]
+#[non_exhaustive]
 pub struct CompletionResponse {
     pub id: String,
     pub object: String,
@@ -117,4 +135,8 @@ pub struct CompletionResponse {
     pub model: String,
     pub choices: Vec<CompletionChoice>,
     pub usage: common::Usage,
+    #[serde(skip)]
+    pub headers: Option<HashMap<String, String>>,
 }
+
+impl_response_headers!(CompletionResponse);
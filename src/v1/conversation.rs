@@ -0,0 +1,75 @@
+use crate::v1::chat_completion::{
+    ChatCompletionMessage, ChatCompletionRequest, ChatCompletionResponse, EmpowerMetadata,
+};
+use crate::v1::common::Usage;
+
+/// Message history of a chat, with the token usage of every reply so far.
+#[derive(Debug, Clone, Default)]
+pub struct Conversation {
+    pub messages: Vec<ChatCompletionMessage>,
+    pub usage: Usage,
+    /// The messages of the last request built by `empower_request`.
+    pub last_request: Vec<ChatCompletionMessage>,
+}
+
+impl Conversation {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_system<S: Into<String>>(text: S) -> Self {
+        let mut conversation = Self::new();
+        conversation.push(ChatCompletionMessage::system(text));
+        conversation
+    }
+
+    pub fn push(&mut self, message: ChatCompletionMessage) {
+        self.messages.push(message);
+    }
+
+    pub fn push_user<S: Into<String>>(&mut self, text: S) {
+        self.push(ChatCompletionMessage::user(text));
+    }
+
+    /// Appends the first choice of `response` and adds its usage to the total.
+    pub fn push_response(&mut self, response: &ChatCompletionResponse) {
+        if let Some(choice) = response.choices.first() {
+            self.push(choice.message.clone().into());
+        }
+        self.usage.accumulate(&response.usage);
+    }
+
+    /// A request carrying the whole history, ready to send to `model`.
+    pub fn request(&self, model: String) -> ChatCompletionRequest {
+        ChatCompletionRequest::new(model, self.messages.clone())
+    }
+
+    /// Number of leading messages unchanged since the last `empower_request`.
+    pub fn cached_prefix_len(&self) -> usize {
+        common_prefix_len(&self.last_request, &self.messages)
+    }
+
+    /// Like `request`, with `metadata.num_cached_prefix_messages` set to the
+    /// messages the server may still have cached from the previous request.
+    pub fn empower_request(
+        &mut self,
+        model: String,
+        mut metadata: EmpowerMetadata,
+    ) -> ChatCompletionRequest {
+        metadata.num_cached_prefix_messages = Some(self.cached_prefix_len());
+        self.last_request = self.messages.clone();
+        self.request(model).empower_metadata(metadata)
+    }
+}
+
+/// Length of the longest run of identical messages at the start of both lists.
+pub fn common_prefix_len(
+    previous: &[ChatCompletionMessage],
+    current: &[ChatCompletionMessage],
+) -> usize {
+    previous
+        .iter()
+        .zip(current)
+        .take_while(|(a, b)| a == b)
+        .count()
+}
@@ -0,0 +1,164 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::v1::common::Usage;
+
+/// Prices in USD per million tokens.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ModelPrice {
+    pub input: f64,
+    /// Price of prompt tokens served from the cache, when discounted.
+    pub cached_input: Option<f64>,
+    pub output: f64,
+}
+
+impl ModelPrice {
+    pub const fn new(input: f64, cached_input: Option<f64>, output: f64) -> Self {
+        Self {
+            input,
+            cached_input,
+            output,
+        }
+    }
+
+    pub fn cost(&self, usage: &Usage) -> f64 {
+        let prompt = usage.prompt_tokens.max(0) as f64;
+        let cached = usage
+            .prompt_tokens_details
+            .as_ref()
+            .and_then(|details| details.cached_tokens)
+            .unwrap_or(0)
+            .clamp(0, usage.prompt_tokens.max(0)) as f64;
+        let cached_price = self.cached_input.unwrap_or(self.input);
+        let completion = usage.completion_tokens.max(0) as f64;
+        ((prompt - cached) * self.input + cached * cached_price + completion * self.output)
+            / 1_000_000.0
+    }
+}
+
+/// List prices of OpenAI models, matched by the longest prefix of the model name
+/// so that dated snapshots such as `gpt-4o-2024-08-06` share the base price.
+/// Prices change; override them with `CostTracker::price` where it matters.
+pub const PRICES: &[(&str, ModelPrice)] = &[
+    ("gpt-5", ModelPrice::new(1.25, Some(0.125), 10.0)),
+    ("gpt-5-mini", ModelPrice::new(0.25, Some(0.025), 2.0)),
+    ("gpt-5-nano", ModelPrice::new(0.05, Some(0.005), 0.4)),
+    ("gpt-4.1", ModelPrice::new(2.0, Some(0.5), 8.0)),
+    ("gpt-4.1-mini", ModelPrice::new(0.4, Some(0.1), 1.6)),
+    ("gpt-4.1-nano", ModelPrice::new(0.1, Some(0.025), 0.4)),
+    ("gpt-4o", ModelPrice::new(2.5, Some(1.25), 10.0)),
+    ("gpt-4o-mini", ModelPrice::new(0.15, Some(0.075), 0.6)),
+    ("gpt-4.5", ModelPrice::new(75.0, Some(37.5), 150.0)),
+    ("o1", ModelPrice::new(15.0, Some(7.5), 60.0)),
+    ("o1-pro", ModelPrice::new(150.0, None, 600.0)),
+    ("o1-mini", ModelPrice::new(1.1, Some(0.55), 4.4)),
+    ("o3", ModelPrice::new(2.0, Some(0.5), 8.0)),
+    ("o3-mini", ModelPrice::new(1.1, Some(0.55), 4.4)),
+    ("o3-pro", ModelPrice::new(20.0, None, 80.0)),
+    ("o4-mini", ModelPrice::new(1.1, Some(0.275), 4.4)),
+    ("gpt-4-turbo", ModelPrice::new(10.0, None, 30.0)),
+    ("gpt-4-1106", ModelPrice::new(10.0, None, 30.0)),
+    ("gpt-4-0125", ModelPrice::new(10.0, None, 30.0)),
+    ("gpt-4-vision", ModelPrice::new(10.0, None, 30.0)),
+    ("gpt-4", ModelPrice::new(30.0, None, 60.0)),
+    ("gpt-4-32k", ModelPrice::new(60.0, None, 120.0)),
+    ("gpt-3.5-turbo", ModelPrice::new(0.5, None, 1.5)),
+    ("gpt-3.5-turbo-instruct", ModelPrice::new(1.5, None, 2.0)),
+    ("text-embedding-3-small", ModelPrice::new(0.02, None, 0.0)),
+    ("text-embedding-3-large", ModelPrice::new(0.13, None, 0.0)),
+    ("text-embedding-ada-002", ModelPrice::new(0.1, None, 0.0)),
+];
+
+/// The built-in price of `model`, if it is listed.
+pub fn price_for(model: &str) -> Option<ModelPrice> {
+    longest_prefix(PRICES.iter().map(|(name, price)| (*name, price)), model).copied()
+}
+
+/// Estimated cost in USD of a request to `model` with the given usage.
+pub fn estimate_cost(model: &str, usage: &Usage) -> Option<f64> {
+    price_for(model).map(|price| price.cost(usage))
+}
+
+fn longest_prefix<'a, I>(prices: I, model: &str) -> Option<&'a ModelPrice>
+where
+    I: Iterator<Item = (&'a str, &'a ModelPrice)>,
+{
+    prices
+        .filter(|(name, _)| model.starts_with(name))
+        .max_by_key(|(name, _)| name.len())
+        .map(|(_, price)| price)
+}
+
+/// Running totals kept by a `CostTracker`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CostSummary {
+    /// Estimated spend in USD over the priced requests.
+    pub total_cost: f64,
+    pub requests: u64,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    /// Requests to models without a known price; their tokens are counted but
+    /// not included in `total_cost`.
+    pub unpriced_requests: u64,
+}
+
+/// Adds up the estimated cost of every response a client receives.
+///
+/// Shared by reference between client clones, so one tracker can follow a
+/// whole batch job across threads.
+#[derive(Debug, Default)]
+pub struct CostTracker {
+    prices: HashMap<String, ModelPrice>,
+    summary: Mutex<CostSummary>,
+}
+
+impl CostTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the price for models starting with `model`, taking precedence over
+    /// the built-in table, e.g. for fine-tuned models or negotiated rates.
+    pub fn price<S: Into<String>>(mut self, model: S, price: ModelPrice) -> Self {
+        self.prices.insert(model.into(), price);
+        self
+    }
+
+    pub fn price_for(&self, model: &str) -> Option<ModelPrice> {
+        longest_prefix(
+            self.prices
+                .iter()
+                .map(|(name, price)| (name.as_str(), price)),
+            model,
+        )
+        .copied()
+        .or_else(|| price_for(model))
+    }
+
+    /// Adds a response to the totals and returns its estimated cost.
+    pub fn record(&self, model: &str, usage: &Usage) -> Option<f64> {
+        let cost = self.price_for(model).map(|price| price.cost(usage));
+        let mut summary = self.summary.lock().unwrap();
+        summary.requests += 1;
+        summary.prompt_tokens += usage.prompt_tokens.max(0) as u64;
+        summary.completion_tokens += usage.completion_tokens.max(0) as u64;
+        match cost {
+            Some(cost) => summary.total_cost += cost,
+            None => summary.unpriced_requests += 1,
+        }
+        cost
+    }
+
+    pub fn summary(&self) -> CostSummary {
+        self.summary.lock().unwrap().clone()
+    }
+
+    pub fn total_cost(&self) -> f64 {
+        self.summary.lock().unwrap().total_cost
+    }
+
+    /// Clears the totals and returns what they were.
+    pub fn reset(&self) -> CostSummary {
+        std::mem::take(&mut *self.summary.lock().unwrap())
+    }
+}
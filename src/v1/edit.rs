@@ -1,8 +1,9 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::option::Option;
 
-use crate::impl_builder_methods;
 use crate::v1::common;
+use crate::{impl_builder_methods, impl_response_headers};
 
 #[derive(Debug, Serialize, Clone)]
 pub struct EditRequest {
@@ -46,9 +47,14 @@ pub struct EditChoice {
 }
 
 #[derive(Debug, Deserialize, Serialize)]
+#[non_exhaustive]
 pub struct EditResponse {
     pub object: String,
     pub created: i64,
     pub usage: common::Usage,
     pub choices: Vec<EditChoice>,
+    #[serde(skip)]
+    pub headers: Option<HashMap<String, String>>,
 }
+
+impl_response_headers!(EditResponse);
@@ -0,0 +1,55 @@
+use std::cmp::Ordering;
+
+/// The dot product of two vectors of the same length, as embeddings from one
+/// model and `dimensions` are.
+pub fn dot(a: &[f32], b: &[f32]) -> f32 {
+    debug_assert_eq!(a.len(), b.len(), "vectors have different lengths");
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+/// The Euclidean length of `v`.
+pub fn norm(v: &[f32]) -> f32 {
+    dot(v, v).sqrt()
+}
+
+/// Cosine of the angle between `a` and `b`, from -1 to 1, or 0 when either is
+/// a zero vector. OpenAI embeddings are normalized, so for them this equals
+/// `dot`.
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    let norms = norm(a) * norm(b);
+    if norms == 0.0 {
+        return 0.0;
+    }
+    dot(a, b) / norms
+}
+
+/// Scales `v` to unit length in place, leaving zero vectors unchanged. Needed
+/// after truncating an embedding to fewer dimensions.
+pub fn normalize(v: &mut [f32]) {
+    let norm = norm(v);
+    if norm > 0.0 {
+        v.iter_mut().for_each(|x| *x /= norm);
+    }
+}
+
+/// The `k` candidates most similar to `query` by cosine similarity, as
+/// `(index, similarity)` pairs, most similar first.
+pub fn top_k(query: &[f32], candidates: &[Vec<f32>], k: usize) -> Vec<(usize, f32)> {
+    let mut scored: Vec<(usize, f32)> = candidates
+        .iter()
+        .map(|candidate| cosine_similarity(query, candidate))
+        .enumerate()
+        .collect();
+    let by_similarity = |a: &(usize, f32), b: &(usize, f32)| -> Ordering {
+        b.1.total_cmp(&a.1).then(a.0.cmp(&b.0))
+    };
+    if k < scored.len() {
+        if k == 0 {
+            return Vec::new();
+        }
+        scored.select_nth_unstable_by(k - 1, by_similarity);
+        scored.truncate(k);
+    }
+    scored.sort_by(by_similarity);
+    scored
+}
@@ -0,0 +1,255 @@
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde::{de, Deserialize, Deserializer, Serialize};
+use std::collections::HashMap;
+use std::option::Option;
+
+use crate::{impl_builder_methods, impl_response_headers};
+
+pub mod math;
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct EmbeddingData {
+    pub object: String,
+    #[serde(deserialize_with = "deserialize_embedding")]
+    pub embedding: Vec<f32>,
+    pub index: i32,
+}
+
+// With `encoding_format: base64` the vector arrives as little-endian f32 bytes.
+fn deserialize_embedding<'de, D>(deserializer: D) -> Result<Vec<f32>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Embedding {
+        Float(Vec<f32>),
+        Base64(String),
+    }
+
+    match Embedding::deserialize(deserializer)? {
+        Embedding::Float(values) => Ok(values),
+        Embedding::Base64(encoded) => {
+            let bytes = STANDARD.decode(encoded).map_err(de::Error::custom)?;
+            if bytes.len() % 4 != 0 {
+                return Err(de::Error::custom(
+                    "base64 embedding is not a whole number of f32 values",
+                ));
+            }
+            Ok(bytes
+                .chunks_exact(4)
+                .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+                .collect())
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[allow(non_camel_case_types)]
+pub enum EncodingFormat {
+    float,
+    base64,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum EmbeddingInput {
+    Text(String),
+    TextArray(Vec<String>),
+    Tokens(Vec<u32>),
+    TokensArray(Vec<Vec<u32>>),
+}
+
+impl From<String> for EmbeddingInput {
+    fn from(text: String) -> Self {
+        EmbeddingInput::Text(text)
+    }
+}
+
+impl From<&str> for EmbeddingInput {
+    fn from(text: &str) -> Self {
+        EmbeddingInput::Text(text.to_owned())
+    }
+}
+
+impl From<Vec<String>> for EmbeddingInput {
+    fn from(texts: Vec<String>) -> Self {
+        EmbeddingInput::TextArray(texts)
+    }
+}
+
+impl From<Vec<u32>> for EmbeddingInput {
+    fn from(tokens: Vec<u32>) -> Self {
+        EmbeddingInput::Tokens(tokens)
+    }
+}
+
+impl From<Vec<Vec<u32>>> for EmbeddingInput {
+    fn from(tokens: Vec<Vec<u32>>) -> Self {
+        EmbeddingInput::TokensArray(tokens)
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct EmbeddingRequest {
+    pub model: String,
+    pub input: EmbeddingInput,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dimensions: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub encoding_format: Option<EncodingFormat>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+}
+
+impl EmbeddingRequest {
+    pub fn new<I: Into<EmbeddingInput>>(model: String, input: I) -> Self {
+        Self {
+            model,
+            input: input.into(),
+            dimensions: None,
+            encoding_format: None,
+            user: None,
+        }
+    }
+}
+
+impl_builder_methods!(
+    EmbeddingRequest,
+    dimensions: i32,
+    encoding_format: EncodingFormat,
+    user: String
+);
+
+/// Most inputs one embeddings request may have.
+pub const MAX_INPUTS_PER_REQUEST: usize = 2048;
+/// Most tokens the inputs of one embeddings request may add up to.
+pub const MAX_TOKENS_PER_REQUEST: usize = 300_000;
+
+/// How `Client::embedding` splits requests with more inputs or tokens than
+/// one request may have into several, whose results are joined in order.
+/// Text is estimated at two bytes per token, which overestimates English
+/// but keeps non-Latin scripts and code within `max_tokens`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmbeddingBatching {
+    pub max_inputs: usize,
+    pub max_tokens: usize,
+    /// How many of the requests are sent at once.
+    pub max_concurrent: usize,
+}
+
+impl EmbeddingBatching {
+    pub fn new(max_concurrent: usize) -> Self {
+        Self {
+            max_concurrent,
+            ..Default::default()
+        }
+    }
+}
+
+impl Default for EmbeddingBatching {
+    fn default() -> Self {
+        Self {
+            max_inputs: MAX_INPUTS_PER_REQUEST,
+            max_tokens: MAX_TOKENS_PER_REQUEST,
+            max_concurrent: 1,
+        }
+    }
+}
+
+impl EmbeddingRequest {
+    /// Splits the inputs into requests within the limits of `batching`, in
+    /// order. A single input is never split.
+    pub fn split(self, batching: &EmbeddingBatching) -> Vec<EmbeddingRequest> {
+        let inputs: Vec<EmbeddingInput> = match &self.input {
+            EmbeddingInput::TextArray(texts) if texts.len() > 1 => {
+                batch(texts, batching, |text| text.len().div_ceil(2))
+                    .into_iter()
+                    .map(EmbeddingInput::TextArray)
+                    .collect()
+            }
+            EmbeddingInput::TokensArray(tokens) if tokens.len() > 1 => {
+                batch(tokens, batching, Vec::len)
+                    .into_iter()
+                    .map(EmbeddingInput::TokensArray)
+                    .collect()
+            }
+            _ => return vec![self],
+        };
+        if inputs.len() == 1 {
+            return vec![self];
+        }
+        inputs
+            .into_iter()
+            .map(|input| EmbeddingRequest {
+                input,
+                ..self.clone()
+            })
+            .collect()
+    }
+}
+
+fn batch<T: Clone>(
+    inputs: &[T],
+    batching: &EmbeddingBatching,
+    tokens: impl Fn(&T) -> usize,
+) -> Vec<Vec<T>> {
+    let mut batches: Vec<Vec<T>> = Vec::new();
+    let mut current = Vec::new();
+    let mut current_tokens = 0;
+    for input in inputs {
+        let input_tokens = tokens(input);
+        if !current.is_empty()
+            && (current.len() >= batching.max_inputs
+                || current_tokens + input_tokens > batching.max_tokens)
+        {
+            batches.push(std::mem::take(&mut current));
+            current_tokens = 0;
+        }
+        current.push(input.clone());
+        current_tokens += input_tokens;
+    }
+    batches.push(current);
+    batches
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct EmbeddingResponse {
+    pub object: String,
+    pub data: Vec<EmbeddingData>,
+    pub model: String,
+    pub usage: Usage,
+    #[serde(skip)]
+    pub headers: Option<HashMap<String, String>>,
+}
+
+impl EmbeddingResponse {
+    /// Joins the responses to the requests `EmbeddingRequest::split` made, in
+    /// the same order.
+    pub fn concat(responses: Vec<EmbeddingResponse>) -> Option<EmbeddingResponse> {
+        let mut responses = responses.into_iter();
+        let mut joined = responses.next()?;
+        for response in responses {
+            let offset = joined.data.len() as i32;
+            joined
+                .data
+                .extend(response.data.into_iter().map(|data| EmbeddingData {
+                    index: data.index + offset,
+                    ..data
+                }));
+            joined.usage.prompt_tokens += response.usage.prompt_tokens;
+            joined.usage.total_tokens += response.usage.total_tokens;
+        }
+        Some(joined)
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Usage {
+    pub prompt_tokens: i32,
+    pub total_tokens: i32,
+}
+
+impl_response_headers!(EmbeddingResponse);
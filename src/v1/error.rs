@@ -1,15 +1,212 @@
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
 use std::error::Error;
 use std::fmt;
+use std::time::Duration;
 
-#[derive(Debug)]
-pub struct APIError {
+use crate::v1::common::{self, REQUEST_ID_HEADER};
+use crate::v1::middleware::HttpResponse;
+
+/// The `error` object of an OpenAI error response.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct ErrorDetail {
     pub message: String,
+    #[serde(rename = "type", default)]
+    pub error_type: Option<String>,
+    #[serde(default)]
+    pub param: Option<String>,
+    // OpenAI sends a string code, vLLM sends the numeric HTTP status.
+    #[serde(default, deserialize_with = "deserialize_code")]
+    pub code: Option<String>,
+}
+
+impl ErrorDetail {
+    /// Parses `{"error": {...}}` bodies as well as the flat form used by vLLM.
+    pub fn from_body(body: &str) -> Option<Self> {
+        #[derive(Deserialize)]
+        struct Wrapper {
+            error: ErrorDetail,
+        }
+        serde_json::from_str::<Wrapper>(body)
+            .map(|w| w.error)
+            .or_else(|_| serde_json::from_str::<ErrorDetail>(body))
+            .ok()
+    }
+}
+
+fn deserialize_code<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(match Option::<Value>::deserialize(deserializer)? {
+        Some(Value::String(code)) => Some(code),
+        Some(Value::Null) | None => None,
+        Some(other) => Some(other.to_string()),
+    })
+}
+
+#[derive(Debug, Clone)]
+pub enum APIError {
+    /// The server answered with a non-2xx status.
+    Status {
+        status_code: i32,
+        detail: Option<Box<ErrorDetail>>,
+        body: String,
+        /// The `x-request-id` header, to quote when contacting support.
+        request_id: Option<String>,
+        /// How long the server asked to wait before retrying.
+        retry_after: Option<Duration>,
+    },
+    /// The request could not be sent or the connection broke.
+    Connection(String),
+    /// The response body did not match the expected type.
+    Decode(String),
+    /// Reading or writing a local file failed.
+    Io(String),
+    /// The request was rejected before it was sent.
+    InvalidRequest(String),
+    /// A structured output reply does not match the requested schema.
+    SchemaValidation {
+        content: String,
+        errors: Vec<String>,
+    },
+    /// A `ModerationGuard` flagged the request, which was not sent.
+    ContentBlocked {
+        categories: Vec<String>,
+        category_scores: HashMap<String, f64>,
+    },
+    /// Calling into Python failed, e.g. because `vllm` is not installed.
+    Python(String),
+}
+
+impl APIError {
+    pub fn from_status(status_code: i32, body: &str) -> Self {
+        APIError::Status {
+            status_code,
+            detail: ErrorDetail::from_body(body).map(Box::new),
+            body: body.to_owned(),
+            request_id: None,
+            retry_after: None,
+        }
+    }
+
+    pub fn from_response(response: &HttpResponse) -> Self {
+        let mut error = Self::from_status(response.status_code, &response.body_str());
+        if let APIError::Status {
+            request_id,
+            retry_after,
+            ..
+        } = &mut error
+        {
+            *request_id = response.headers.get(REQUEST_ID_HEADER).cloned();
+            *retry_after = common::retry_after(response.status_code, &response.headers);
+        }
+        error
+    }
+
+    pub fn status_code(&self) -> Option<i32> {
+        match self {
+            APIError::Status { status_code, .. } => Some(*status_code),
+            _ => None,
+        }
+    }
+
+    pub fn request_id(&self) -> Option<&str> {
+        match self {
+            APIError::Status { request_id, .. } => request_id.as_deref(),
+            _ => None,
+        }
+    }
+
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            APIError::Status { retry_after, .. } => *retry_after,
+            _ => None,
+        }
+    }
+
+    pub fn detail(&self) -> Option<&ErrorDetail> {
+        match self {
+            APIError::Status { detail, .. } => detail.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// Whether sending the same request again may succeed: rate limits, server
+    /// errors, timeouts and dropped connections.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            APIError::Status { status_code, .. } => {
+                matches!(status_code, 408 | 409 | 429) || *status_code >= 500
+            }
+            APIError::Connection(_) => true,
+            APIError::Decode(_)
+            | APIError::Io(_)
+            | APIError::InvalidRequest(_)
+            | APIError::SchemaValidation { .. }
+            | APIError::ContentBlocked { .. }
+            | APIError::Python(_) => false,
+        }
+    }
+
+    pub fn message(&self) -> String {
+        match self {
+            APIError::Status {
+                status_code,
+                detail,
+                body,
+                ..
+            } => match detail {
+                Some(detail) => format!("{}: {}", status_code, detail.message),
+                None => format!("{}: {}", status_code, body),
+            },
+            APIError::Connection(message)
+            | APIError::Decode(message)
+            | APIError::Io(message)
+            | APIError::InvalidRequest(message)
+            | APIError::Python(message) => message.clone(),
+            APIError::SchemaValidation { errors, .. } => {
+                format!("reply does not match the schema: {}", errors.join("; "))
+            }
+            APIError::ContentBlocked { categories, .. } => {
+                format!("content flagged by moderation: {}", categories.join(", "))
+            }
+        }
+    }
 }
 
 impl fmt::Display for APIError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "APIError: {}", self.message)
+        write!(f, "APIError: {}", self.message())?;
+        if let Some(request_id) = self.request_id() {
+            write!(f, " (request id: {})", request_id)?;
+        }
+        Ok(())
     }
 }
 
 impl Error for APIError {}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl From<minreq::Error> for APIError {
+    fn from(err: minreq::Error) -> Self {
+        match err {
+            minreq::Error::SerdeJsonError(e) => APIError::Decode(e.to_string()),
+            e => APIError::Connection(e.to_string()),
+        }
+    }
+}
+
+impl From<std::io::Error> for APIError {
+    fn from(err: std::io::Error) -> Self {
+        APIError::Io(err.to_string())
+    }
+}
+
+#[cfg(feature = "vllm")]
+impl From<pyo3::PyErr> for APIError {
+    fn from(err: pyo3::PyErr) -> Self {
+        APIError::Python(err.to_string())
+    }
+}
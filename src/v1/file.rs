@@ -1,23 +1,30 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::impl_response_headers;
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct FileData {
     pub id: String,
-    pub oejct: String,
-    pub bytes: i32,
+    pub object: String,
+    pub bytes: i64,
     pub created_at: i64,
     pub filename: String,
     pub purpose: String,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
+#[non_exhaustive]
 pub struct FileListResponse {
     pub object: String,
     pub data: Vec<FileData>,
+    #[serde(skip)]
+    pub headers: Option<HashMap<String, String>>,
 }
 
 #[derive(Debug, Serialize)]
 pub struct FileUploadRequest {
+    /// Path of the file to upload.
     pub file: String,
     pub purpose: String,
 }
@@ -29,13 +36,16 @@ impl FileUploadRequest {
 }
 
 #[derive(Debug, Deserialize, Serialize)]
+#[non_exhaustive]
 pub struct FileUploadResponse {
     pub id: String,
-    pub oejct: String,
-    pub bytes: i32,
+    pub object: String,
+    pub bytes: i64,
     pub created_at: i64,
     pub filename: String,
     pub purpose: String,
+    #[serde(skip)]
+    pub headers: Option<HashMap<String, String>>,
 }
 
 #[derive(Debug, Serialize)]
@@ -50,10 +60,13 @@ impl FileDeleteRequest {
 }
 
 #[derive(Debug, Deserialize, Serialize)]
+#[non_exhaustive]
 pub struct FileDeleteResponse {
     pub id: String,
-    pub oejct: String,
-    pub delete: bool,
+    pub object: String,
+    pub deleted: bool,
+    #[serde(skip)]
+    pub headers: Option<HashMap<String, String>>,
 }
 
 #[derive(Debug, Serialize)]
@@ -68,13 +81,16 @@ impl FileRetrieveRequest {
 }
 
 #[derive(Debug, Deserialize, Serialize)]
+#[non_exhaustive]
 pub struct FileRetrieveResponse {
     pub id: String,
-    pub oejct: String,
-    pub bytes: i32,
+    pub object: String,
+    pub bytes: i64,
     pub created_at: i64,
     pub filename: String,
     pub purpose: String,
+    #[serde(skip)]
+    pub headers: Option<HashMap<String, String>>,
 }
 
 #[derive(Debug, Serialize)]
@@ -88,12 +104,17 @@ impl FileRetrieveContentRequest {
     }
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug)]
+#[non_exhaustive]
 pub struct FileRetrieveContentResponse {
-    pub id: String,
-    pub oejct: String,
-    pub bytes: i32,
-    pub created_at: i64,
-    pub filename: String,
-    pub purpose: String,
+    pub content: Vec<u8>,
+    pub headers: Option<HashMap<String, String>>,
 }
+
+impl_response_headers!(
+    FileListResponse,
+    FileUploadResponse,
+    FileDeleteResponse,
+    FileRetrieveResponse,
+    FileRetrieveContentResponse,
+);
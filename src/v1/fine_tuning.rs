@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
-use crate::impl_builder_methods;
+use crate::v1::common::ResponseHeaders;
+use crate::{impl_builder_methods, impl_response_headers};
 
 #[derive(Debug, Serialize, Clone)]
 pub struct CreateFineTuningJobRequest {
@@ -98,9 +100,12 @@ pub struct FineTuningPagination<T> {
     pub object: String,
     pub data: Vec<T>,
     pub has_more: bool,
+    #[serde(skip)]
+    pub headers: Option<HashMap<String, String>>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
+#[non_exhaustive]
 pub struct FineTuningJobObject {
     pub id: String,
     pub created_at: i64,
@@ -116,6 +121,8 @@ pub struct FineTuningJobObject {
     pub trained_tokens: Option<i64>,
     pub training_file: String,
     pub validation_file: Option<String>,
+    #[serde(skip)]
+    pub headers: Option<HashMap<String, String>>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -143,3 +150,15 @@ pub struct HyperParameters {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub n_epochs: Option<String>,
 }
+
+impl_response_headers!(FineTuningJobObject);
+
+impl<T> ResponseHeaders for FineTuningPagination<T> {
+    fn headers(&self) -> Option<&HashMap<String, String>> {
+        self.headers.as_ref()
+    }
+
+    fn set_headers(&mut self, headers: HashMap<String, String>) {
+        self.headers = Some(headers);
+    }
+}
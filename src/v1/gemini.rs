@@ -0,0 +1,450 @@
+use serde_json::{Map, Value};
+
+use crate::impl_builder_methods;
+use crate::v1::error::APIError;
+use crate::v1::middleware::{HttpRequest, HttpResponse, Method, Middleware};
+
+pub const GEMINI_API_URL: &str = "https://generativelanguage.googleapis.com/v1beta/openai";
+
+/// Chat request fields Gemini's OpenAI-compatible endpoint accepts. Any
+/// other field fails the whole request.
+pub const SUPPORTED_FIELDS: &[&str] = &[
+    "model",
+    "messages",
+    "temperature",
+    "top_p",
+    "n",
+    "stream",
+    "stream_options",
+    "stop",
+    "max_tokens",
+    "max_completion_tokens",
+    "reasoning_effort",
+    "presence_penalty",
+    "frequency_penalty",
+    "response_format",
+    "seed",
+    "tools",
+    "tool_choice",
+    "modalities",
+    "audio",
+    "extra_body",
+];
+
+/// The JSON schema keywords Gemini accepts in response and tool schemas.
+pub const SUPPORTED_SCHEMA_KEYWORDS: &[&str] = &[
+    "type",
+    "format",
+    "title",
+    "description",
+    "nullable",
+    "enum",
+    "properties",
+    "required",
+    "propertyOrdering",
+    "items",
+    "minItems",
+    "maxItems",
+    "minProperties",
+    "maxProperties",
+    "minLength",
+    "maxLength",
+    "pattern",
+    "minimum",
+    "maximum",
+    "anyOf",
+];
+
+/// Middleware adapting chat requests to Gemini's OpenAI-compatible endpoint,
+/// `GEMINI_API_URL`.
+///
+/// Fields outside `SUPPORTED_FIELDS`, such as `logit_bias`, `store` or the
+/// vLLM extensions, are dropped. Schemas are rewritten into the subset Gemini
+/// takes: `$ref`s are inlined, `oneOf` becomes `anyOf`, `const` a one-value
+/// `enum` and a `null` type `nullable`, and other keywords such as
+/// `additionalProperties` and `default` are dropped. Schemas that cannot be
+/// rewritten without changing their meaning, such as recursive ones or ones
+/// with both `oneOf` and `anyOf`, fail with `APIError::InvalidRequest`. `tool_choice: "any"`
+/// is sent as `"required"`, and `developer` messages as `system` ones.
+///
+/// With `reject_unsupported`, requests that would lose a field or schema
+/// keyword fail with `APIError::InvalidRequest` naming them instead.
+///
+/// ```no_run
+/// use openai_api_rs::v1::api::Client;
+/// use openai_api_rs::v1::gemini::{GeminiCompat, GEMINI_API_URL};
+///
+/// let client = Client::new_with_endpoint(GEMINI_API_URL.to_owned(), "AIza...".to_owned())
+///     .with_middleware(GeminiCompat::new());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GeminiCompat {
+    pub reject_unsupported: Option<bool>,
+}
+
+impl GeminiCompat {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Rewrites a chat request body for Gemini, returning the paths of the
+    /// fields and schema keywords it dropped.
+    pub fn adapt(&self, body: &mut Value) -> Result<Vec<String>, APIError> {
+        let body = match body.as_object_mut() {
+            Some(body) => body,
+            None => return Ok(Vec::new()),
+        };
+        let mut dropped: Vec<String> = body
+            .keys()
+            .filter(|key| !SUPPORTED_FIELDS.contains(&key.as_str()))
+            .cloned()
+            .collect();
+        body.retain(|key, _| SUPPORTED_FIELDS.contains(&key.as_str()));
+
+        if let Some(Value::Array(messages)) = body.get_mut("messages") {
+            for message in messages {
+                if message.get("role").and_then(Value::as_str) == Some("developer") {
+                    message["role"] = Value::from("system");
+                }
+            }
+        }
+        match body.get_mut("tool_choice") {
+            Some(choice) if choice.as_str() == Some("any") => *choice = Value::from("required"),
+            // Only the name picks the function; the rest of it is rejected.
+            Some(Value::Object(choice)) => {
+                if let Some(Value::Object(function)) = choice.get_mut("function") {
+                    function.retain(|key, _| key == "name");
+                }
+            }
+            _ => {}
+        }
+        if let Some(Value::Array(tools)) = body.get_mut("tools") {
+            for (i, tool) in tools.iter_mut().enumerate() {
+                let function = match tool.get_mut("function").and_then(Value::as_object_mut) {
+                    Some(function) => function,
+                    None => continue,
+                };
+                let name = function
+                    .get("name")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default()
+                    .to_owned();
+                if function.remove("strict").is_some() {
+                    dropped.push(format!("tools[{}].function.strict", i));
+                }
+                if let Some(parameters) = function.get_mut("parameters") {
+                    adapt_schema(parameters, &format!("{}.parameters", name), &mut dropped)?;
+                }
+            }
+        }
+        if let Some(Value::Object(json_schema)) = body
+            .get_mut("response_format")
+            .and_then(|format| format.get_mut("json_schema"))
+        {
+            if json_schema.remove("strict").is_some() {
+                dropped.push("response_format.json_schema.strict".to_owned());
+            }
+            if let Some(schema) = json_schema.get_mut("schema") {
+                adapt_schema(schema, "response_format.json_schema.schema", &mut dropped)?;
+            }
+        }
+
+        if self.reject_unsupported == Some(true) && !dropped.is_empty() {
+            return Err(APIError::InvalidRequest(format!(
+                "not supported by Gemini: {}",
+                dropped.join(", ")
+            )));
+        }
+        Ok(dropped)
+    }
+}
+
+impl_builder_methods!(GeminiCompat, reject_unsupported: bool);
+
+impl Middleware for GeminiCompat {
+    fn on_request(&self, request: &mut HttpRequest) -> Result<Option<HttpResponse>, APIError> {
+        let path = request.url.split('?').next().unwrap_or_default();
+        if request.method != Method::Post || !path.ends_with("/chat/completions") {
+            return Ok(None);
+        }
+        let mut body: Value = match request.body.as_deref().map(serde_json::from_slice) {
+            Some(Ok(body)) => body,
+            _ => return Ok(None),
+        };
+        let _dropped = self.adapt(&mut body)?;
+        #[cfg(feature = "tracing")]
+        if !_dropped.is_empty() {
+            tracing::debug!(dropped = ?_dropped, "dropped fields Gemini does not support");
+        }
+        request.body =
+            Some(serde_json::to_vec(&body).map_err(|e| APIError::Decode(e.to_string()))?);
+        Ok(None)
+    }
+}
+
+fn adapt_schema(schema: &mut Value, path: &str, dropped: &mut Vec<String>) -> Result<(), APIError> {
+    let defs = match schema.as_object_mut() {
+        Some(root) => {
+            let mut defs = Map::new();
+            for key in ["$defs", "definitions"] {
+                if let Some(Value::Object(found)) = root.remove(key) {
+                    defs.extend(
+                        found
+                            .into_iter()
+                            .map(|(name, def)| (format!("#/{}/{}", key, name), def)),
+                    );
+                }
+            }
+            defs
+        }
+        None => return Ok(()),
+    };
+    rewrite_schema(schema, path, &defs, &mut Vec::new(), dropped)
+}
+
+fn rewrite_schema(
+    schema: &mut Value,
+    path: &str,
+    defs: &Map<String, Value>,
+    resolving: &mut Vec<String>,
+    dropped: &mut Vec<String>,
+) -> Result<(), APIError> {
+    let object = match schema.as_object_mut() {
+        Some(object) => object,
+        None => return Ok(()),
+    };
+    // Gemini has no references, so definitions are copied in place, which
+    // recursive ones cannot be. A definition may itself be a reference, so
+    // this repeats until none is left.
+    let depth = resolving.len();
+    while let Some(reference) = object.remove("$ref") {
+        let reference = match reference {
+            Value::String(reference) => reference,
+            reference => {
+                return Err(APIError::InvalidRequest(format!(
+                    "{}: invalid schema reference {}",
+                    path, reference
+                )))
+            }
+        };
+        if resolving.contains(&reference) {
+            return Err(APIError::InvalidRequest(format!(
+                "{}: recursive schema {} is not supported by Gemini",
+                path, reference
+            )));
+        }
+        match defs.get(&reference) {
+            Some(Value::Object(resolved)) => {
+                for (key, value) in resolved {
+                    object.entry(key.clone()).or_insert_with(|| value.clone());
+                }
+            }
+            _ => {
+                return Err(APIError::InvalidRequest(format!(
+                    "{}: unresolved schema reference {}",
+                    path, reference
+                )))
+            }
+        }
+        resolving.push(reference);
+    }
+    if let Some(variants) = object.remove("oneOf") {
+        if object.contains_key("anyOf") {
+            return Err(APIError::InvalidRequest(format!(
+                "{}: a schema with both oneOf and anyOf is not supported by Gemini",
+                path
+            )));
+        }
+        object.insert("anyOf".to_owned(), variants);
+    }
+    if let Some(value) = object.remove("const") {
+        object.insert("enum".to_owned(), Value::Array(vec![value]));
+    }
+    if let Some(Value::Array(types)) = object.get("type").cloned() {
+        object.remove("type");
+        let nullable = types.iter().any(|t| t.as_str() == Some("null"));
+        let mut types: Vec<Value> = types
+            .into_iter()
+            .filter(|t| t.as_str() != Some("null"))
+            .collect();
+        if nullable {
+            object.insert("nullable".to_owned(), Value::Bool(true));
+        }
+        match types.len() {
+            0 => {}
+            1 => {
+                object.insert("type".to_owned(), types.remove(0));
+            }
+            _ if object.contains_key("anyOf") => {
+                return Err(APIError::InvalidRequest(format!(
+                    "{}: a schema with both several types and anyOf is not supported by Gemini",
+                    path
+                )))
+            }
+            _ => {
+                let variants = types
+                    .into_iter()
+                    .map(|t| serde_json::json!({ "type": t }))
+                    .collect();
+                object.insert("anyOf".to_owned(), Value::Array(variants));
+            }
+        }
+    }
+
+    let unsupported: Vec<String> = object
+        .keys()
+        .filter(|key| !SUPPORTED_SCHEMA_KEYWORDS.contains(&key.as_str()))
+        .cloned()
+        .collect();
+    for key in unsupported {
+        object.remove(&key);
+        dropped.push(format!("{}.{}", path, key));
+    }
+
+    if let Some(Value::Object(properties)) = object.get_mut("properties") {
+        for (name, property) in properties.iter_mut() {
+            rewrite_schema(
+                property,
+                &format!("{}.{}", path, name),
+                defs,
+                resolving,
+                dropped,
+            )?;
+        }
+    }
+    if let Some(items) = object.get_mut("items") {
+        rewrite_schema(items, &format!("{}[]", path), defs, resolving, dropped)?;
+    }
+    if let Some(Value::Array(variants)) = object.get_mut("anyOf") {
+        for (i, variant) in variants.iter_mut().enumerate() {
+            rewrite_schema(
+                variant,
+                &format!("{}.anyOf[{}]", path, i),
+                defs,
+                resolving,
+                dropped,
+            )?;
+        }
+    }
+    resolving.truncate(depth);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn adapt_response_schema(schema: Value) -> Result<Value, APIError> {
+        let mut body = json!({
+            "model": "gemini-2.0-flash",
+            "messages": [],
+            "response_format": {
+                "type": "json_schema",
+                "json_schema": { "name": "answer", "schema": schema },
+            },
+        });
+        GeminiCompat::new().adapt(&mut body)?;
+        Ok(body["response_format"]["json_schema"]["schema"].take())
+    }
+
+    fn invalid_request(result: Result<Value, APIError>) -> String {
+        match result {
+            Err(APIError::InvalidRequest(message)) => message,
+            other => panic!("expected InvalidRequest, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn inlines_references_inside_definitions() {
+        let schema = adapt_response_schema(json!({
+            "type": "object",
+            "properties": {
+                "home": { "$ref": "#/$defs/Home" },
+                "work": { "$ref": "#/$defs/Place" },
+            },
+            "$defs": {
+                "Home": { "$ref": "#/$defs/Place", "description": "Where they live" },
+                "Place": {
+                    "type": "object",
+                    "properties": { "city": { "$ref": "#/definitions/City" } },
+                },
+            },
+            "definitions": { "City": { "type": "string" } },
+        }))
+        .unwrap();
+        assert_eq!(
+            schema["properties"]["home"],
+            json!({
+                "type": "object",
+                "description": "Where they live",
+                "properties": { "city": { "type": "string" } },
+            })
+        );
+        assert_eq!(
+            schema["properties"]["work"]["properties"]["city"],
+            json!({ "type": "string" })
+        );
+    }
+
+    #[test]
+    fn rejects_recursive_definitions() {
+        let message = invalid_request(adapt_response_schema(json!({
+            "$ref": "#/$defs/Node",
+            "$defs": {
+                "Node": {
+                    "type": "object",
+                    "properties": { "next": { "$ref": "#/$defs/Next" } },
+                },
+                "Next": { "$ref": "#/$defs/Node" },
+            },
+        })));
+        assert!(
+            message.contains("recursive schema #/$defs/Node"),
+            "{}",
+            message
+        );
+    }
+
+    #[test]
+    fn rejects_unresolved_references() {
+        let message = invalid_request(adapt_response_schema(json!({ "$ref": "#/$defs/Missing" })));
+        assert!(message.contains("unresolved"), "{}", message);
+    }
+
+    #[test]
+    fn one_of_becomes_any_of() {
+        let schema = adapt_response_schema(json!({
+            "oneOf": [{ "type": "string" }, { "const": 1 }],
+        }))
+        .unwrap();
+        assert_eq!(
+            schema,
+            json!({ "anyOf": [{ "type": "string" }, { "enum": [1] }] })
+        );
+    }
+
+    #[test]
+    fn rejects_one_of_with_any_of() {
+        let message = invalid_request(adapt_response_schema(json!({
+            "oneOf": [{ "type": "string" }, { "type": "integer" }],
+            "anyOf": [{ "minLength": 1 }, { "minimum": 1 }],
+        })));
+        assert!(message.contains("both oneOf and anyOf"), "{}", message);
+    }
+
+    #[test]
+    fn rejects_several_types_with_any_of() {
+        let message = invalid_request(adapt_response_schema(json!({
+            "type": ["string", "integer"],
+            "anyOf": [{ "minLength": 1 }, { "minimum": 1 }],
+        })));
+        assert!(message.contains("several types and anyOf"), "{}", message);
+    }
+
+    #[test]
+    fn nullable_types() {
+        let schema = adapt_response_schema(json!({ "type": ["string", "null"] })).unwrap();
+        assert_eq!(schema, json!({ "type": "string", "nullable": true }));
+    }
+}
@@ -1,7 +1,8 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::option::Option;
 
-use crate::impl_builder_methods;
+use crate::{impl_builder_methods, impl_response_headers};
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct ImageData {
@@ -46,14 +47,19 @@ impl_builder_methods!(
 );
 
 #[derive(Debug, Deserialize, Serialize)]
+#[non_exhaustive]
 pub struct ImageGenerationResponse {
     pub created: i64,
     pub data: Vec<ImageData>,
+    #[serde(skip)]
+    pub headers: Option<HashMap<String, String>>,
 }
 
 #[derive(Debug, Serialize, Clone)]
 pub struct ImageEditRequest {
+    /// Path of the PNG image to upload.
     pub image: String,
+    /// Path of a PNG whose transparent areas mark where `image` should be edited.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub mask: Option<String>,
     pub prompt: String,
@@ -95,13 +101,17 @@ impl_builder_methods!(
 );
 
 #[derive(Debug, Deserialize, Serialize)]
+#[non_exhaustive]
 pub struct ImageEditResponse {
     pub created: i64,
     pub data: Vec<ImageData>,
+    #[serde(skip)]
+    pub headers: Option<HashMap<String, String>>,
 }
 
 #[derive(Debug, Serialize, Clone)]
 pub struct ImageVariationRequest {
+    /// Path of the PNG image to upload.
     pub image: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub n: Option<i32>,
@@ -138,7 +148,16 @@ impl_builder_methods!(
 );
 
 #[derive(Debug, Deserialize, Serialize)]
+#[non_exhaustive]
 pub struct ImageVariationResponse {
     pub created: i64,
     pub data: Vec<ImageData>,
+    #[serde(skip)]
+    pub headers: Option<HashMap<String, String>>,
 }
+
+impl_response_headers!(
+    ImageGenerationResponse,
+    ImageEditResponse,
+    ImageVariationResponse
+);
@@ -0,0 +1,214 @@
+/// Best-effort fix-up of almost-JSON produced by a model.
+///
+/// Handles markdown code fences, an escaped object sent without its
+/// surrounding quotes, single-quoted strings, raw newlines inside strings,
+/// trailing commas, and output cut off mid-way: open strings and brackets are
+/// closed and a dangling key is dropped. Valid JSON is returned unchanged.
+pub fn repair_json(text: &str) -> String {
+    let text = strip_code_fence(text);
+    if serde_json::from_str::<serde_json::Value>(text).is_ok() {
+        return text.to_owned();
+    }
+    let text = unescape(text).unwrap_or_else(|| text.to_owned());
+
+    let mut out = String::with_capacity(text.len() + 8);
+    let mut stack: Vec<Container> = Vec::new();
+    // Quote character of the string being read, if any.
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+    let mut key_start: Option<usize> = None;
+
+    for c in text.chars() {
+        if let Some(q) = quote {
+            if escaped {
+                escaped = false;
+                // `\'` is not a JSON escape; the quote needs none.
+                if c == '\'' {
+                    out.pop();
+                }
+                out.push(c);
+            } else if c == '\\' {
+                escaped = true;
+                out.push(c);
+            } else if c == q {
+                quote = None;
+                out.push('"');
+            } else if c == '"' {
+                out.push_str("\\\"");
+            } else if c == '\n' {
+                out.push_str("\\n");
+            } else if c == '\r' {
+                out.push_str("\\r");
+            } else if c == '\t' {
+                out.push_str("\\t");
+            } else {
+                out.push(c);
+            }
+            continue;
+        }
+        match c {
+            '"' | '\'' => {
+                if let Some(Container::Object { expect_key: true }) = stack.last() {
+                    key_start = Some(out.len());
+                }
+                quote = Some(c);
+                out.push('"');
+            }
+            '{' => {
+                stack.push(Container::Object { expect_key: true });
+                out.push(c);
+            }
+            '[' => {
+                stack.push(Container::Array);
+                out.push(c);
+            }
+            '}' | ']' => {
+                trim_trailing_comma(&mut out);
+                stack.pop();
+                key_start = None;
+                out.push(c);
+            }
+            ',' => {
+                if let Some(Container::Object { expect_key }) = stack.last_mut() {
+                    *expect_key = true;
+                }
+                key_start = None;
+                out.push(c);
+            }
+            ':' => {
+                if let Some(Container::Object { expect_key }) = stack.last_mut() {
+                    *expect_key = false;
+                }
+                out.push(c);
+            }
+            _ => out.push(c),
+        }
+    }
+
+    // Close whatever the text was cut off in the middle of.
+    if quote.is_some() {
+        if escaped {
+            out.pop();
+        }
+        out.push('"');
+    }
+    while let Some(container) = stack.pop() {
+        let trimmed = out.trim_end().len();
+        out.truncate(trimmed);
+        if let Container::Object { expect_key } = container {
+            // A key without a value, or a colon with nothing after it.
+            if expect_key || out.ends_with(':') {
+                if let Some(start) = key_start.take() {
+                    out.truncate(start);
+                } else if out.ends_with(':') {
+                    out.pop();
+                }
+            }
+        }
+        trim_trailing_comma(&mut out);
+        out.push(match container {
+            Container::Object { .. } => '}',
+            Container::Array => ']',
+        });
+        key_start = None;
+    }
+    out
+}
+
+enum Container {
+    Object { expect_key: bool },
+    Array,
+}
+
+fn trim_trailing_comma(out: &mut String) {
+    let trimmed = out.trim_end().len();
+    if out[..trimmed].ends_with(',') {
+        out.truncate(trimmed - 1);
+    }
+}
+
+// `{\"a\": 1}` is an object that was escaped for embedding in a string.
+fn unescape(text: &str) -> Option<String> {
+    if !(text.starts_with("{\\\"") || text.starts_with("[\\\"")) {
+        return None;
+    }
+    serde_json::from_str(&format!("\"{}\"", text)).ok()
+}
+
+pub(crate) fn strip_code_fence(text: &str) -> &str {
+    let text = text.trim();
+    match text.strip_prefix("```") {
+        Some(rest) => {
+            // Drop the language tag on the opening line, e.g. ```json.
+            let rest = rest.split_once('\n').map_or("", |(_, body)| body);
+            rest.trim_end().trim_end_matches("```").trim()
+        }
+        None => text,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_repairs(input: &str, expected: &str) {
+        let repaired = repair_json(input);
+        assert_eq!(repaired, expected, "repairing {:?}", input);
+        serde_json::from_str::<serde_json::Value>(&repaired).unwrap();
+    }
+
+    #[test]
+    fn leaves_valid_json_unchanged() {
+        assert_repairs(
+            "{\"a\": [1, 2], \"b\": \"x, y\"}",
+            "{\"a\": [1, 2], \"b\": \"x, y\"}",
+        );
+        assert_repairs("  [true]\n", "[true]");
+    }
+
+    #[test]
+    fn strips_code_fences() {
+        assert_eq!(strip_code_fence("```json\n{\"a\":1}\n```"), "{\"a\":1}");
+        assert_eq!(strip_code_fence("```\n[1]\n```  \n"), "[1]");
+        assert_eq!(strip_code_fence("```json\n{\"a\":1}"), "{\"a\":1}");
+        assert_eq!(strip_code_fence("```json"), "");
+        assert_eq!(strip_code_fence("  {\"a\": \"```\"}  "), "{\"a\": \"```\"}");
+        assert_repairs("```json\n{\"a\": 1,}\n```", "{\"a\": 1}");
+    }
+
+    #[test]
+    fn converts_single_quoted_strings() {
+        assert_repairs("{'a': 'it\\'s \"x\"'}", "{\"a\": \"it's \\\"x\\\"\"}");
+    }
+
+    #[test]
+    fn escapes_control_characters_in_strings() {
+        assert_repairs("{\"a\": \"x\ny\tz\r\"}", "{\"a\": \"x\\ny\\tz\\r\"}");
+    }
+
+    #[test]
+    fn drops_trailing_commas() {
+        assert_repairs("{\"a\": [1, 2,], }", "{\"a\": [1, 2]}");
+    }
+
+    #[test]
+    fn unescapes_an_escaped_object() {
+        assert_repairs("{\\\"a\\\": 1}", "{\"a\": 1}");
+    }
+
+    #[test]
+    fn closes_truncated_output() {
+        assert_repairs("{\"a\": \"hel", "{\"a\": \"hel\"}");
+        assert_repairs("{\"a\": [1, {\"b\": 2", "{\"a\": [1, {\"b\": 2}]}");
+        assert_repairs("{\"a\": \"x\\", "{\"a\": \"x\"}");
+        assert_repairs("[1, 2, ", "[1, 2]");
+    }
+
+    #[test]
+    fn drops_a_dangling_key() {
+        assert_repairs("{\"a\": 1, \"b", "{\"a\": 1}");
+        assert_repairs("{\"a\": 1, \"b\"", "{\"a\": 1}");
+        assert_repairs("{\"a\": 1, \"b\":", "{\"a\": 1}");
+        assert_repairs("{\"a\": {\"b\": ", "{\"a\": {}}");
+    }
+}
@@ -0,0 +1,204 @@
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::Arc;
+
+use crate::v1::error::APIError;
+use crate::v1::middleware::{HttpRequest, HttpResponse, Middleware};
+
+/// Headers whose values are never logged.
+pub const SECRET_HEADERS: &[&str] = &["authorization", "api-key", "cookie", "set-cookie"];
+
+/// Body fields holding user or model text, affected by `Redaction`.
+pub const CONTENT_FIELDS: &[&str] = &[
+    "content",
+    "reasoning_content",
+    "prompt",
+    "input",
+    "instructions",
+    "text",
+    "arguments",
+];
+
+pub type LogSink = Arc<dyn Fn(&str) + Send + Sync>;
+
+/// What `RequestLogger` does with the text in `CONTENT_FIELDS`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Redaction {
+    /// Logs the text as is.
+    #[default]
+    Keep,
+    /// Replaces the text with `[redacted]`.
+    Redact,
+    /// Replaces the text with a hash and its length, so equal values can
+    /// still be matched within a log.
+    Hash,
+}
+
+/// Middleware logging every request and response, for debugging what is
+/// actually sent to and received from the server.
+///
+/// Secret headers are always redacted; message text only with `redact_content`.
+/// Streaming response bodies are not logged, since the caller consumes them.
+/// Lines go to stderr unless a `sink` is set.
+///
+/// ```no_run
+/// use openai_api_rs::v1::api::Client;
+/// use openai_api_rs::v1::logging::{Redaction, RequestLogger};
+///
+/// let client = Client::new("sk-...".to_owned())
+///     .with_middleware(RequestLogger::new().redact_content(Redaction::Hash));
+/// ```
+#[derive(Clone)]
+pub struct RequestLogger {
+    redaction: Redaction,
+    sink: LogSink,
+}
+
+impl RequestLogger {
+    pub fn new() -> Self {
+        Self {
+            redaction: Redaction::Keep,
+            sink: Arc::new(|line| eprintln!("{}", line)),
+        }
+    }
+
+    pub fn redact_content(mut self, redaction: Redaction) -> Self {
+        self.redaction = redaction;
+        self
+    }
+
+    pub fn sink<F>(mut self, sink: F) -> Self
+    where
+        F: Fn(&str) + Send + Sync + 'static,
+    {
+        self.sink = Arc::new(sink);
+        self
+    }
+
+    fn log_body(&self, out: &mut String, headers: &HashMap<String, String>, body: &[u8]) {
+        if body.is_empty() {
+            return;
+        }
+        let content_type = headers
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case("content-type"))
+            .map(|(_, value)| value.as_str())
+            .unwrap_or_default();
+        let is_text =
+            content_type.starts_with("application/json") || content_type.starts_with("text/");
+        if self.redaction == Redaction::Keep && is_text {
+            // Verbatim, since key order and formatting matter when comparing
+            // against what a server expects.
+            out.push_str(&format!("\n{}", String::from_utf8_lossy(body)));
+            return;
+        }
+        match serde_json::from_slice::<Value>(body) {
+            Ok(mut json) => {
+                redact_value(&mut json, self.redaction, false);
+                out.push_str(&format!("\n{}", json));
+            }
+            // Multipart uploads, audio and redacted plain text.
+            Err(_) => out.push_str(&format!("\n<{} bytes of {}>", body.len(), content_type)),
+        }
+    }
+}
+
+impl Default for RequestLogger {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for RequestLogger {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("RequestLogger")
+            .field("redaction", &self.redaction)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Middleware for RequestLogger {
+    fn on_request(&self, request: &mut HttpRequest) -> Result<Option<HttpResponse>, APIError> {
+        let mut out = format!("--> {} {}", request.method.as_str(), request.url);
+        log_headers(&mut out, &request.headers);
+        self.log_body(
+            &mut out,
+            &request.headers,
+            request.body.as_deref().unwrap_or_default(),
+        );
+        (self.sink)(&out);
+        Ok(None)
+    }
+
+    fn on_response(
+        &self,
+        request: &HttpRequest,
+        response: &mut HttpResponse,
+    ) -> Result<(), APIError> {
+        let mut out = format!(
+            "<-- {} {} {}",
+            response.status_code,
+            request.method.as_str(),
+            request.url
+        );
+        log_headers(&mut out, &response.headers);
+        if request.stream && response.is_success() {
+            out.push_str("\n<streamed body>");
+        } else {
+            self.log_body(&mut out, &response.headers, &response.body);
+        }
+        (self.sink)(&out);
+        Ok(())
+    }
+}
+
+fn log_headers(out: &mut String, headers: &HashMap<String, String>) {
+    let mut names: Vec<&String> = headers.keys().collect();
+    names.sort();
+    for name in names {
+        let value = if SECRET_HEADERS.contains(&name.to_ascii_lowercase().as_str()) {
+            "[redacted]"
+        } else {
+            &headers[name]
+        };
+        out.push_str(&format!("\n{}: {}", name, value));
+    }
+}
+
+// `in_content` is set below a content field, so that structured content such
+// as `[{"type": "text", "text": ...}]` is redacted as a whole.
+fn redact_value(value: &mut Value, redaction: Redaction, in_content: bool) {
+    match value {
+        Value::String(text) if in_content => match redaction {
+            Redaction::Keep => {}
+            Redaction::Redact => *text = "[redacted]".to_owned(),
+            Redaction::Hash => {
+                let mut hasher = DefaultHasher::new();
+                text.hash(&mut hasher);
+                *text = format!(
+                    "[hash {:016x}, {} chars]",
+                    hasher.finish(),
+                    text.chars().count()
+                );
+            }
+        },
+        Value::Array(values) => {
+            for value in values {
+                redact_value(value, redaction, in_content);
+            }
+        }
+        Value::Object(map) => {
+            for (key, value) in map {
+                let in_content = in_content || CONTENT_FIELDS.contains(&key.as_str());
+                // Keep the shape of structured content readable.
+                if in_content && (key == "type" || key == "role") {
+                    continue;
+                }
+                redact_value(value, redaction, in_content);
+            }
+        }
+        _ => {}
+    }
+}
@@ -1,32 +1,66 @@
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::collections::HashMap;
 
-use crate::impl_builder_methods;
+use crate::{impl_builder_methods, impl_response_headers};
+
+/// A file attached to a message, and the tools it is made available to.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct Attachment {
+    pub file_id: String,
+    #[serde(default)]
+    pub tools: Vec<AttachmentTool>,
+}
+
+impl Attachment {
+    pub fn new(file_id: String, tools: Vec<AttachmentTool>) -> Self {
+        Self { file_id, tools }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AttachmentTool {
+    CodeInterpreter,
+    FileSearch,
+}
 
 #[derive(Debug, Serialize, Clone)]
 pub struct CreateMessageRequest {
     pub role: MessageRole,
     pub content: String,
+    #[deprecated(since = "4.0.8", note = "Assistants v2 takes files as `attachments`")]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub file_ids: Option<Vec<String>>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub attachments: Option<Vec<Attachment>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub metadata: Option<HashMap<String, String>>,
 }
 
 impl CreateMessageRequest {
+    #[allow(deprecated)]
     pub fn new(role: MessageRole, content: String) -> Self {
         Self {
             role,
             content,
             file_ids: None,
+            attachments: None,
             metadata: None,
         }
     }
+
+    #[deprecated(since = "4.0.8", note = "Assistants v2 takes files as `attachments`")]
+    #[allow(deprecated)]
+    pub fn file_ids(mut self, file_ids: Vec<String>) -> Self {
+        self.file_ids = Some(file_ids);
+        self
+    }
 }
 
 impl_builder_methods!(
     CreateMessageRequest,
-    file_ids: Vec<String>,
+    attachments: Vec<Attachment>,
     metadata: HashMap<String, String>
 );
 
@@ -54,6 +88,7 @@ impl_builder_methods!(
 );
 
 #[derive(Debug, Deserialize, Serialize)]
+#[non_exhaustive]
 pub struct MessageObject {
     pub id: String,
     pub object: String,
@@ -65,8 +100,14 @@ pub struct MessageObject {
     pub assistant_id: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub run_id: Option<String>,
+    #[deprecated(since = "4.0.8", note = "Assistants v2 returns files as `attachments`")]
+    #[serde(default)]
     pub file_ids: Vec<String>,
+    #[serde(default)]
+    pub attachments: Vec<Attachment>,
     pub metadata: HashMap<String, String>,
+    #[serde(skip)]
+    pub headers: Option<HashMap<String, String>>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
@@ -88,31 +129,101 @@ pub struct Content {
 #[derive(Debug, Deserialize, Serialize)]
 pub struct ContentText {
     pub value: String,
-    pub annotations: Vec<String>,
+    pub annotations: Vec<Value>,
+}
+
+/// A `thread.message.delta` event: text added to a message being streamed.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct MessageDeltaObject {
+    pub id: String,
+    pub object: String,
+    pub delta: MessageDelta,
+}
+
+impl MessageDeltaObject {
+    /// The text added by this delta, across its content parts.
+    pub fn text(&self) -> String {
+        self.delta
+            .content
+            .iter()
+            .filter_map(|part| part.text.as_ref()?.value.as_deref())
+            .collect()
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct MessageDelta {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub role: Option<MessageRole>,
+    #[serde(default)]
+    pub content: Vec<MessageDeltaContent>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct MessageDeltaContent {
+    /// The content part of the message this delta extends.
+    pub index: usize,
+    #[serde(rename = "type")]
+    pub content_type: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<TextDelta>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct TextDelta {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    #[serde(default)]
+    pub annotations: Vec<Value>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
+#[non_exhaustive]
 pub struct ListMessage {
     pub object: String,
     pub data: Vec<MessageObject>,
-    pub first_id: String,
-    pub last_id: String,
+    pub first_id: Option<String>,
+    pub last_id: Option<String>,
     pub has_more: bool,
+    #[serde(skip)]
+    pub headers: Option<HashMap<String, String>>,
 }
 
+#[deprecated(
+    since = "4.0.8",
+    note = "Assistants v2 has no message files; see `MessageObject::attachments`"
+)]
 #[derive(Debug, Deserialize, Serialize)]
+#[non_exhaustive]
 pub struct MessageFileObject {
     pub id: String,
     pub object: String,
     pub created_at: i64,
     pub message_id: String,
+    #[serde(skip)]
+    pub headers: Option<HashMap<String, String>>,
 }
 
+#[deprecated(
+    since = "4.0.8",
+    note = "Assistants v2 has no message files; see `MessageObject::attachments`"
+)]
 #[derive(Debug, Deserialize, Serialize)]
+#[non_exhaustive]
+#[allow(deprecated)]
 pub struct ListMessageFile {
     pub object: String,
     pub data: Vec<MessageFileObject>,
-    pub first_id: String,
-    pub last_id: String,
+    pub first_id: Option<String>,
+    pub last_id: Option<String>,
     pub has_more: bool,
+    #[serde(skip)]
+    pub headers: Option<HashMap<String, String>>,
 }
+
+impl_response_headers!(
+    MessageObject,
+    ListMessage,
+    MessageFileObject,
+    ListMessageFile
+);
@@ -0,0 +1,157 @@
+use std::borrow::Cow;
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hasher};
+
+use crate::v1::error::APIError;
+
+/// Lets compatible servers and gateways recognize a retried POST and not run it twice.
+pub const IDEMPOTENCY_KEY_HEADER: &str = "Idempotency-Key";
+
+/// A random UUID v4, for `IDEMPOTENCY_KEY_HEADER`.
+pub fn new_idempotency_key() -> String {
+    let high = RandomState::new().build_hasher().finish();
+    let low = RandomState::new().build_hasher().finish();
+    let uuid = (u128::from(high) << 64 | u128::from(low)) & !(0xf000 << 64) & !(0xc << 60)
+        | 0x4000 << 64
+        | 0x8 << 60;
+    let hex = format!("{:032x}", uuid);
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Method {
+    Get,
+    Post,
+    Delete,
+}
+
+impl Method {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Method::Get => "GET",
+            Method::Post => "POST",
+            Method::Delete => "DELETE",
+        }
+    }
+
+    pub fn is_idempotent(&self) -> bool {
+        !matches!(self, Method::Post)
+    }
+}
+
+/// A fully prepared request, as seen by middleware just before it is sent.
+#[derive(Debug, Clone)]
+pub struct HttpRequest {
+    pub method: Method,
+    pub url: String,
+    pub headers: HashMap<String, String>,
+    pub body: Option<Vec<u8>>,
+    /// Timeout in seconds.
+    pub timeout: Option<u64>,
+    /// Whether the caller reads the body incrementally instead of buffering it.
+    pub stream: bool,
+}
+
+impl HttpRequest {
+    pub fn new(method: Method, url: String) -> Self {
+        Self {
+            method,
+            url,
+            headers: HashMap::new(),
+            body: None,
+            timeout: None,
+            stream: false,
+        }
+    }
+
+    pub fn with_header<K: Into<String>, V: Into<String>>(mut self, key: K, value: V) -> Self {
+        self.headers.insert(key.into(), value.into());
+        self
+    }
+
+    pub fn body_str(&self) -> Option<Cow<'_, str>> {
+        self.body.as_deref().map(String::from_utf8_lossy)
+    }
+
+    /// The value of a header, whatever the case of its name.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct HttpResponse {
+    pub status_code: i32,
+    /// Header names are lowercase.
+    pub headers: HashMap<String, String>,
+    pub body: Vec<u8>,
+}
+
+impl HttpResponse {
+    pub fn is_success(&self) -> bool {
+        (200..=299).contains(&self.status_code)
+    }
+
+    pub fn body_str(&self) -> Cow<'_, str> {
+        String::from_utf8_lossy(&self.body)
+    }
+}
+
+/// Per-call overrides applied on top of the client configuration.
+#[derive(Debug, Clone, Default)]
+pub struct RequestOptions {
+    pub timeout: Option<u64>,
+    pub extra_headers: Option<HashMap<String, String>>,
+    /// Sent as `Idempotency-Key`, the same on every retry.
+    pub idempotency_key: Option<String>,
+}
+
+impl RequestOptions {
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn apply(&self, request: &mut HttpRequest) {
+        if let Some(timeout) = self.timeout {
+            request.timeout = Some(timeout);
+        }
+        if let Some(extra_headers) = &self.extra_headers {
+            request.headers.extend(extra_headers.clone());
+        }
+        if let Some(idempotency_key) = &self.idempotency_key {
+            request
+                .headers
+                .insert(IDEMPOTENCY_KEY_HEADER.to_owned(), idempotency_key.clone());
+        }
+    }
+}
+
+/// Hooks run around every request the client sends.
+///
+/// Middleware runs in the order it was added for `on_request` and in reverse
+/// order for `on_response`. Each retry attempt goes through the chain again.
+pub trait Middleware: Send + Sync {
+    /// Inspects or rewrites the request. Returning a response skips the network
+    /// and the remaining `on_request` hooks; `on_response` hooks still run.
+    fn on_request(&self, _request: &mut HttpRequest) -> Result<Option<HttpResponse>, APIError> {
+        Ok(None)
+    }
+
+    /// Inspects or rewrites the response, including error statuses. For streaming
+    /// requests the body is empty, since it is consumed incrementally by the caller.
+    fn on_response(
+        &self,
+        _request: &HttpRequest,
+        _response: &mut HttpResponse,
+    ) -> Result<(), APIError> {
+        Ok(())
+    }
+}
@@ -0,0 +1,315 @@
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+
+use crate::v1::api::Client;
+
+/// A canned HTTP response served by a `MockServer`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MockResponse {
+    pub status_code: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl MockResponse {
+    pub fn json(status_code: u16, body: &Value) -> Self {
+        Self {
+            status_code,
+            headers: vec![("Content-Type".to_owned(), "application/json".to_owned())],
+            body: body.to_string().into_bytes(),
+        }
+    }
+
+    /// An error in the OpenAI format, e.g. `error(429, "Rate limit reached")`.
+    pub fn error(status_code: u16, message: &str) -> Self {
+        Self::json(
+            status_code,
+            &json!({
+                "error": { "message": message, "type": "mock_error", "param": null, "code": null }
+            }),
+        )
+    }
+
+    /// A chat completion whose assistant reply is `content`.
+    pub fn chat(content: &str) -> Self {
+        Self::chat_message(json!({ "role": "assistant", "content": content }), "stop")
+    }
+
+    /// A chat completion calling the given `(name, arguments)` tools.
+    pub fn tool_calls(calls: &[(&str, Value)]) -> Self {
+        let tool_calls: Vec<Value> = calls
+            .iter()
+            .enumerate()
+            .map(|(i, (name, arguments))| {
+                json!({
+                    "id": format!("call_{}", i),
+                    "type": "function",
+                    "function": { "name": name, "arguments": arguments.to_string() },
+                })
+            })
+            .collect();
+        Self::chat_message(
+            json!({ "role": "assistant", "content": null, "tool_calls": tool_calls }),
+            "tool_calls",
+        )
+    }
+
+    /// A streamed chat completion sending `chunks` as content deltas.
+    pub fn chat_stream(chunks: &[&str]) -> Self {
+        let mut body = String::new();
+        let deltas = chunks
+            .iter()
+            .map(|chunk| (json!({ "content": chunk }), Value::Null))
+            .chain(std::iter::once((json!({}), json!("stop"))));
+        for (delta, finish_reason) in deltas {
+            let event = json!({
+                "id": "chatcmpl-mock",
+                "object": "chat.completion.chunk",
+                "created": 0,
+                "model": "mock",
+                "choices": [{ "index": 0, "delta": delta, "finish_reason": finish_reason }],
+            });
+            body.push_str(&format!("data: {}\n\n", event));
+        }
+        body.push_str("data: [DONE]\n\n");
+        Self {
+            status_code: 200,
+            headers: vec![("Content-Type".to_owned(), "text/event-stream".to_owned())],
+            body: body.into_bytes(),
+        }
+    }
+
+    pub fn header<K: Into<String>, V: Into<String>>(mut self, name: K, value: V) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    fn chat_message(message: Value, finish_reason: &str) -> Self {
+        Self::json(
+            200,
+            &json!({
+                "id": "chatcmpl-mock",
+                "object": "chat.completion",
+                "created": 0,
+                "model": "mock",
+                "choices": [{
+                    "index": 0,
+                    "message": message,
+                    "finish_reason": finish_reason,
+                    "logprobs": null,
+                }],
+                "usage": { "prompt_tokens": 0, "completion_tokens": 0, "total_tokens": 0 },
+            }),
+        )
+    }
+}
+
+/// A request received by a `MockServer`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordedRequest {
+    pub method: String,
+    /// The path after `/v1`, with any query string.
+    pub path: String,
+    /// Header names are lowercase.
+    pub headers: HashMap<String, String>,
+    pub body: Vec<u8>,
+}
+
+impl RecordedRequest {
+    /// The body parsed as JSON, or `Value::Null` when it is not JSON.
+    pub fn json(&self) -> Value {
+        serde_json::from_slice(&self.body).unwrap_or(Value::Null)
+    }
+}
+
+#[derive(Debug, Default)]
+struct State {
+    // Responses per "METHOD /path"; the last one repeats once the others are used.
+    routes: HashMap<String, Vec<MockResponse>>,
+    requests: Vec<RecordedRequest>,
+}
+
+/// An HTTP server on a local port answering with canned responses, for
+/// testing code built on this crate without reaching the real API.
+///
+/// Routes are registered with `mock` and matched on the method and the path
+/// after `/v1`, ignoring the query string. Unmatched requests get a 404 in the
+/// OpenAI error format. The server stops when dropped.
+///
+/// ```no_run
+/// use openai_api_rs::v1::mock::{MockResponse, MockServer};
+///
+/// let server = MockServer::start();
+/// server.mock("POST", "/chat/completions", MockResponse::chat("Hello"));
+/// let client = server.client();
+/// // ... run the code under test with `client` ...
+/// assert_eq!(server.requests().len(), 1);
+/// ```
+pub struct MockServer {
+    addr: SocketAddr,
+    state: Arc<Mutex<State>>,
+    shutdown: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl MockServer {
+    pub fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("failed to bind mock server");
+        let addr = listener.local_addr().expect("mock server has no address");
+        let state = Arc::new(Mutex::new(State::default()));
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let handle = {
+            let state = state.clone();
+            let shutdown = shutdown.clone();
+            std::thread::spawn(move || {
+                for stream in listener.incoming() {
+                    if shutdown.load(Ordering::SeqCst) {
+                        break;
+                    }
+                    if let Ok(stream) = stream {
+                        // A client that hangs up early is no concern of the server.
+                        let _ = handle_connection(stream, &state);
+                    }
+                }
+            })
+        };
+        Self {
+            addr,
+            state,
+            shutdown,
+            handle: Some(handle),
+        }
+    }
+
+    /// The API base URL, ending in `/v1`.
+    pub fn url(&self) -> String {
+        format!("http://{}/v1", self.addr)
+    }
+
+    /// A client sending its requests to this server.
+    pub fn client(&self) -> Client {
+        Client::new_with_endpoint(self.url(), "mock-api-key".to_owned())
+    }
+
+    /// Answers `method` requests to `path` with `response`. Mocking the same
+    /// route again queues another response: they are served in order and the
+    /// last one is repeated.
+    pub fn mock(&self, method: &str, path: &str, response: MockResponse) {
+        self.state
+            .lock()
+            .unwrap()
+            .routes
+            .entry(route_key(method, path))
+            .or_default()
+            .push(response);
+    }
+
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.state.lock().unwrap().requests.clone()
+    }
+
+    pub fn last_request(&self) -> Option<RecordedRequest> {
+        self.state.lock().unwrap().requests.last().cloned()
+    }
+
+    /// Panics unless exactly `count` requests were made to `path`.
+    pub fn assert_called(&self, method: &str, path: &str, count: usize) {
+        let key = route_key(method, path);
+        let calls = self
+            .requests()
+            .iter()
+            .filter(|request| route_key(&request.method, &request.path) == key)
+            .count();
+        assert_eq!(
+            calls, count,
+            "expected {} request(s) to {}, got {}",
+            count, key, calls
+        );
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.shutdown.store(true, Ordering::SeqCst);
+        // Wake up the accept loop so it sees the flag.
+        let _ = TcpStream::connect(self.addr);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+fn route_key(method: &str, path: &str) -> String {
+    let path = path.split('?').next().unwrap_or_default();
+    format!(
+        "{} {}",
+        method.to_ascii_uppercase(),
+        path.trim_end_matches('/')
+    )
+}
+
+fn handle_connection(stream: TcpStream, state: &Mutex<State>) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_owned();
+    let target = parts.next().unwrap_or_default();
+    let path = target.strip_prefix("/v1").unwrap_or(target).to_owned();
+
+    let mut headers = HashMap::new();
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.insert(name.trim().to_ascii_lowercase(), value.trim().to_owned());
+        }
+    }
+    let length = headers
+        .get("content-length")
+        .and_then(|length| length.parse().ok())
+        .unwrap_or(0);
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body)?;
+
+    let key = route_key(&method, &path);
+    let response = {
+        let mut state = state.lock().unwrap();
+        state.requests.push(RecordedRequest {
+            method,
+            path,
+            headers,
+            body,
+        });
+        match state.routes.get_mut(&key) {
+            Some(responses) if responses.len() > 1 => Some(responses.remove(0)),
+            Some(responses) => responses.first().cloned(),
+            None => None,
+        }
+    };
+    let response =
+        response.unwrap_or_else(|| MockResponse::error(404, &format!("no mock for {}", key)));
+    write_response(stream, &response)
+}
+
+fn write_response(mut stream: TcpStream, response: &MockResponse) -> std::io::Result<()> {
+    let mut head = format!(
+        "HTTP/1.1 {} Mock\r\nContent-Length: {}\r\nConnection: close\r\n",
+        response.status_code,
+        response.body.len()
+    );
+    for (name, value) in &response.headers {
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    head.push_str("\r\n");
+    stream.write_all(head.as_bytes())?;
+    stream.write_all(&response.body)?;
+    stream.flush()
+}
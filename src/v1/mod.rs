@@ -4,18 +4,51 @@ pub mod error;
 pub mod audio;
 pub mod chat_completion;
 pub mod completion;
+pub mod conversation;
+pub mod cost;
 pub mod edit;
 pub mod embedding;
 pub mod file;
 pub mod fine_tuning;
 pub mod image;
+pub mod model;
 pub mod moderation;
+pub mod raw_output;
+pub mod stream;
 
 // beta
 pub mod assistant;
 pub mod message;
 pub mod run;
 pub mod thread;
+pub mod vector_store;
 
+pub mod anthropic;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod api;
+pub mod azure;
+pub mod cache;
+#[cfg(all(any(test, feature = "test-utils"), not(target_arch = "wasm32")))]
+pub mod cassette;
+pub mod client;
+pub mod coalesce;
+pub mod gemini;
+pub mod json_repair;
+pub mod logging;
+pub mod middleware;
+#[cfg(all(any(test, feature = "test-utils"), not(target_arch = "wasm32")))]
+pub mod mock;
+pub mod multipart;
+pub mod ollama;
+pub mod retry;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod router;
+#[cfg(feature = "schema-validation")]
+pub mod schema_validation;
+pub mod thinking;
+pub mod throttle;
+pub mod tool_schema;
+pub mod tools;
+
+#[cfg(feature = "vllm")]
 pub mod pyo3;
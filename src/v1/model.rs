@@ -0,0 +1,33 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::impl_response_headers;
+
+#[derive(Debug, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct ModelObject {
+    pub id: String,
+    pub object: String,
+    pub created: i64,
+    pub owned_by: String,
+    // Extra metadata reported by vLLM.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub root: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_model_len: Option<i64>,
+    #[serde(skip)]
+    pub headers: Option<HashMap<String, String>>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct ListModel {
+    pub object: String,
+    pub data: Vec<ModelObject>,
+    #[serde(skip)]
+    pub headers: Option<HashMap<String, String>>,
+}
+
+impl_response_headers!(ModelObject, ListModel);
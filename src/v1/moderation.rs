@@ -1,6 +1,15 @@
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
 
-use crate::impl_builder_methods;
+use crate::v1::chat_completion::{ChatCompletionRequest, Content, MessageRole};
+use crate::v1::error::APIError;
+use crate::{impl_builder_methods, impl_response_headers};
+
+pub const TEXT_MODERATION_LATEST: &str = "text-moderation-latest";
+pub const TEXT_MODERATION_STABLE: &str = "text-moderation-stable";
 
 #[derive(Debug, Serialize, Clone)]
 pub struct CreateModerationRequest {
@@ -21,10 +30,20 @@ impl_builder_methods!(
 );
 
 #[derive(Debug, Deserialize, Serialize)]
+#[non_exhaustive]
 pub struct CreateModerationResponse {
     pub id: String,
     pub model: String,
     pub results: Vec<ModerationResult>,
+    #[serde(skip)]
+    pub headers: Option<HashMap<String, String>>,
+}
+
+impl CreateModerationResponse {
+    /// Whether any input was flagged, for screening content before it is sent on.
+    pub fn flagged(&self) -> bool {
+        self.results.iter().any(|result| result.flagged)
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -34,14 +53,126 @@ pub struct ModerationResult {
     pub flagged: bool,
 }
 
+impl ModerationResult {
+    /// The flagged categories and all scores, keyed by their API names such
+    /// as `"hate/threatening"`.
+    pub fn verdict(&self) -> ModerationVerdict {
+        let categories = match serde_json::to_value(&self.categories) {
+            Ok(Value::Object(categories)) => categories
+                .into_iter()
+                .filter(|(_, flagged)| flagged.as_bool() == Some(true))
+                .map(|(name, _)| name)
+                .collect(),
+            _ => Vec::new(),
+        };
+        let category_scores = match serde_json::to_value(&self.category_scores) {
+            Ok(Value::Object(scores)) => scores
+                .into_iter()
+                .filter_map(|(name, score)| Some((name, score.as_f64()?)))
+                .collect(),
+            _ => HashMap::new(),
+        };
+        ModerationVerdict {
+            flagged: self.flagged,
+            categories,
+            category_scores,
+        }
+    }
+}
+
+/// Whether content may be sent on, as decided by a `ModerationGuard`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ModerationVerdict {
+    pub flagged: bool,
+    /// Names of the flagged categories.
+    pub categories: Vec<String>,
+    pub category_scores: HashMap<String, f64>,
+}
+
+pub type ModerationClassifier =
+    Arc<dyn Fn(&str) -> Result<ModerationVerdict, APIError> + Send + Sync>;
+
+/// Screens the user messages of chat requests before they are sent, failing
+/// with `APIError::ContentBlocked` instead when they are flagged.
+///
+/// The text of the user messages since the last assistant message is checked
+/// with the moderations endpoint, or with `classifier` when one is set, e.g.
+/// to run a local model or to apply stricter score thresholds. Replies served
+/// from `response_cache` are not screened again.
+///
+/// ```no_run
+/// use openai_api_rs::v1::api::Client;
+/// use openai_api_rs::v1::moderation::ModerationGuard;
+///
+/// let client = Client::new("sk-...".to_owned()).moderation_guard(ModerationGuard::new());
+/// ```
+#[derive(Clone, Default)]
+pub struct ModerationGuard {
+    /// Moderation model for the endpoint, its default when unset.
+    pub model: Option<String>,
+    pub classifier: Option<ModerationClassifier>,
+}
+
+impl ModerationGuard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn classifier<F>(mut self, classifier: F) -> Self
+    where
+        F: Fn(&str) -> Result<ModerationVerdict, APIError> + Send + Sync + 'static,
+    {
+        self.classifier = Some(Arc::new(classifier));
+        self
+    }
+
+    /// The text of the user messages after the last assistant message of
+    /// `req`, or `None` when there is none. Earlier turns of the conversation
+    /// were screened when they were sent.
+    pub fn input(req: &ChatCompletionRequest) -> Option<String> {
+        let start = req
+            .messages
+            .iter()
+            .rposition(|message| message.role == MessageRole::assistant)
+            .map_or(0, |i| i + 1);
+        let texts: Vec<String> = req.messages[start..]
+            .iter()
+            .filter(|message| message.role == MessageRole::user)
+            .filter_map(|message| message.content.as_ref().map(Content::text))
+            .filter(|text| !text.trim().is_empty())
+            .map(|text| text.into_owned())
+            .collect();
+        (!texts.is_empty()).then(|| texts.join("\n\n"))
+    }
+}
+
+impl_builder_methods!(ModerationGuard, model: String);
+
+impl fmt::Debug for ModerationGuard {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ModerationGuard")
+            .field("model", &self.model)
+            .field("classifier", &self.classifier.is_some())
+            .finish()
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct ModerationCategories {
     #[serde(rename = "hate")]
     pub is_hate: bool,
     #[serde(rename = "hate/threatening")]
     pub is_hate_threatening: bool,
+    #[serde(default)]
+    pub harassment: bool,
+    #[serde(rename = "harassment/threatening", default)]
+    pub is_harassment_threatening: bool,
     #[serde(rename = "self-harm")]
     pub is_self_harm: bool,
+    #[serde(rename = "self-harm/intent", default)]
+    pub is_self_harm_intent: bool,
+    #[serde(rename = "self-harm/instructions", default)]
+    pub is_self_harm_instructions: bool,
     pub sexual: bool,
     #[serde(rename = "sexual/minors")]
     pub is_sexual_minors: bool,
@@ -56,8 +187,16 @@ pub struct ModerationCategoryScores {
     pub hate_score: f64,
     #[serde(rename = "hate/threatening")]
     pub hate_threatening_score: f64,
+    #[serde(default)]
+    pub harassment: f64,
+    #[serde(rename = "harassment/threatening", default)]
+    pub harassment_threatening_score: f64,
     #[serde(rename = "self-harm")]
     pub self_harm_score: f64,
+    #[serde(rename = "self-harm/intent", default)]
+    pub self_harm_intent_score: f64,
+    #[serde(rename = "self-harm/instructions", default)]
+    pub self_harm_instructions_score: f64,
     pub sexual: f64,
     #[serde(rename = "sexual/minors")]
     pub sexual_minors_score: f64,
@@ -65,3 +204,5 @@ pub struct ModerationCategoryScores {
     #[serde(rename = "violence/graphic")]
     pub violence_graphic_score: f64,
 }
+
+impl_response_headers!(CreateModerationResponse);
@@ -0,0 +1,114 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::path::Path;
+
+use crate::v1::error::APIError;
+
+/// A `multipart/form-data` body for the upload endpoints.
+#[derive(Debug, Clone)]
+pub struct Form {
+    boundary: String,
+    body: Vec<u8>,
+}
+
+impl Form {
+    pub fn new() -> Self {
+        let random = RandomState::new().build_hasher().finish();
+        Self {
+            boundary: format!("----openai-api-rs-{:016x}", random),
+            body: Vec::new(),
+        }
+    }
+
+    pub fn text<V: ToString>(mut self, name: &str, value: V) -> Self {
+        self.part_header(name, None, None);
+        self.body.extend_from_slice(value.to_string().as_bytes());
+        self.body.extend_from_slice(b"\r\n");
+        self
+    }
+
+    pub fn text_opt<V: ToString>(self, name: &str, value: Option<V>) -> Self {
+        match value {
+            Some(value) => self.text(name, value),
+            None => self,
+        }
+    }
+
+    pub fn bytes(mut self, name: &str, filename: &str, content: &[u8]) -> Self {
+        self.part_header(name, Some(filename), Some(mime_type(filename)));
+        self.body.extend_from_slice(content);
+        self.body.extend_from_slice(b"\r\n");
+        self
+    }
+
+    /// Adds the file at `path`, named after its last path component.
+    pub fn file<P: AsRef<Path>>(self, name: &str, path: P) -> Result<Self, APIError> {
+        let path = path.as_ref();
+        let content =
+            std::fs::read(path).map_err(|e| APIError::Io(format!("{}: {}", path.display(), e)))?;
+        let filename = path
+            .file_name()
+            .map(|f| f.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "file".to_owned());
+        Ok(self.bytes(name, &filename, &content))
+    }
+
+    pub fn content_type(&self) -> String {
+        format!("multipart/form-data; boundary={}", self.boundary)
+    }
+
+    pub fn into_body(mut self) -> Vec<u8> {
+        self.body
+            .extend_from_slice(format!("--{}--\r\n", self.boundary).as_bytes());
+        self.body
+    }
+
+    fn part_header(&mut self, name: &str, filename: Option<&str>, content_type: Option<&str>) {
+        let mut header = format!(
+            "--{}\r\nContent-Disposition: form-data; name=\"{}\"",
+            self.boundary,
+            escape(name)
+        );
+        if let Some(filename) = filename {
+            header.push_str(&format!("; filename=\"{}\"", escape(filename)));
+        }
+        header.push_str("\r\n");
+        if let Some(content_type) = content_type {
+            header.push_str(&format!("Content-Type: {}\r\n", content_type));
+        }
+        header.push_str("\r\n");
+        self.body.extend_from_slice(header.as_bytes());
+    }
+}
+
+impl Default for Form {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// The image endpoints reject uploads that are not labelled as PNG.
+fn mime_type(filename: &str) -> &'static str {
+    let extension = Path::new(filename)
+        .extension()
+        .map(|e| e.to_string_lossy().to_ascii_lowercase());
+    match extension.as_deref() {
+        Some("png") => "image/png",
+        Some("jpg") | Some("jpeg") => "image/jpeg",
+        Some("webp") => "image/webp",
+        Some("mp3") | Some("mpga") | Some("mpeg") => "audio/mpeg",
+        Some("m4a") | Some("mp4") => "audio/mp4",
+        Some("wav") => "audio/wav",
+        Some("webm") => "audio/webm",
+        Some("json") => "application/json",
+        Some("jsonl") => "application/jsonl",
+        _ => "application/octet-stream",
+    }
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace(['\r', '\n'], " ")
+}
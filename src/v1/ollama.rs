@@ -0,0 +1,305 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::impl_builder_methods;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::v1::api::Client;
+use crate::v1::chat_completion::{
+    ChatCompletionChoice, ChatCompletionMessage, ChatCompletionMessageForResponse,
+    ChatCompletionRequest, ChatCompletionResponse, Content, FinishReason, MessageRole,
+    StructuredContent, Tool, ToolCall, ToolCallFunction,
+};
+use crate::v1::common::Usage;
+use crate::v1::error::APIError;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::v1::middleware::Method;
+
+pub const OLLAMA_API_URL: &str = "http://localhost:11434/v1";
+
+/// Settings for a local Ollama server that its OpenAI-compatible endpoint
+/// does not take.
+///
+/// When `keep_alive` or `options` are set, or `native_api` is enabled,
+/// requests go to Ollama's own `/api/chat` instead.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OllamaConfig {
+    /// How long the model stays loaded after the request, e.g. `"10m"`, or
+    /// `"0"` to unload it at once.
+    pub keep_alive: Option<String>,
+    /// Model options such as `num_ctx`, overriding the ones derived from the request.
+    pub options: Option<Map<String, Value>>,
+    pub native_api: Option<bool>,
+}
+
+impl OllamaConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn option<K: Into<String>, V: Into<Value>>(mut self, key: K, value: V) -> Self {
+        self.options
+            .get_or_insert_with(Map::new)
+            .insert(key.into(), value.into());
+        self
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn uses_native_api(&self) -> bool {
+        self.native_api == Some(true) || self.keep_alive.is_some() || self.options.is_some()
+    }
+}
+
+impl_builder_methods!(OllamaConfig, keep_alive: String, native_api: bool);
+
+/// A request to Ollama's `/api/chat`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct OllamaChatRequest {
+    pub model: String,
+    pub messages: Vec<OllamaMessage>,
+    pub stream: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<Value>,
+    #[serde(skip_serializing_if = "Map::is_empty")]
+    pub options: Map<String, Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keep_alive: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tools: Option<Vec<Tool>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct OllamaMessage {
+    pub role: MessageRole,
+    #[serde(default)]
+    pub content: String,
+    /// Base64 encoded images, without a `data:` prefix.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub images: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thinking: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tool_calls: Vec<OllamaToolCall>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct OllamaToolCall {
+    pub function: OllamaFunction,
+}
+
+/// Unlike OpenAI, Ollama sends tool call arguments as an object.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct OllamaFunction {
+    pub name: String,
+    pub arguments: Value,
+}
+
+/// A non-streamed response from Ollama's `/api/chat`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct OllamaChatResponse {
+    pub model: String,
+    #[serde(default)]
+    pub created_at: String,
+    pub message: OllamaMessage,
+    #[serde(default)]
+    pub done_reason: Option<String>,
+    #[serde(default)]
+    pub prompt_eval_count: Option<i32>,
+    #[serde(default)]
+    pub eval_count: Option<i32>,
+}
+
+impl OllamaChatRequest {
+    /// Converts a chat request. Sampling parameters and the token limit move
+    /// into `options`, and a JSON schema response format becomes `format`.
+    /// Images must be `data:` URIs, as Ollama does not fetch URLs.
+    pub fn from_request(
+        req: &ChatCompletionRequest,
+        config: &OllamaConfig,
+    ) -> Result<Self, APIError> {
+        let mut options = Map::new();
+        let sampling = [
+            ("temperature", req.temperature.map(Value::from)),
+            ("top_p", req.top_p.map(Value::from)),
+            ("seed", req.seed.map(Value::from)),
+            ("num_predict", req.completion_token_limit().map(Value::from)),
+            ("presence_penalty", req.presence_penalty.map(Value::from)),
+            ("frequency_penalty", req.frequency_penalty.map(Value::from)),
+            ("stop", req.stop.clone().map(Value::from)),
+        ];
+        for (key, value) in sampling {
+            if let Some(value) = value {
+                options.insert(key.to_owned(), value);
+            }
+        }
+        options.extend(config.options.clone().unwrap_or_default());
+        let format = match req.output_schema() {
+            Some(schema) => Some(schema),
+            None => req
+                .response_format
+                .as_ref()
+                .filter(|format| format.get("type").and_then(Value::as_str) == Some("json_object"))
+                .map(|_| Value::from("json")),
+        };
+        Ok(Self {
+            model: req.model.clone(),
+            messages: req
+                .messages
+                .iter()
+                .map(OllamaMessage::from_message)
+                .collect::<Result<_, _>>()?,
+            stream: false,
+            format,
+            options,
+            keep_alive: config.keep_alive.clone(),
+            tools: req.tools.clone(),
+        })
+    }
+}
+
+impl OllamaMessage {
+    fn from_message(message: &ChatCompletionMessage) -> Result<Self, APIError> {
+        let mut content = Vec::new();
+        let mut images = Vec::new();
+        match &message.content {
+            None => {}
+            Some(Content::PlainText(text)) => content.push(text.as_str()),
+            Some(Content::Structured(parts)) => {
+                for part in parts {
+                    match part {
+                        StructuredContent::Text { text } => content.push(text),
+                        StructuredContent::ImageUrl { image_url } => images.push(
+                            image_url
+                                .url
+                                .strip_prefix("data:")
+                                .and_then(|rest| rest.split_once(";base64,"))
+                                .map(|(_, data)| data.to_owned())
+                                .ok_or_else(|| {
+                                    APIError::InvalidRequest(
+                                        "Ollama only takes images as base64 data: URIs".into(),
+                                    )
+                                })?,
+                        ),
+                        StructuredContent::InputAudio { .. } => {
+                            return Err(APIError::InvalidRequest(
+                                "input audio is not supported by Ollama".into(),
+                            ))
+                        }
+                    }
+                }
+            }
+        }
+        let tool_calls = message
+            .tool_calls
+            .iter()
+            .flatten()
+            .map(|call| {
+                Ok(OllamaToolCall {
+                    function: OllamaFunction {
+                        name: call.function.name.clone().unwrap_or_default(),
+                        arguments: call.function.parse_arguments().map_err(|e| {
+                            APIError::InvalidRequest(format!("tool call {}: {}", call.id, e))
+                        })?,
+                    },
+                })
+            })
+            .collect::<Result<_, APIError>>()?;
+        Ok(Self {
+            role: message.role.clone(),
+            content: content.join("\n"),
+            images,
+            thinking: None,
+            tool_calls,
+        })
+    }
+}
+
+impl From<OllamaChatResponse> for ChatCompletionResponse {
+    fn from(res: OllamaChatResponse) -> Self {
+        let message = res.message;
+        // Ollama does not assign tool call IDs.
+        let tool_calls: Vec<ToolCall> = message
+            .tool_calls
+            .into_iter()
+            .enumerate()
+            .map(|(i, call)| ToolCall {
+                id: format!("call_{}", i),
+                r#type: "function".to_owned(),
+                function: ToolCallFunction {
+                    name: Some(call.function.name),
+                    arguments: Some(call.function.arguments.to_string()),
+                },
+            })
+            .collect();
+        let finish_reason = if !tool_calls.is_empty() {
+            FinishReason::tool_calls
+        } else if res.done_reason.as_deref() == Some("length") {
+            FinishReason::length
+        } else {
+            FinishReason::stop
+        };
+        let mut reply = ChatCompletionMessageForResponse::new(
+            MessageRole::assistant,
+            (!message.content.is_empty() || tool_calls.is_empty()).then_some(message.content),
+        );
+        reply.reasoning_content = message.thinking;
+        reply.tool_calls = (!tool_calls.is_empty()).then_some(tool_calls);
+        let prompt_tokens = res.prompt_eval_count.unwrap_or(0);
+        let completion_tokens = res.eval_count.unwrap_or(0);
+        ChatCompletionResponse::new(
+            format!("chatcmpl-ollama-{}", res.created_at),
+            res.model,
+            vec![ChatCompletionChoice::new(0, reply, Some(finish_reason))],
+            Usage::new(prompt_tokens, completion_tokens),
+        )
+    }
+}
+
+/// Sends a chat request to the Ollama server `client` points at, e.g.
+/// `OLLAMA_API_URL`.
+///
+/// Uses the OpenAI-compatible endpoint unless `config` calls for the native
+/// API, and falls back to `/api/chat` when that endpoint answers 404, as
+/// Ollama versions without it do. Streaming is only supported through the
+/// OpenAI-compatible endpoint, with `Client::chat_completion_stream`.
+///
+/// ```no_run
+/// use openai_api_rs::v1::api::Client;
+/// use openai_api_rs::v1::chat_completion::{ChatCompletionMessage, ChatCompletionRequest};
+/// use openai_api_rs::v1::ollama::{self, OllamaConfig, OLLAMA_API_URL};
+///
+/// let client = Client::new_with_endpoint(OLLAMA_API_URL.to_owned(), String::new());
+/// let config = OllamaConfig::new().keep_alive("10m".to_owned()).option("num_ctx", 8192);
+/// let req = ChatCompletionRequest::new("llama3.2".to_owned(), vec![ChatCompletionMessage::user("Hi")]);
+/// let res = ollama::chat_completion(&client, &config, req)?;
+/// # Ok::<(), openai_api_rs::v1::error::APIError>(())
+/// ```
+#[cfg(not(target_arch = "wasm32"))]
+pub fn chat_completion(
+    client: &Client,
+    config: &OllamaConfig,
+    req: ChatCompletionRequest,
+) -> Result<ChatCompletionResponse, APIError> {
+    if !config.uses_native_api() {
+        match client.chat_completion(req.clone()) {
+            Err(error) if error.status_code() == Some(404) => {}
+            result => return result,
+        }
+    }
+    let body = OllamaChatRequest::from_request(&req, config)?;
+    let mut request = client.build_request(Method::Post, "");
+    request.url = native_url(&client.api_endpoint);
+    request.body = Some(serde_json::to_vec(&body).map_err(|e| APIError::Decode(e.to_string()))?);
+    req.request_options().apply(&mut request);
+    let res = client.execute(request)?;
+    let message: OllamaChatResponse = crate::v1::common::from_slice(&res.body)?;
+    let mut response = ChatCompletionResponse::from(message);
+    client.record_usage(&response.model, &response.usage);
+    response.headers = Some(res.headers);
+    Ok(response)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn native_url(api_endpoint: &str) -> String {
+    let base = api_endpoint.trim_end_matches('/');
+    format!("{}/api/chat", base.strip_suffix("/v1").unwrap_or(base))
+}
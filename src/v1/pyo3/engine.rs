@@ -0,0 +1,188 @@
+use futures::stream::{self, Stream};
+use pyo3::exceptions::PyStopAsyncIteration;
+use pyo3::prelude::*;
+use pyo3::types::PyModule;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::v1::chat_completion::ChatCompletionRequest;
+use crate::v1::pyo3::SamplingParams;
+
+// Keeps the asyncio side in Python: building the prompt with the model's chat
+// template and turning vLLM's cumulative outputs into deltas.
+const HELPERS: &std::ffi::CStr = c"
+import json
+
+def create_engine(kwargs):
+    from vllm import AsyncEngineArgs, AsyncLLMEngine
+    return AsyncLLMEngine.from_engine_args(AsyncEngineArgs(**json.loads(kwargs)))
+
+async def generate_chat(
+    engine,
+    messages,
+    prompt_token_ids,
+    sampling_params,
+    request_id,
+    lora_request,
+    prompt_adapter_request,
+):
+    if prompt_token_ids is not None:
+        prompt = {'prompt_token_ids': prompt_token_ids}
+    else:
+        tokenizer = await engine.get_tokenizer()
+        prompt = tokenizer.apply_chat_template(
+            json.loads(messages), tokenize=False, add_generation_prompt=True
+        )
+    # Only passed when used: vLLM 0.10 removed prompt adapters.
+    adapters = {'lora_request': lora_request}
+    if prompt_adapter_request is not None:
+        adapters['prompt_adapter_request'] = prompt_adapter_request
+    text_len = 0
+    token_len = 0
+    async for output in engine.generate(prompt, sampling_params, request_id, **adapters):
+        completion = output.outputs[0]
+        text = completion.text[text_len:]
+        token_ids = list(completion.token_ids[token_len:])
+        text_len = len(completion.text)
+        token_len = len(completion.token_ids)
+        yield text, token_ids, output.finished, completion.finish_reason
+";
+
+static NEXT_REQUEST_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Arguments for `vllm.AsyncEngineArgs`.
+#[derive(Debug, Clone, Default)]
+pub struct EngineArgs {
+    pub model: String,
+    /// Any other keyword argument, e.g. `tensor_parallel_size` or `enable_lora`.
+    pub options: HashMap<String, Value>,
+}
+
+impl EngineArgs {
+    pub fn new<S: Into<String>>(model: S) -> Self {
+        Self {
+            model: model.into(),
+            options: HashMap::new(),
+        }
+    }
+
+    pub fn option<K: Into<String>, V: Into<Value>>(mut self, key: K, value: V) -> Self {
+        self.options.insert(key.into(), value.into());
+        self
+    }
+}
+
+/// One step of a generation: the text and tokens added since the previous one.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EngineOutput {
+    pub text: String,
+    pub token_ids: Vec<u32>,
+    pub finished: bool,
+    /// vLLM's finish reason, such as `stop` or `length`, once finished.
+    pub finish_reason: Option<String>,
+}
+
+/// A `vllm.AsyncLLMEngine` running in this process, serving chat requests
+/// without going through HTTP.
+///
+/// The engine runs on Python's asyncio loop, so streams must be polled inside
+/// a pyo3-async-runtimes tokio context, e.g. a `#[pyo3_async_runtimes::tokio::main]`
+/// function or `pyo3_async_runtimes::tokio::run`.
+pub struct Engine {
+    engine: PyObject,
+    helpers: PyObject,
+}
+
+impl Engine {
+    pub fn new(args: &EngineArgs) -> PyResult<Self> {
+        Python::with_gil(|py| {
+            let helpers =
+                PyModule::from_code(py, HELPERS, c"openai_api_rs_vllm.py", c"openai_api_rs_vllm")?;
+            let mut kwargs = args.options.clone();
+            kwargs.insert("model".to_owned(), Value::String(args.model.clone()));
+            let kwargs = serde_json::to_string(&kwargs).unwrap_or_default();
+            let engine = helpers.call_method1("create_engine", (kwargs,))?;
+            Ok(Self {
+                engine: engine.unbind(),
+                helpers: helpers.into_any().unbind(),
+            })
+        })
+    }
+
+    /// Generates a reply to `req`, applying the model's chat template to its
+    /// messages, or sending the metadata's `prompt_token_ids` as they are.
+    /// Sampling settings come from the request; the LoRA or prompt adapter and
+    /// request id from its Empower metadata when set.
+    pub fn generate_chat(
+        &self,
+        req: &ChatCompletionRequest,
+    ) -> PyResult<impl Stream<Item = PyResult<EngineOutput>> + Send + 'static> {
+        let messages = serde_json::to_string(&req.messages)
+            .map_err(|e| pyo3::exceptions::PyValueError::new_err(e.to_string()))?;
+        let metadata = req.empower_metadata.as_ref();
+        let request_id = metadata
+            .map(|m| m.id.clone())
+            .filter(|id| !id.is_empty())
+            .unwrap_or_else(|| {
+                format!(
+                    "openai-api-rs-{}",
+                    NEXT_REQUEST_ID.fetch_add(1, Ordering::Relaxed)
+                )
+            });
+        let generator = Python::with_gil(|py| -> PyResult<PyObject> {
+            let helpers = self.helpers.bind(py);
+            let lora_request = metadata
+                .and_then(|m| m.lora_request.as_ref())
+                .into_pyobject(py)?;
+            let prompt_adapter_request = metadata
+                .and_then(|m| m.prompt_adapter_request.as_ref())
+                .into_pyobject(py)?;
+            let sampling_params = SamplingParams::from(req).into_pyobject(py)?;
+            let generator = helpers.call_method1(
+                "generate_chat",
+                (
+                    self.engine.bind(py),
+                    messages,
+                    metadata.and_then(|m| m.prompt_token_ids.as_ref()),
+                    sampling_params,
+                    request_id,
+                    lora_request,
+                    prompt_adapter_request,
+                ),
+            )?;
+            Ok(generator.unbind())
+        })?;
+
+        Ok(stream::unfold(Some(generator), |generator| async move {
+            let generator = generator?;
+            let next = Python::with_gil(|py| {
+                pyo3_async_runtimes::tokio::into_future(
+                    generator.bind(py).call_method0("__anext__")?,
+                )
+            });
+            let item = match next {
+                Ok(next) => next.await,
+                Err(e) => Err(e),
+            };
+            match item {
+                Ok(item) => {
+                    let output = Python::with_gil(|py| {
+                        let (text, token_ids, finished, finish_reason) = item.extract(py)?;
+                        Ok(EngineOutput {
+                            text,
+                            token_ids,
+                            finished,
+                            finish_reason,
+                        })
+                    });
+                    Some((output, Some(generator)))
+                }
+                Err(e) if Python::with_gil(|py| e.is_instance_of::<PyStopAsyncIteration>(py)) => {
+                    None
+                }
+                Err(e) => Some((Err(e), None)),
+            }
+        }))
+    }
+}
@@ -0,0 +1,123 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::v1::api::Client;
+use crate::v1::chat_completion::{ChatCompletionRequest, EmpowerMetadata};
+use crate::v1::error::APIError;
+use crate::v1::pyo3::LoraRequest;
+
+#[derive(Debug, Serialize)]
+struct LoadLoraAdapterRequest<'a> {
+    lora_name: &'a str,
+    lora_path: &'a str,
+}
+
+#[derive(Debug, Serialize)]
+struct UnloadLoraAdapterRequest<'a> {
+    lora_name: &'a str,
+}
+
+#[derive(Debug, Default)]
+struct Adapters {
+    by_name: HashMap<String, LoraRequest>,
+    last_int_id: i32,
+}
+
+/// Keeps track of the LoRA adapters served by a vLLM server, by the model name
+/// requests use to select them.
+///
+/// Integer IDs are allocated from 1, as vLLM reserves 0, and are not reused
+/// after an adapter is removed. Loading and unloading at runtime needs the
+/// server started with `VLLM_ALLOW_RUNTIME_LORA_UPDATING=True`.
+#[derive(Debug, Default)]
+pub struct LoraManager {
+    adapters: Mutex<Adapters>,
+}
+
+impl LoraManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records an adapter already available on the server, e.g. one passed
+    /// with `--lora-modules`. Registering a known name updates its path and
+    /// keeps its integer ID.
+    pub fn register<N: Into<String>, P: Into<String>>(&self, name: N, path: P) -> LoraRequest {
+        let name = name.into();
+        let path = path.into();
+        let mut adapters = self.adapters.lock().unwrap();
+        if let Some(lora) = adapters.by_name.get_mut(&name) {
+            lora.lora_local_path = path;
+            return lora.clone();
+        }
+        adapters.last_int_id += 1;
+        let lora = LoraRequest {
+            lora_id: name.clone(),
+            lora_int_id: adapters.last_int_id,
+            lora_local_path: path,
+        };
+        adapters.by_name.insert(name, lora.clone());
+        lora
+    }
+
+    pub fn unregister(&self, name: &str) -> Option<LoraRequest> {
+        self.adapters.lock().unwrap().by_name.remove(name)
+    }
+
+    /// The adapter served under `model`, if any.
+    pub fn resolve(&self, model: &str) -> Option<LoraRequest> {
+        self.adapters.lock().unwrap().by_name.get(model).cloned()
+    }
+
+    pub fn adapters(&self) -> Vec<LoraRequest> {
+        let mut adapters: Vec<LoraRequest> = self
+            .adapters
+            .lock()
+            .unwrap()
+            .by_name
+            .values()
+            .cloned()
+            .collect();
+        adapters.sort_by_key(|lora| lora.lora_int_id);
+        adapters
+    }
+
+    /// Loads an adapter on the server through `/load_lora_adapter` and
+    /// registers it once the server accepts it.
+    pub fn load(&self, client: &Client, name: &str, path: &str) -> Result<LoraRequest, APIError> {
+        client.post(
+            "/load_lora_adapter",
+            &LoadLoraAdapterRequest {
+                lora_name: name,
+                lora_path: path,
+            },
+        )?;
+        Ok(self.register(name, path))
+    }
+
+    /// Unloads an adapter from the server through `/unload_lora_adapter` and
+    /// returns its registration, if it had one.
+    pub fn unload(&self, client: &Client, name: &str) -> Result<Option<LoraRequest>, APIError> {
+        client.post(
+            "/unload_lora_adapter",
+            &UnloadLoraAdapterRequest { lora_name: name },
+        )?;
+        Ok(self.unregister(name))
+    }
+
+    /// Sets the Empower `lora_request` for the adapter named by the request's
+    /// model. Returns `false`, leaving the request untouched, when the model is
+    /// not a registered adapter.
+    pub fn apply(&self, req: &mut ChatCompletionRequest) -> bool {
+        match self.resolve(&req.model) {
+            Some(lora) => {
+                req.empower_metadata
+                    .get_or_insert_with(EmpowerMetadata::default)
+                    .lora_request = Some(lora);
+                true
+            }
+            None => false,
+        }
+    }
+}
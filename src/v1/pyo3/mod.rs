@@ -1,8 +1,81 @@
+use pyo3::prelude::*;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+use crate::v1::error::APIError;
+
+pub mod engine;
+mod lora;
+pub mod output;
+mod sampling;
+mod tokenizer;
+
+pub use lora::LoraManager;
+pub use sampling::{GuidedDecodingParams, SamplingParams};
+pub use tokenizer::{get_tokenizer, Tokenizer};
+
+#[derive(Debug, Serialize, Deserialize, Clone, FromPyObject)]
 pub struct LoraRequest {
+    #[pyo3(attribute("lora_name"))]
     pub lora_id: String,
     pub lora_int_id: i32,
+    #[pyo3(attribute("lora_path"))]
     pub lora_local_path: String,
 }
+
+/// A soft prompt adapter, converted to `vllm.prompt_adapter.request.PromptAdapterRequest`.
+/// Prompt adapters were removed in vLLM 0.10.
+#[derive(Debug, Serialize, Deserialize, Clone, FromPyObject)]
+pub struct PromptAdapterRequest {
+    #[pyo3(attribute("prompt_adapter_name"))]
+    pub prompt_adapter_id: String,
+    #[pyo3(attribute("prompt_adapter_id"))]
+    pub prompt_adapter_int_id: i32,
+    pub prompt_adapter_local_path: String,
+    /// Number of virtual tokens the adapter prepends to the prompt.
+    pub prompt_adapter_num_virtual_tokens: i32,
+}
+
+impl LoraRequest {
+    /// Builds a `vllm.lora.request.LoRARequest`, reporting a missing or
+    /// incompatible `vllm` package as an error instead of panicking.
+    pub fn try_into_py<'py>(&self, py: Python<'py>) -> Result<Bound<'py, PyAny>, APIError> {
+        self.into_pyobject(py).map_err(APIError::from)
+    }
+}
+
+impl<'py> IntoPyObject<'py> for &LoraRequest {
+    type Target = PyAny;
+    type Output = Bound<'py, PyAny>;
+    type Error = PyErr;
+
+    fn into_pyobject(self, py: Python<'py>) -> Result<Self::Output, Self::Error> {
+        py.import("vllm.lora.request")?
+            .getattr("LoRARequest")?
+            .call1((&self.lora_id, self.lora_int_id, &self.lora_local_path))
+    }
+}
+
+impl PromptAdapterRequest {
+    /// Builds a `vllm.prompt_adapter.request.PromptAdapterRequest`, reporting a
+    /// missing or incompatible `vllm` package as an error instead of panicking.
+    pub fn try_into_py<'py>(&self, py: Python<'py>) -> Result<Bound<'py, PyAny>, APIError> {
+        self.into_pyobject(py).map_err(APIError::from)
+    }
+}
+
+impl<'py> IntoPyObject<'py> for &PromptAdapterRequest {
+    type Target = PyAny;
+    type Output = Bound<'py, PyAny>;
+    type Error = PyErr;
+
+    fn into_pyobject(self, py: Python<'py>) -> Result<Self::Output, Self::Error> {
+        py.import("vllm.prompt_adapter.request")?
+            .getattr("PromptAdapterRequest")?
+            .call1((
+                &self.prompt_adapter_id,
+                self.prompt_adapter_int_id,
+                &self.prompt_adapter_local_path,
+                self.prompt_adapter_num_virtual_tokens,
+            ))
+    }
+}
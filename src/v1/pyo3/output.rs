@@ -0,0 +1,138 @@
+use pyo3::prelude::*;
+use std::collections::HashMap;
+
+use crate::v1::chat_completion::{
+    ChatCompletionChoice, ChatCompletionResponse, ChoiceLogprobs, FinishReason, LogprobsContent,
+    TopLogprob,
+};
+use crate::v1::common::{PromptTokensDetails, Usage};
+use crate::v1::raw_output::RawOutput;
+
+/// `vllm.sequence.Logprob`.
+#[derive(Debug, Clone, PartialEq, FromPyObject)]
+pub struct Logprob {
+    pub logprob: f64,
+    pub rank: Option<u32>,
+    pub decoded_token: Option<String>,
+}
+
+/// Why generation stopped at a stop string or stop token, as opposed to EOS.
+#[derive(Debug, Clone, PartialEq, FromPyObject)]
+pub enum StopReason {
+    Token(u32),
+    Text(String),
+}
+
+/// `vllm.outputs.CompletionOutput`, one of the sequences of a request.
+#[derive(Debug, Clone, PartialEq, FromPyObject)]
+pub struct CompletionOutput {
+    pub index: usize,
+    pub text: String,
+    pub token_ids: Vec<u32>,
+    pub cumulative_logprob: Option<f64>,
+    /// For each generated token, the sampled token and the top candidates by ID.
+    pub logprobs: Option<Vec<HashMap<u32, Logprob>>>,
+    /// `stop`, `length` or `abort`; `None` while still generating.
+    pub finish_reason: Option<String>,
+    pub stop_reason: Option<StopReason>,
+}
+
+/// `vllm.outputs.RequestOutput`, as yielded by `AsyncLLMEngine.generate`.
+#[derive(Debug, Clone, PartialEq, FromPyObject)]
+pub struct RequestOutput {
+    pub request_id: String,
+    pub prompt: Option<String>,
+    pub prompt_token_ids: Option<Vec<u32>>,
+    pub outputs: Vec<CompletionOutput>,
+    pub finished: bool,
+    /// Prompt tokens served from the prefix cache; missing before vLLM 0.7.
+    #[pyo3(default)]
+    pub num_cached_tokens: Option<i32>,
+}
+
+impl RequestOutput {
+    /// The response the OpenAI-compatible server would send for this output.
+    ///
+    /// vLLM returns text as generated, so each sequence is parsed as raw model
+    /// output to separate thinking and tool calls from the answer.
+    pub fn into_chat_response(self, model: &str) -> ChatCompletionResponse {
+        let prompt_tokens = self.prompt_token_ids.as_ref().map_or(0, Vec::len) as i32;
+        let completion_tokens = self
+            .outputs
+            .iter()
+            .map(|output| output.token_ids.len() as i32)
+            .sum();
+        let mut usage = Usage::new(prompt_tokens, completion_tokens);
+        usage.prompt_tokens_details = self.num_cached_tokens.map(|cached| PromptTokensDetails {
+            cached_tokens: Some(cached),
+            audio_tokens: None,
+        });
+        let mut response = ChatCompletionResponse::new(
+            self.request_id,
+            model.to_owned(),
+            self.outputs
+                .into_iter()
+                .map(CompletionOutput::into_choice)
+                .collect(),
+            usage,
+        );
+        response.created = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs() as i64);
+        response
+    }
+}
+
+impl CompletionOutput {
+    pub fn into_choice(self) -> ChatCompletionChoice {
+        let message = RawOutput::parse(&self.text).into_message();
+        let finish_reason = match self.finish_reason.as_deref() {
+            Some(_) if message.tool_calls.is_some() => Some(FinishReason::tool_calls),
+            Some("stop") => Some(FinishReason::stop),
+            Some("length") => Some(FinishReason::length),
+            _ => None,
+        };
+        let logprobs = self.logprobs.map(|logprobs| ChoiceLogprobs {
+            content: Some(
+                logprobs
+                    .into_iter()
+                    .zip(&self.token_ids)
+                    .map(|(candidates, token_id)| logprobs_content(candidates, *token_id))
+                    .collect(),
+            ),
+        });
+        let mut choice = ChatCompletionChoice::new(self.index as i64, message, finish_reason);
+        choice.logprobs = logprobs;
+        choice
+    }
+}
+
+fn logprobs_content(candidates: HashMap<u32, Logprob>, token_id: u32) -> LogprobsContent {
+    let mut top: Vec<(u32, Logprob)> = candidates.into_iter().collect();
+    top.sort_by_key(|(_, logprob)| logprob.rank.unwrap_or(u32::MAX));
+    let (token, logprob) = top
+        .iter()
+        .find(|(id, _)| *id == token_id)
+        .map(|(_, logprob)| (token_text(logprob), logprob.logprob))
+        .unwrap_or_default();
+    LogprobsContent {
+        bytes: Some(token.clone().into_bytes()),
+        token,
+        logprob,
+        top_logprobs: top
+            .iter()
+            .map(|(_, logprob)| {
+                let token = token_text(logprob);
+                TopLogprob {
+                    bytes: Some(token.clone().into_bytes()),
+                    token,
+                    logprob: logprob.logprob,
+                }
+            })
+            .collect(),
+    }
+}
+
+fn token_text(logprob: &Logprob) -> String {
+    logprob.decoded_token.clone().unwrap_or_default()
+}
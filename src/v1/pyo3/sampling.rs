@@ -0,0 +1,226 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::impl_builder_methods;
+use crate::v1::chat_completion::ChatCompletionRequest;
+
+/// Sampling settings for a local vLLM engine, converted to `vllm.SamplingParams`
+/// with the `vllm` feature. Unset fields keep vLLM's defaults.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct SamplingParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_k: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_p: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repetition_penalty: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop_token_ids: Option<Vec<u32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bad_words: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include_stop_str_in_output: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<i64>,
+    /// Number of log probabilities to return per generated token.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub logprobs: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub guided_decoding: Option<GuidedDecodingParams>,
+}
+
+impl SamplingParams {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl_builder_methods!(
+    SamplingParams,
+    temperature: f64,
+    top_p: f64,
+    top_k: i32,
+    min_p: f64,
+    repetition_penalty: f64,
+    stop: Vec<String>,
+    stop_token_ids: Vec<u32>,
+    bad_words: Vec<String>,
+    include_stop_str_in_output: bool,
+    max_tokens: i64,
+    seed: i64,
+    logprobs: usize,
+    guided_decoding: GuidedDecodingParams
+);
+
+/// Constrains generation to a structure, converted to vLLM's
+/// `GuidedDecodingParams`. Set only one of the constraints.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct GuidedDecodingParams {
+    /// A JSON schema the output must match.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub json: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub regex: Option<String>,
+    /// An EBNF or Lark grammar.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub grammar: Option<String>,
+    /// The output must be exactly one of these strings.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub choice: Option<Vec<String>>,
+    /// The decoding backend, e.g. `xgrammar` or `outlines`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub backend: Option<String>,
+}
+
+impl GuidedDecodingParams {
+    pub fn json(schema: Value) -> Self {
+        Self {
+            json: Some(schema),
+            ..Self::default()
+        }
+    }
+
+    pub fn regex<S: Into<String>>(regex: S) -> Self {
+        Self {
+            regex: Some(regex.into()),
+            ..Self::default()
+        }
+    }
+
+    pub fn grammar<S: Into<String>>(grammar: S) -> Self {
+        Self {
+            grammar: Some(grammar.into()),
+            ..Self::default()
+        }
+    }
+
+    pub fn choice(choice: Vec<String>) -> Self {
+        Self {
+            choice: Some(choice),
+            ..Self::default()
+        }
+    }
+
+    /// The schema a request asks for: Empower's `tools_json_schema` when
+    /// `tools_only` is set, otherwise the request's output schema.
+    pub fn from_request(req: &ChatCompletionRequest) -> Option<Self> {
+        let metadata = req.empower_metadata.as_ref();
+        let tools_schema = metadata
+            .filter(|m| m.tools_only == Some(true))
+            .and_then(|m| m.tools_json_schema.clone());
+        tools_schema.or_else(|| req.output_schema()).map(Self::json)
+    }
+}
+
+impl_builder_methods!(GuidedDecodingParams, backend: String);
+
+// Carries over the settings a chat request shares with vLLM; top_k, min_p
+// and repetition_penalty have no counterpart on the request.
+impl From<&ChatCompletionRequest> for SamplingParams {
+    fn from(req: &ChatCompletionRequest) -> Self {
+        let logprobs = match req.logprobs {
+            Some(true) => Some(req.top_logprobs.unwrap_or(0) as usize),
+            _ => None,
+        };
+        Self {
+            guided_decoding: GuidedDecodingParams::from_request(req),
+            temperature: req.temperature,
+            top_p: req.top_p,
+            stop: req.stop.clone(),
+            stop_token_ids: req.stop_token_ids.clone(),
+            bad_words: req.bad_words.clone(),
+            include_stop_str_in_output: req.include_stop_str_in_output,
+            max_tokens: req.completion_token_limit(),
+            seed: req.seed,
+            logprobs,
+            ..Self::default()
+        }
+    }
+}
+
+mod python {
+    use pyo3::prelude::*;
+    use pyo3::types::PyDict;
+
+    use serde_json::Value;
+
+    use super::{GuidedDecodingParams, SamplingParams};
+
+    impl<'py> IntoPyObject<'py> for &SamplingParams {
+        type Target = PyAny;
+        type Output = Bound<'py, PyAny>;
+        type Error = PyErr;
+
+        fn into_pyobject(self, py: Python<'py>) -> Result<Self::Output, Self::Error> {
+            let kwargs = PyDict::new(py);
+            // Unset values are left out so vLLM applies its own defaults.
+            set(&kwargs, "temperature", self.temperature)?;
+            set(&kwargs, "top_p", self.top_p)?;
+            set(&kwargs, "top_k", self.top_k)?;
+            set(&kwargs, "min_p", self.min_p)?;
+            set(&kwargs, "repetition_penalty", self.repetition_penalty)?;
+            set(&kwargs, "stop", self.stop.as_ref())?;
+            set(&kwargs, "stop_token_ids", self.stop_token_ids.as_ref())?;
+            set(&kwargs, "bad_words", self.bad_words.as_ref())?;
+            set(
+                &kwargs,
+                "include_stop_str_in_output",
+                self.include_stop_str_in_output,
+            )?;
+            set(&kwargs, "max_tokens", self.max_tokens)?;
+            set(&kwargs, "seed", self.seed)?;
+            set(&kwargs, "logprobs", self.logprobs)?;
+            set(&kwargs, "guided_decoding", self.guided_decoding.as_ref())?;
+            py.import("vllm")?
+                .getattr("SamplingParams")?
+                .call((), Some(&kwargs))
+        }
+    }
+
+    fn set<'py, T>(kwargs: &Bound<'py, PyDict>, key: &str, value: Option<T>) -> PyResult<()>
+    where
+        T: IntoPyObject<'py>,
+    {
+        match value {
+            Some(value) => kwargs.set_item(key, value),
+            None => Ok(()),
+        }
+    }
+
+    impl<'py> IntoPyObject<'py> for SamplingParams {
+        type Target = PyAny;
+        type Output = Bound<'py, PyAny>;
+        type Error = PyErr;
+
+        fn into_pyobject(self, py: Python<'py>) -> Result<Self::Output, Self::Error> {
+            (&self).into_pyobject(py)
+        }
+    }
+
+    impl<'py> IntoPyObject<'py> for &GuidedDecodingParams {
+        type Target = PyAny;
+        type Output = Bound<'py, PyAny>;
+        type Error = PyErr;
+
+        fn into_pyobject(self, py: Python<'py>) -> Result<Self::Output, Self::Error> {
+            let kwargs = PyDict::new(py);
+            // vLLM accepts the schema as a JSON string as well as a dict.
+            set(&kwargs, "json", self.json.as_ref().map(Value::to_string))?;
+            set(&kwargs, "regex", self.regex.as_ref())?;
+            set(&kwargs, "grammar", self.grammar.as_ref())?;
+            set(&kwargs, "choice", self.choice.as_ref())?;
+            set(&kwargs, "backend", self.backend.as_ref())?;
+            py.import("vllm.sampling_params")?
+                .getattr("GuidedDecodingParams")?
+                .call((), Some(&kwargs))
+        }
+    }
+}
@@ -0,0 +1,89 @@
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+use std::collections::HashMap;
+
+use crate::v1::chat_completion::ChatCompletionMessage;
+use crate::v1::common::logit_bias_from_strings;
+use crate::v1::error::APIError;
+
+/// The tokenizer vLLM loads for `model`, for token counts that match the
+/// serving engine exactly.
+pub fn get_tokenizer(model: &str) -> Result<Tokenizer, APIError> {
+    Tokenizer::new(model)
+}
+
+/// A handle on a Hugging Face tokenizer loaded through
+/// `vllm.transformers_utils.tokenizer.get_tokenizer`.
+pub struct Tokenizer {
+    tokenizer: PyObject,
+}
+
+impl Tokenizer {
+    pub fn new(model: &str) -> Result<Self, APIError> {
+        Python::with_gil(|py| {
+            let tokenizer = py
+                .import("vllm.transformers_utils.tokenizer")?
+                .call_method1("get_tokenizer", (model,))?;
+            Ok(Self {
+                tokenizer: tokenizer.unbind(),
+            })
+        })
+    }
+
+    /// Token IDs of `text`, without special tokens such as BOS.
+    pub fn encode(&self, text: &str) -> Result<Vec<u32>, APIError> {
+        Python::with_gil(|py| {
+            let kwargs = PyDict::new(py);
+            kwargs.set_item("add_special_tokens", false)?;
+            let ids = self
+                .tokenizer
+                .bind(py)
+                .call_method("encode", (text,), Some(&kwargs))?;
+            Ok(ids.extract()?)
+        })
+    }
+
+    pub fn decode(&self, token_ids: &[u32]) -> Result<String, APIError> {
+        Python::with_gil(|py| {
+            let text = self
+                .tokenizer
+                .bind(py)
+                .call_method1("decode", (token_ids,))?;
+            Ok(text.extract()?)
+        })
+    }
+
+    /// A `logit_bias` map from biases for strings, see
+    /// `common::logit_bias_from_strings`.
+    pub fn logit_bias<I, S>(&self, biases: I) -> Result<HashMap<String, i32>, APIError>
+    where
+        I: IntoIterator<Item = (S, i32)>,
+        S: AsRef<str>,
+    {
+        logit_bias_from_strings(biases, |text| self.encode(text))
+    }
+
+    pub fn count(&self, text: &str) -> Result<usize, APIError> {
+        self.encode(text).map(|ids| ids.len())
+    }
+
+    /// Prompt tokens of a conversation after the model's chat template is
+    /// applied, as vLLM reports in `usage.prompt_tokens`.
+    pub fn count_messages(&self, messages: &[ChatCompletionMessage]) -> Result<usize, APIError> {
+        let messages =
+            serde_json::to_string(messages).map_err(|e| APIError::Decode(e.to_string()))?;
+        Python::with_gil(|py| {
+            let messages = py.import("json")?.call_method1("loads", (messages,))?;
+            let kwargs = PyDict::new(py);
+            kwargs.set_item("tokenize", true)?;
+            kwargs.set_item("add_generation_prompt", true)?;
+            let ids = self.tokenizer.bind(py).call_method(
+                "apply_chat_template",
+                (messages,),
+                Some(&kwargs),
+            )?;
+            Ok(ids.len()?)
+        })
+    }
+}
@@ -0,0 +1,124 @@
+use serde_json::Value;
+
+use crate::v1::chat_completion::{
+    ChatCompletionMessageForResponse, MessageRole, ToolCall, ToolCallFunction,
+};
+use crate::v1::thinking::split_thinking;
+
+// End-of-turn tokens that chat templates leave at the end of the raw text.
+const STOP_TOKENS: &[&str] = &["<|eot_id|>", "<|eom_id|>", "<|im_end|>", "<|end|>", "</s>"];
+
+/// Undecoded model output, as returned with `use_raw_output`, parsed into the
+/// parts a decoded reply would carry.
+///
+/// Tool calls are recognised in the formats of the common open model families:
+/// `<tool_call>{...}</tool_call>` blocks (Hermes, Qwen), a `[TOOL_CALLS]` JSON
+/// array (Mistral) and `<|python_tag|>` JSON (Llama 3.1). Each call is an object
+/// with a `name` and its `arguments` or `parameters`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RawOutput {
+    pub thinking: Option<String>,
+    /// Text outside the tool calls and thinking; `None` when nothing is left.
+    pub content: Option<String>,
+    pub tool_calls: Vec<ToolCall>,
+}
+
+impl RawOutput {
+    pub fn parse(raw: &str) -> Self {
+        let mut text = raw.trim();
+        while let Some(stripped) = STOP_TOKENS
+            .iter()
+            .find_map(|token| text.strip_suffix(token))
+        {
+            text = stripped.trim_end();
+        }
+        let split = split_thinking(text);
+        let (content, calls) = extract_tool_calls(&split.content);
+        let tool_calls = calls
+            .into_iter()
+            .enumerate()
+            .map(|(i, (name, arguments))| ToolCall {
+                id: format!("call_{}", i),
+                r#type: "function".to_owned(),
+                function: ToolCallFunction {
+                    name: Some(name),
+                    arguments: Some(arguments),
+                },
+            })
+            .collect();
+        let content = content.trim();
+        RawOutput {
+            thinking: split.thinking,
+            content: if content.is_empty() {
+                None
+            } else {
+                Some(content.to_owned())
+            },
+            tool_calls,
+        }
+    }
+
+    /// The reply as the server would have sent it without `use_raw_output`.
+    pub fn into_message(self) -> ChatCompletionMessageForResponse {
+        let mut message =
+            ChatCompletionMessageForResponse::new(MessageRole::assistant, self.content);
+        message.reasoning_content = self.thinking;
+        if !self.tool_calls.is_empty() {
+            message.tool_calls = Some(self.tool_calls);
+        }
+        message
+    }
+}
+
+// Returns the remaining text and the (name, arguments) of every call found.
+fn extract_tool_calls(text: &str) -> (String, Vec<(String, String)>) {
+    if let Some(start) = text.find("<tool_call>") {
+        let mut content = text[..start].to_owned();
+        let mut calls = Vec::new();
+        let mut rest = &text[start..];
+        while let Some(start) = rest.find("<tool_call>") {
+            content.push_str(&rest[..start]);
+            let after = &rest[start + "<tool_call>".len()..];
+            let (body, next) = match after.find("</tool_call>") {
+                Some(end) => (&after[..end], &after[end + "</tool_call>".len()..]),
+                None => (after, ""),
+            };
+            calls.extend(parse_calls(body));
+            rest = next;
+        }
+        content.push_str(rest);
+        return (content, calls);
+    }
+    for marker in ["[TOOL_CALLS]", "<|python_tag|>"] {
+        if let Some(start) = text.find(marker) {
+            let body = &text[start + marker.len()..];
+            return (text[..start].to_owned(), parse_calls(body));
+        }
+    }
+    (text.to_owned(), Vec::new())
+}
+
+// A JSON object, an array of objects, or objects separated by `;`.
+fn parse_calls(body: &str) -> Vec<(String, String)> {
+    let body = body.trim();
+    let values: Vec<Value> = match serde_json::from_str(body) {
+        Ok(Value::Array(values)) => values,
+        Ok(value) => vec![value],
+        Err(_) => body
+            .split(';')
+            .filter_map(|part| serde_json::from_str(part.trim()).ok())
+            .collect(),
+    };
+    values.iter().filter_map(call_from_value).collect()
+}
+
+fn call_from_value(value: &Value) -> Option<(String, String)> {
+    let name = value.get("name")?.as_str()?.to_owned();
+    let arguments = match value.get("arguments").or_else(|| value.get("parameters")) {
+        // Some models encode the arguments as a JSON string already.
+        Some(Value::String(arguments)) => arguments.clone(),
+        Some(arguments) => arguments.to_string(),
+        None => "{}".to_owned(),
+    };
+    Some((name, arguments))
+}
@@ -0,0 +1,99 @@
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::Arc;
+use std::time::Duration;
+
+pub type RetryHook = Arc<dyn Fn(&RetryAttempt) + Send + Sync>;
+
+#[derive(Debug, Clone)]
+pub struct RetryAttempt {
+    /// 1 for the first retry, 2 for the second, and so on.
+    pub attempt: u32,
+    pub delay: Duration,
+    /// `None` when the request failed before a response was received.
+    pub status_code: Option<i32>,
+    pub message: String,
+}
+
+/// Retries requests that fail with 429, a 5xx status, or a connection error.
+///
+/// Delays grow as `base_delay * 2^n`, capped at `max_delay`, unless the server
+/// says how long to wait with `Retry-After` or the rate limit reset headers;
+/// such waits longer than `max_retry_after` fail at once. POST requests are
+/// retried on error statuses, but only retried after a connection error when
+/// `retry_non_idempotent` is set, since the server may already have processed them.
+#[derive(Clone)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    /// The longest wait asked for by the server that is honored.
+    pub max_retry_after: Duration,
+    pub jitter: bool,
+    pub retry_non_idempotent: bool,
+    pub on_retry: Option<RetryHook>,
+}
+
+impl RetryPolicy {
+    pub fn new(max_retries: u32) -> Self {
+        Self {
+            max_retries,
+            ..Default::default()
+        }
+    }
+
+    pub fn on_retry<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(&RetryAttempt) + Send + Sync + 'static,
+    {
+        self.on_retry = Some(Arc::new(hook));
+        self
+    }
+
+    /// Delay before the given retry (starting at 1), or `None` once retries are exhausted.
+    pub fn delay_for(&self, attempt: u32) -> Option<Duration> {
+        if attempt == 0 || attempt > self.max_retries {
+            return None;
+        }
+        let factor = 2u32.saturating_pow(attempt - 1);
+        let delay = self.base_delay.saturating_mul(factor).min(self.max_delay);
+        if !self.jitter {
+            return Some(delay);
+        }
+        // Equal jitter: keep half of the delay and randomize the other half.
+        let half = delay / 2;
+        let random = RandomState::new().build_hasher().finish();
+        let extra = half.as_nanos() as u64;
+        let extra = if extra == 0 { 0 } else { random % (extra + 1) };
+        Some(half + Duration::from_nanos(extra))
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 2,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(8),
+            max_retry_after: Duration::from_secs(60),
+            jitter: true,
+            retry_non_idempotent: false,
+            on_retry: None,
+        }
+    }
+}
+
+impl fmt::Debug for RetryPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("RetryPolicy")
+            .field("max_retries", &self.max_retries)
+            .field("base_delay", &self.base_delay)
+            .field("max_delay", &self.max_delay)
+            .field("max_retry_after", &self.max_retry_after)
+            .field("jitter", &self.jitter)
+            .field("retry_non_idempotent", &self.retry_non_idempotent)
+            .field("on_retry", &self.on_retry.is_some())
+            .finish()
+    }
+}
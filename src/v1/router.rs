@@ -0,0 +1,266 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::v1::api::Client;
+use crate::v1::chat_completion::{
+    ChatCompletionRequest, ChatCompletionResponse, ChatCompletionStreamResponse,
+};
+use crate::v1::client::OpenAIClient;
+use crate::v1::completion::{CompletionRequest, CompletionResponse};
+use crate::v1::embedding::{EmbeddingRequest, EmbeddingResponse};
+use crate::v1::error::APIError;
+use crate::v1::model::ListModel;
+use crate::v1::moderation::{CreateModerationRequest, CreateModerationResponse};
+use crate::v1::stream::SseStream;
+
+const DEFAULT_COOLDOWN: Duration = Duration::from_secs(30);
+
+/// One provider behind a `RouterClient`.
+#[derive(Clone)]
+pub struct Endpoint {
+    pub name: String,
+    pub client: Client,
+    /// Model names to send to this provider instead of the requested ones.
+    pub models: HashMap<String, String>,
+}
+
+impl Endpoint {
+    pub fn new<S: Into<String>>(name: S, client: Client) -> Self {
+        Self {
+            name: name.into(),
+            client,
+            models: HashMap::new(),
+        }
+    }
+
+    /// Sends `to` to this provider when `from` is requested, e.g. to map
+    /// `gpt-4o` to the name a fallback server serves its model under.
+    pub fn model<F: Into<String>, T: Into<String>>(mut self, from: F, to: T) -> Self {
+        self.models.insert(from.into(), to.into());
+        self
+    }
+
+    fn rewrite(&self, model: &mut String) {
+        if let Some(to) = self.models.get(model.as_str()) {
+            *model = to.clone();
+        }
+    }
+}
+
+/// Health of an endpoint as seen by a `RouterClient`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EndpointHealth {
+    pub name: String,
+    /// Failures since the last success.
+    pub consecutive_failures: u32,
+    /// Whether the endpoint failed within the cooldown and is tried last.
+    pub healthy: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Health {
+    consecutive_failures: u32,
+    down_until: Option<Instant>,
+}
+
+/// A client sending each request to the first of several OpenAI-compatible
+/// endpoints that can serve it, e.g. a self-hosted vLLM server with OpenAI
+/// as a fallback.
+///
+/// A request moves on to the next endpoint on connection errors, 5xx
+/// responses and unknown models, after the endpoint's own `retry_policy` is
+/// exhausted. Other errors, such as invalid requests, are returned as is.
+/// An endpoint that failed is tried after the healthy ones until its cooldown
+/// passes. Streams fail over only while being opened.
+///
+/// ```no_run
+/// use openai_api_rs::v1::api::Client;
+/// use openai_api_rs::v1::router::{Endpoint, RouterClient};
+///
+/// let router = RouterClient::new()
+///     .endpoint(
+///         Endpoint::new(
+///             "vllm",
+///             Client::new_with_endpoint("http://localhost:8000/v1".to_owned(), String::new()),
+///         )
+///         .model("gpt-4o", "meta-llama/Llama-3.1-8B-Instruct"),
+///     )
+///     .endpoint(Endpoint::new("openai", Client::new("sk-...".to_owned())));
+/// ```
+pub struct RouterClient {
+    endpoints: Vec<Endpoint>,
+    health: Mutex<Vec<Health>>,
+    cooldown: Duration,
+}
+
+impl RouterClient {
+    pub fn new() -> Self {
+        Self {
+            endpoints: Vec::new(),
+            health: Mutex::new(Vec::new()),
+            cooldown: DEFAULT_COOLDOWN,
+        }
+    }
+
+    /// Adds a fallback, tried after the endpoints added before it.
+    pub fn endpoint(mut self, endpoint: Endpoint) -> Self {
+        self.endpoints.push(endpoint);
+        self.health.get_mut().unwrap().push(Health::default());
+        self
+    }
+
+    /// How long a failed endpoint is tried last, 30 seconds by default.
+    pub fn cooldown(mut self, cooldown: Duration) -> Self {
+        self.cooldown = cooldown;
+        self
+    }
+
+    pub fn endpoints(&self) -> &[Endpoint] {
+        &self.endpoints
+    }
+
+    pub fn health(&self) -> Vec<EndpointHealth> {
+        let now = Instant::now();
+        let health = self.health.lock().unwrap();
+        self.endpoints
+            .iter()
+            .zip(health.iter())
+            .map(|(endpoint, health)| EndpointHealth {
+                name: endpoint.name.clone(),
+                consecutive_failures: health.consecutive_failures,
+                healthy: health.down_until.is_none_or(|until| until <= now),
+            })
+            .collect()
+    }
+
+    /// Sends `request` to each endpoint in turn until one answers, with the
+    /// model rewritten for the endpoint by `set_model`.
+    pub fn route<R, T, F, S>(&self, request: &R, set_model: F, send: S) -> Result<T, APIError>
+    where
+        R: Clone,
+        F: Fn(&Endpoint, &mut R),
+        S: Fn(&Client, R) -> Result<T, APIError>,
+    {
+        let mut last_error = None;
+        for index in self.order() {
+            let endpoint = &self.endpoints[index];
+            let mut request = request.clone();
+            set_model(endpoint, &mut request);
+            match send(&endpoint.client, request) {
+                Ok(response) => {
+                    self.health.lock().unwrap()[index] = Health::default();
+                    return Ok(response);
+                }
+                Err(error) if should_fail_over(&error) => {
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!(
+                        endpoint = %endpoint.name,
+                        status_code = error.status_code(),
+                        error = %error.message(),
+                        "failing over to the next endpoint"
+                    );
+                    let mut health = self.health.lock().unwrap();
+                    health[index].consecutive_failures += 1;
+                    health[index].down_until = Some(Instant::now() + self.cooldown);
+                    last_error = Some(error);
+                }
+                Err(error) => return Err(error),
+            }
+        }
+        Err(last_error
+            .unwrap_or_else(|| APIError::InvalidRequest("the router has no endpoints".to_owned())))
+    }
+
+    // Healthy endpoints first, each group in the order the endpoints were added.
+    fn order(&self) -> Vec<usize> {
+        let now = Instant::now();
+        let health = self.health.lock().unwrap();
+        let (mut healthy, down): (Vec<usize>, Vec<usize>) = (0..self.endpoints.len())
+            .partition(|&i| health[i].down_until.is_none_or(|until| until <= now));
+        healthy.extend(down);
+        healthy
+    }
+}
+
+impl Default for RouterClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Whether another endpoint may succeed where this error was returned:
+/// connection errors, server errors and models the endpoint does not serve.
+pub fn should_fail_over(error: &APIError) -> bool {
+    match error {
+        APIError::Connection(_) => true,
+        APIError::Status { status_code, .. } if *status_code >= 500 => true,
+        APIError::Status { status_code, .. } => {
+            let detail = error.detail();
+            detail.and_then(|detail| detail.code.as_deref()) == Some("model_not_found")
+                // vLLM answers an unknown model with a plain 404.
+                || (*status_code == 404
+                    && detail.is_some_and(|detail| detail.message.contains("model")))
+        }
+        _ => false,
+    }
+}
+
+impl OpenAIClient for RouterClient {
+    fn chat_completion(
+        &self,
+        req: ChatCompletionRequest,
+    ) -> Result<ChatCompletionResponse, APIError> {
+        self.route(
+            &req,
+            |endpoint, req| endpoint.rewrite(&mut req.model),
+            Client::chat_completion,
+        )
+    }
+
+    fn chat_completion_stream(
+        &self,
+        req: ChatCompletionRequest,
+    ) -> Result<SseStream<ChatCompletionStreamResponse>, APIError> {
+        self.route(
+            &req,
+            |endpoint, req| endpoint.rewrite(&mut req.model),
+            Client::chat_completion_stream,
+        )
+    }
+
+    fn completion(&self, req: CompletionRequest) -> Result<CompletionResponse, APIError> {
+        self.route(
+            &req,
+            |endpoint, req| endpoint.rewrite(&mut req.model),
+            Client::completion,
+        )
+    }
+
+    fn embedding(&self, req: EmbeddingRequest) -> Result<EmbeddingResponse, APIError> {
+        self.route(
+            &req,
+            |endpoint, req| endpoint.rewrite(&mut req.model),
+            Client::embedding,
+        )
+    }
+
+    fn create_moderation(
+        &self,
+        req: CreateModerationRequest,
+    ) -> Result<CreateModerationResponse, APIError> {
+        self.route(
+            &req,
+            |endpoint, req| {
+                if let Some(model) = &mut req.model {
+                    endpoint.rewrite(model);
+                }
+            },
+            Client::create_moderation,
+        )
+    }
+
+    fn list_models(&self) -> Result<ListModel, APIError> {
+        self.route(&(), |_, _| {}, |client, ()| client.list_models())
+    }
+}
@@ -1,8 +1,16 @@
 use super::thread::CreateThreadRequest;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::collections::HashMap;
 
-use crate::impl_builder_methods;
+use crate::v1::assistant::AssistantTool;
+use crate::v1::chat_completion::ToolCall;
+use crate::v1::common::{self, ResponseHeaders};
+use crate::v1::error::{APIError, ErrorDetail};
+use crate::v1::message::{MessageDeltaObject, MessageObject};
+use crate::v1::stream::{AbortHandle, SseStream, StreamingResponse};
+use crate::v1::thread::ThreadObject;
+use crate::{impl_builder_methods, impl_response_headers};
 
 #[derive(Debug, Serialize, Clone)]
 pub struct CreateRunRequest {
@@ -12,9 +20,11 @@ pub struct CreateRunRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub instructions: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub tools: Option<Vec<HashMap<String, String>>>,
+    pub tools: Option<Vec<AssistantTool>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub metadata: Option<HashMap<String, String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stream: Option<bool>,
 }
 
 impl CreateRunRequest {
@@ -25,6 +35,7 @@ impl CreateRunRequest {
             instructions: None,
             tools: None,
             metadata: None,
+            stream: None,
         }
     }
 }
@@ -33,8 +44,9 @@ impl_builder_methods!(
     CreateRunRequest,
     model: String,
     instructions: String,
-    tools: Vec<HashMap<String, String>>,
-    metadata: HashMap<String, String>
+    tools: Vec<AssistantTool>,
+    metadata: HashMap<String, String>,
+    stream: bool
 );
 
 #[derive(Debug, Serialize, Clone)]
@@ -61,6 +73,7 @@ impl_builder_methods!(
 );
 
 #[derive(Debug, Deserialize, Serialize)]
+#[non_exhaustive]
 pub struct RunObject {
     pub id: String,
     pub object: String,
@@ -69,9 +82,9 @@ pub struct RunObject {
     pub assistant_id: String,
     pub status: String,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub required_action: Option<HashMap<String, String>>,
+    pub required_action: Option<RequiredAction>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub last_error: Option<String>,
+    pub last_error: Option<RunError>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub expires_at: Option<i64>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -84,18 +97,74 @@ pub struct RunObject {
     pub completed_at: Option<i64>,
     pub model: String,
     pub instructions: Option<String>,
-    pub tools: Vec<HashMap<String, String>>,
-    pub file_ids: Vec<String>,
+    pub tools: Vec<AssistantTool>,
     pub metadata: HashMap<String, String>,
+    #[serde(skip)]
+    pub headers: Option<HashMap<String, String>>,
+}
+
+impl RunObject {
+    /// Whether the run has stopped and will not change status on its own.
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self.status.as_str(),
+            "completed" | "failed" | "cancelled" | "expired" | "incomplete"
+        )
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct RequiredAction {
+    #[serde(rename = "type")]
+    pub action_type: String,
+    pub submit_tool_outputs: SubmitToolOutputs,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct SubmitToolOutputs {
+    pub tool_calls: Vec<ToolCall>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct RunError {
+    pub code: String,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ToolOutput {
+    pub tool_call_id: String,
+    pub output: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct SubmitToolOutputsRequest {
+    pub tool_outputs: Vec<ToolOutput>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stream: Option<bool>,
+}
+
+impl SubmitToolOutputsRequest {
+    pub fn new(tool_outputs: Vec<ToolOutput>) -> Self {
+        Self {
+            tool_outputs,
+            stream: None,
+        }
+    }
 }
 
+impl_builder_methods!(SubmitToolOutputsRequest, stream: bool);
+
 #[derive(Debug, Deserialize, Serialize)]
+#[non_exhaustive]
 pub struct ListRun {
     pub object: String,
     pub data: Vec<RunObject>,
-    pub first_id: String,
-    pub last_id: String,
+    pub first_id: Option<String>,
+    pub last_id: Option<String>,
     pub has_more: bool,
+    #[serde(skip)]
+    pub headers: Option<HashMap<String, String>>,
 }
 
 #[derive(Debug, Serialize, Clone)]
@@ -108,12 +177,39 @@ pub struct CreateThreadAndRunRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub instructions: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub tools: Option<Vec<HashMap<String, String>>>,
+    pub tools: Option<Vec<AssistantTool>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub metadata: Option<HashMap<String, String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stream: Option<bool>,
+}
+
+impl CreateThreadAndRunRequest {
+    pub fn new(assistant_id: String) -> Self {
+        Self {
+            assistant_id,
+            thread: None,
+            model: None,
+            instructions: None,
+            tools: None,
+            metadata: None,
+            stream: None,
+        }
+    }
 }
 
+impl_builder_methods!(
+    CreateThreadAndRunRequest,
+    thread: CreateThreadRequest,
+    model: String,
+    instructions: String,
+    tools: Vec<AssistantTool>,
+    metadata: HashMap<String, String>,
+    stream: bool
+);
+
 #[derive(Debug, Deserialize, Serialize, Clone)]
+#[non_exhaustive]
 pub struct RunStepObject {
     pub id: String,
     pub object: String,
@@ -124,9 +220,9 @@ pub struct RunStepObject {
     #[serde(rename = "type")]
     pub run_step_type: String,
     pub status: String,
-    pub step_details: HashMap<String, String>,
+    pub step_details: Value,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub last_error: Option<String>,
+    pub last_error: Option<RunError>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub expires_at: Option<i64>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -138,13 +234,148 @@ pub struct RunStepObject {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub completed_at: Option<i64>,
     pub metadata: HashMap<String, String>,
+    #[serde(skip)]
+    pub headers: Option<HashMap<String, String>>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
+#[non_exhaustive]
 pub struct ListRunStep {
     pub object: String,
     pub data: Vec<RunStepObject>,
-    pub first_id: String,
-    pub last_id: String,
+    pub first_id: Option<String>,
+    pub last_id: Option<String>,
     pub has_more: bool,
+    #[serde(skip)]
+    pub headers: Option<HashMap<String, String>>,
+}
+
+/// A `thread.run.step.delta` event: progress of a step being streamed, such
+/// as tool call arguments as they are generated.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct RunStepDeltaObject {
+    pub id: String,
+    pub object: String,
+    pub delta: RunStepDelta,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct RunStepDelta {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub step_details: Option<Value>,
+}
+
+/// An event of a streamed run, named after its SSE event type.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum AssistantStreamEvent {
+    /// `thread.created`, when the run was started with
+    /// `create_thread_and_run_stream`.
+    ThreadCreated(ThreadObject),
+    /// A run status change, e.g. `thread.run.requires_action`.
+    Run {
+        event: String,
+        run: RunObject,
+    },
+    /// A run step status change, e.g. `thread.run.step.completed`.
+    RunStep {
+        event: String,
+        step: RunStepObject,
+    },
+    RunStepDelta(RunStepDeltaObject),
+    /// A message status change, e.g. `thread.message.completed`.
+    Message {
+        event: String,
+        message: MessageObject,
+    },
+    MessageDelta(MessageDeltaObject),
+    /// The run failed on the server side; the stream ends after it.
+    Error(ErrorDetail),
+    /// An event this crate does not know yet.
+    Unknown {
+        event: String,
+        data: Value,
+    },
+}
+
+impl AssistantStreamEvent {
+    pub fn from_sse(event: &str, data: &str) -> Result<Self, APIError> {
+        let parsed = match event {
+            "thread.created" => Self::ThreadCreated(common::from_slice(data.as_bytes())?),
+            "thread.run.step.delta" => Self::RunStepDelta(common::from_slice(data.as_bytes())?),
+            "thread.message.delta" => Self::MessageDelta(common::from_slice(data.as_bytes())?),
+            "error" => Self::Error(
+                ErrorDetail::from_body(data)
+                    .ok_or_else(|| APIError::Decode(format!("unexpected error event: {}", data)))?,
+            ),
+            _ if event.starts_with("thread.run.step.") => Self::RunStep {
+                event: event.to_owned(),
+                step: common::from_slice(data.as_bytes())?,
+            },
+            _ if event.starts_with("thread.run.") => Self::Run {
+                event: event.to_owned(),
+                run: common::from_slice(data.as_bytes())?,
+            },
+            _ if event.starts_with("thread.message.") => Self::Message {
+                event: event.to_owned(),
+                message: common::from_slice(data.as_bytes())?,
+            },
+            _ => Self::Unknown {
+                event: event.to_owned(),
+                data: common::from_slice(data.as_bytes())?,
+            },
+        };
+        Ok(parsed)
+    }
+}
+
+/// Iterator over the events of a streamed run, ending after `done`.
+pub struct RunEventStream {
+    inner: SseStream<Value>,
 }
+
+impl RunEventStream {
+    pub fn new(response: StreamingResponse) -> Self {
+        Self {
+            inner: SseStream::new(response),
+        }
+    }
+
+    pub fn abort_handle(&self) -> AbortHandle {
+        self.inner.abort_handle()
+    }
+
+    pub fn abort(&mut self) {
+        self.inner.abort();
+    }
+}
+
+impl ResponseHeaders for RunEventStream {
+    fn headers(&self) -> Option<&HashMap<String, String>> {
+        self.inner.headers()
+    }
+
+    fn set_headers(&mut self, headers: HashMap<String, String>) {
+        self.inner.set_headers(headers);
+    }
+}
+
+impl Iterator for RunEventStream {
+    type Item = Result<AssistantStreamEvent, APIError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (event, data) = match self.inner.next_raw()? {
+            Ok(event) => event,
+            Err(e) => return Some(Err(e)),
+        };
+        let event = event.unwrap_or_default();
+        Some(
+            AssistantStreamEvent::from_sse(&event, &data).map_err(|e| match e {
+                APIError::Decode(e) => APIError::Decode(format!("{} event: {}", event, e)),
+                e => e,
+            }),
+        )
+    }
+}
+
+impl_response_headers!(RunObject, ListRun, RunStepObject, ListRunStep);
@@ -0,0 +1,48 @@
+use serde_json::Value;
+
+use crate::v1::chat_completion::{ChatCompletionRequest, ChatCompletionResponse};
+use crate::v1::error::APIError;
+
+/// Checks every choice of a structured output reply against the schema the
+/// request asked for. Requests without a schema always pass.
+pub fn validate_response(
+    req: &ChatCompletionRequest,
+    res: &ChatCompletionResponse,
+) -> Result<(), APIError> {
+    let schema = match req.output_schema() {
+        Some(schema) => schema,
+        None => return Ok(()),
+    };
+    let validator = jsonschema::validator_for(&schema)
+        .map_err(|e| APIError::InvalidRequest(format!("invalid output schema: {}", e)))?;
+    for choice in &res.choices {
+        // Refusals and tool calls come without content.
+        let content = match &choice.message.content {
+            Some(content) => content,
+            None => continue,
+        };
+        let instance: Value = match serde_json::from_str(content) {
+            Ok(instance) => instance,
+            Err(e) => {
+                return Err(APIError::SchemaValidation {
+                    content: content.clone(),
+                    errors: vec![format!("not valid JSON: {}", e)],
+                })
+            }
+        };
+        let errors: Vec<String> = validator
+            .iter_errors(&instance)
+            .map(|e| match e.instance_path().to_string() {
+                path if path.is_empty() => e.to_string(),
+                path => format!("{}: {}", path, e),
+            })
+            .collect();
+        if !errors.is_empty() {
+            return Err(APIError::SchemaValidation {
+                content: content.clone(),
+                errors,
+            });
+        }
+    }
+    Ok(())
+}
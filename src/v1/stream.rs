@@ -0,0 +1,351 @@
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::io::{self, Read};
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use crate::v1::common::{self, ResponseHeaders, Usage};
+use crate::v1::error::APIError;
+use crate::v1::throttle::ThrottlePermit;
+
+const DONE_SENTINEL: &str = "[DONE]";
+
+pub type ByteStream = Box<dyn Iterator<Item = Result<u8, APIError>> + Send>;
+
+/// A response whose body is read incrementally.
+pub struct StreamingResponse {
+    pub status_code: i32,
+    pub headers: HashMap<String, String>,
+    pub body: ByteStream,
+}
+
+impl StreamingResponse {
+    pub fn from_bytes(status_code: i32, headers: HashMap<String, String>, body: Vec<u8>) -> Self {
+        Self {
+            status_code,
+            headers,
+            body: Box::new(body.into_iter().map(Ok)),
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl From<minreq::ResponseLazy> for StreamingResponse {
+    fn from(mut response: minreq::ResponseLazy) -> Self {
+        let headers = std::mem::take(&mut response.headers);
+        Self {
+            status_code: response.status_code,
+            headers,
+            // `ResponseLazy`'s `Read` impl blocks until the buffer is full, so bytes
+            // are taken from its iterator to hand out events as soon as they arrive.
+            body: Box::new(response.map(|b| b.map(|(byte, _)| byte).map_err(APIError::from))),
+        }
+    }
+}
+
+// Each read blocks until `buf` is full or the body ends, so callers that want
+// low latency (e.g. audio playback) should read with small buffers.
+impl Read for StreamingResponse {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut read = 0;
+        while read < buf.len() {
+            match self.body.next() {
+                Some(Ok(byte)) => {
+                    buf[read] = byte;
+                    read += 1;
+                }
+                Some(Err(e)) => return Err(io::Error::other(e.to_string())),
+                None => break,
+            }
+        }
+        Ok(read)
+    }
+}
+
+/// Cancels an [`SseStream`] from another thread.
+///
+/// The stream checks the handle between bytes, so an abort takes effect as soon
+/// as the server sends anything further; the connection is dropped at that point.
+#[derive(Debug, Clone, Default)]
+pub struct AbortHandle {
+    aborted: Arc<AtomicBool>,
+}
+
+impl AbortHandle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn abort(&self) {
+        self.aborted.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_aborted(&self) -> bool {
+        self.aborted.load(Ordering::SeqCst)
+    }
+}
+
+/// Iterator over the server-sent events of a streaming response.
+///
+/// Each `data:` payload is deserialized into `T`; the stream ends when the
+/// server sends `[DONE]` or closes the connection.
+pub struct SseStream<T> {
+    body: Option<ByteStream>,
+    headers: HashMap<String, String>,
+    abort_handle: AbortHandle,
+    // Settled from the `usage` chunk, kept at its estimate when the stream
+    // ends without one, and freed when the stream fails or is dropped early.
+    permit: Option<ThrottlePermit>,
+    _marker: PhantomData<T>,
+}
+
+#[derive(Deserialize)]
+struct UsageChunk {
+    usage: Option<Usage>,
+}
+
+impl<T: DeserializeOwned> SseStream<T> {
+    pub fn new(response: StreamingResponse) -> Self {
+        Self {
+            headers: response.headers,
+            body: Some(response.body),
+            abort_handle: AbortHandle::new(),
+            permit: None,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Ties a throttle reservation to the stream, as `chat_completion_stream`
+    /// does: it is settled from a `usage` chunk, or freed if the stream fails.
+    pub fn with_throttle_permit(mut self, permit: ThrottlePermit) -> Self {
+        self.permit = Some(permit);
+        self
+    }
+
+    pub fn with_abort_handle(mut self, abort_handle: AbortHandle) -> Self {
+        self.abort_handle = abort_handle;
+        self
+    }
+
+    pub fn abort_handle(&self) -> AbortHandle {
+        self.abort_handle.clone()
+    }
+
+    pub fn abort(&mut self) {
+        self.abort_handle.abort();
+        self.body = None;
+    }
+
+    fn read_line(&mut self) -> Result<Option<String>, APIError> {
+        let mut line = Vec::new();
+        loop {
+            if self.abort_handle.is_aborted() {
+                self.body = None;
+                return Ok(None);
+            }
+            let body = match self.body.as_mut() {
+                Some(body) => body,
+                None => return Ok(None),
+            };
+            match body.next() {
+                Some(Ok(b'\n')) => break,
+                Some(Ok(byte)) => line.push(byte),
+                Some(Err(e)) => return Err(e),
+                None if line.is_empty() => return Ok(None),
+                None => break,
+            }
+        }
+        if line.last() == Some(&b'\r') {
+            line.pop();
+        }
+        String::from_utf8(line)
+            .map(Some)
+            .map_err(|e| APIError::Decode(e.to_string()))
+    }
+
+    fn read_event(&mut self) -> Result<Option<(Option<String>, String)>, APIError> {
+        let mut event: Option<String> = None;
+        let mut data: Option<String> = None;
+        while let Some(line) = self.read_line()? {
+            if line.is_empty() {
+                if data.is_some() {
+                    break;
+                }
+                continue;
+            }
+            // Comments, `id` and `retry` are skipped.
+            if let Some(value) = line.strip_prefix("event:") {
+                event = Some(value.trim().to_owned());
+            } else if let Some(value) = line.strip_prefix("data:") {
+                let value = value.strip_prefix(' ').unwrap_or(value);
+                match data {
+                    Some(ref mut data) => {
+                        data.push('\n');
+                        data.push_str(value);
+                    }
+                    None => data = Some(value.to_owned()),
+                }
+            }
+        }
+        Ok(data.map(|data| (event, data)))
+    }
+
+    // Keeps the throttle estimate of a stream that completed without reporting
+    // usage, and frees it otherwise.
+    fn end_permit(&mut self, completed: bool) {
+        if let Some(permit) = self.permit.take() {
+            if completed {
+                permit.keep();
+            }
+        }
+    }
+
+    /// The next event's name, if the server sent one, and its raw `data`.
+    pub(crate) fn next_raw(&mut self) -> Option<Result<(Option<String>, String), APIError>> {
+        self.body.as_ref()?;
+        let (event, data) = match self.read_event() {
+            Ok(Some(event)) => event,
+            Ok(None) => {
+                self.body = None;
+                self.end_permit(true);
+                return None;
+            }
+            Err(e) => {
+                self.body = None;
+                self.end_permit(false);
+                return Some(Err(e));
+            }
+        };
+        if self.abort_handle.is_aborted() || data.trim() == DONE_SENTINEL {
+            #[cfg(feature = "tracing")]
+            tracing::debug!(aborted = self.abort_handle.is_aborted(), "stream finished");
+            self.body = None;
+            self.end_permit(!self.abort_handle.is_aborted());
+            return None;
+        }
+        if self.permit.is_some() && data.contains("\"total_tokens\"") {
+            if let Ok(UsageChunk { usage: Some(usage) }) = serde_json::from_str(&data) {
+                if let Some(permit) = self.permit.take() {
+                    permit.settle(usage.total_tokens.max(0) as u32);
+                }
+            }
+        }
+        #[cfg(feature = "tracing")]
+        tracing::trace!(bytes = data.len(), "stream chunk");
+        Some(Ok((event, data)))
+    }
+}
+
+impl<T> ResponseHeaders for SseStream<T> {
+    fn headers(&self) -> Option<&HashMap<String, String>> {
+        Some(&self.headers)
+    }
+
+    fn set_headers(&mut self, headers: HashMap<String, String>) {
+        self.headers = headers;
+    }
+}
+
+impl<T: DeserializeOwned> Iterator for SseStream<T> {
+    type Item = Result<T, APIError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let data = match self.next_raw()? {
+            Ok((_, data)) => data,
+            Err(e) => return Some(Err(e)),
+        };
+        Some(
+            common::from_slice::<T>(data.as_bytes()).map_err(|e| match e {
+                APIError::Decode(e) => APIError::Decode(format!("{}: {}", e, data)),
+                e => e,
+            }),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    fn stream(body: &str) -> SseStream<Value> {
+        SseStream::new(StreamingResponse::from_bytes(
+            200,
+            HashMap::new(),
+            body.into(),
+        ))
+    }
+
+    fn raw_events(body: &str) -> Vec<(Option<String>, String)> {
+        let mut stream = stream(body);
+        let mut events = Vec::new();
+        while let Some(event) = stream.next_raw() {
+            events.push(event.unwrap());
+        }
+        events
+    }
+
+    #[test]
+    fn joins_multi_line_data() {
+        let events = raw_events("data: {\"a\":\ndata:1}\n\ndata: second\n\n");
+        assert_eq!(
+            events,
+            vec![
+                (None, "{\"a\":\n1}".to_owned()),
+                (None, "second".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn reads_event_names_and_skips_other_fields() {
+        let body = ": keep-alive\nid: 1\nretry: 10\nevent: response.done\ndata: {}\n\n\n\nevent:  ping \ndata: 1\n\n";
+        assert_eq!(
+            raw_events(body),
+            vec![
+                (Some("response.done".to_owned()), "{}".to_owned()),
+                (Some("ping".to_owned()), "1".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn stops_at_done() {
+        let values: Vec<Value> = stream("data: {\"n\":1}\n\ndata: [DONE]\n\ndata: {\"n\":2}\n\n")
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(values, vec![json!({ "n": 1 })]);
+    }
+
+    #[test]
+    fn accepts_crlf_line_endings() {
+        let values: Vec<Value> = stream(
+            "event: x\r\ndata: {\"n\":1}\r\n\r\ndata: {\"n\":2}\r\n\r\ndata: [DONE]\r\n\r\n",
+        )
+        .map(Result::unwrap)
+        .collect();
+        assert_eq!(values, vec![json!({ "n": 1 }), json!({ "n": 2 })]);
+    }
+
+    #[test]
+    fn yields_a_trailing_event_without_blank_line() {
+        assert_eq!(raw_events("data: last"), vec![(None, "last".to_owned())]);
+    }
+
+    #[test]
+    fn reports_undecodable_data() {
+        let mut stream = stream("data: not json\n\n");
+        match stream.next() {
+            Some(Err(APIError::Decode(message))) => {
+                assert!(message.ends_with("not json"), "{}", message)
+            }
+            other => panic!(
+                "expected a decode error, got {:?}",
+                other.map(|r| r.is_ok())
+            ),
+        }
+        assert!(stream.next().is_none());
+    }
+}
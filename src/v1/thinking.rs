@@ -0,0 +1,85 @@
+/// Tag pairs that wrap reasoning in the content of a reply, tried in order.
+pub const THINKING_TAGS: &[(&str, &str)] =
+    &[("<think>", "</think>"), ("<thinking>", "</thinking>")];
+
+/// An assistant reply separated into its reasoning and its answer.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ThinkingSplit {
+    /// The reasoning, without the surrounding tags; `None` when the reply has none.
+    pub thinking: Option<String>,
+    pub content: String,
+}
+
+/// Splits reasoning wrapped in any of `THINKING_TAGS` from the answer.
+pub fn split_thinking(text: &str) -> ThinkingSplit {
+    THINKING_TAGS
+        .iter()
+        .find_map(|(open, close)| split_tagged(text, open, close))
+        .unwrap_or_else(|| ThinkingSplit {
+            thinking: None,
+            content: text.to_owned(),
+        })
+}
+
+/// Splits reasoning wrapped in `open` and `close` from the answer.
+///
+/// Several reasoning blocks are joined with blank lines. A block missing its
+/// closing tag, as in a reply cut off by `max_tokens`, runs to the end of the
+/// text. A closing tag without an opening one, as sent by chat templates that
+/// put the opening tag in the prompt, ends reasoning that starts the reply.
+pub fn split_thinking_with(text: &str, open: &str, close: &str) -> ThinkingSplit {
+    split_tagged(text, open, close).unwrap_or_else(|| ThinkingSplit {
+        thinking: None,
+        content: text.to_owned(),
+    })
+}
+
+fn split_tagged(text: &str, open: &str, close: &str) -> Option<ThinkingSplit> {
+    let first_open = text.find(open);
+    let first_close = text.find(close);
+    let mut thinking = Vec::new();
+    let mut content = String::new();
+    let mut rest = text;
+
+    match (first_open, first_close) {
+        (None, None) => return None,
+        (None, Some(end)) => {
+            thinking.push(&text[..end]);
+            rest = &text[end + close.len()..];
+        }
+        (Some(start), Some(end)) if end < start => {
+            thinking.push(&text[..end]);
+            rest = &text[end + close.len()..];
+        }
+        _ => {}
+    }
+    while let Some(start) = rest.find(open) {
+        content.push_str(&rest[..start]);
+        let after = &rest[start + open.len()..];
+        match after.find(close) {
+            Some(end) => {
+                thinking.push(&after[..end]);
+                rest = &after[end + close.len()..];
+            }
+            None => {
+                thinking.push(after);
+                rest = "";
+            }
+        }
+    }
+    content.push_str(rest);
+
+    let thinking: Vec<&str> = thinking
+        .into_iter()
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .collect();
+    Some(ThinkingSplit {
+        thinking: if thinking.is_empty() {
+            None
+        } else {
+            Some(thinking.join("\n\n"))
+        },
+        content: content.trim().to_owned(),
+    })
+}
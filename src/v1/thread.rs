@@ -1,7 +1,8 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-use crate::impl_builder_methods;
+use crate::v1::message::Attachment;
+use crate::{impl_builder_methods, impl_response_headers};
 
 #[derive(Debug, Serialize, Clone)]
 pub struct CreateThreadRequest {
@@ -33,20 +34,26 @@ impl_builder_methods!(
 );
 
 #[derive(Debug, Deserialize, Serialize)]
+#[non_exhaustive]
 pub struct ThreadObject {
     pub id: String,
     pub object: String,
     pub created_at: i64,
     pub metadata: HashMap<String, String>,
+    #[serde(skip)]
+    pub headers: Option<HashMap<String, String>>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Message {
     pub role: MessageRole,
     pub content: String,
+    #[deprecated(since = "4.0.8", note = "Assistants v2 takes files as `attachments`")]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub file_ids: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub attachments: Option<Vec<Attachment>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub metadata: Option<HashMap<String, String>>,
 }
 
@@ -81,3 +88,5 @@ impl_builder_methods!(
     ModifyThreadRequest,
     metadata: HashMap<String, String>
 );
+
+impl_response_headers!(ThreadObject);
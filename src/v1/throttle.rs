@@ -0,0 +1,231 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::v1::chat_completion::ChatCompletionRequest;
+
+const WINDOW: Duration = Duration::from_secs(60);
+
+#[derive(Debug)]
+struct Reservation {
+    id: u64,
+    at: Instant,
+    tokens: u32,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    next_id: u64,
+    reservations: VecDeque<Reservation>,
+}
+
+/// Keeps chat requests within per-minute request and token budgets.
+///
+/// Each request reserves its estimated tokens over a sliding one-minute window
+/// and blocks until the window has room; the reservation is corrected with the
+/// reported usage once the response arrives, and its tokens are freed when
+/// the request fails.
+#[derive(Debug)]
+pub struct Throttle {
+    pub requests_per_minute: Option<u32>,
+    pub tokens_per_minute: Option<u32>,
+    state: Mutex<State>,
+}
+
+impl Throttle {
+    pub fn new(requests_per_minute: Option<u32>, tokens_per_minute: Option<u32>) -> Self {
+        Self {
+            requests_per_minute,
+            tokens_per_minute,
+            state: Mutex::new(State::default()),
+        }
+    }
+
+    /// Blocks until the request fits the budget and returns its reservation id.
+    pub fn acquire(&self, tokens: u32) -> u64 {
+        loop {
+            let wait = {
+                let mut state = self.state.lock().unwrap();
+                let now = Instant::now();
+                while let Some(front) = state.reservations.front() {
+                    if now.duration_since(front.at) < WINDOW {
+                        break;
+                    }
+                    state.reservations.pop_front();
+                }
+                if self.has_room(&state, tokens) {
+                    let id = state.next_id;
+                    state.next_id += 1;
+                    state.reservations.push_back(Reservation {
+                        id,
+                        at: now,
+                        tokens,
+                    });
+                    return id;
+                }
+                let oldest = state.reservations.front().map(|r| r.at).unwrap_or(now);
+                (oldest + WINDOW).saturating_duration_since(now)
+            };
+            std::thread::sleep(wait.max(Duration::from_millis(10)));
+        }
+    }
+
+    /// Like `acquire`, but returns a permit that frees the reserved tokens
+    /// when dropped without being settled.
+    pub fn reserve(self: &Arc<Self>, tokens: u32) -> ThrottlePermit {
+        ThrottlePermit {
+            id: self.acquire(tokens),
+            throttle: Arc::clone(self),
+            done: false,
+        }
+    }
+
+    /// Replaces the estimate of a reservation with the tokens actually used.
+    pub fn settle(&self, id: u64, tokens: u32) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(reservation) = state.reservations.iter_mut().find(|r| r.id == id) {
+            reservation.tokens = tokens;
+        }
+    }
+
+    /// Rough prompt size (four characters per token) plus the completion budget.
+    pub fn estimate_tokens(req: &ChatCompletionRequest) -> u32 {
+        let prompt = serde_json::to_string(&req.messages)
+            .map(|s| s.len() / 4)
+            .unwrap_or(0);
+        let completion = req.completion_token_limit().unwrap_or(0).max(0) as usize;
+        (prompt + completion).min(u32::MAX as usize) as u32
+    }
+
+    fn has_room(&self, state: &State, tokens: u32) -> bool {
+        if state.reservations.is_empty() {
+            return true;
+        }
+        if let Some(limit) = self.requests_per_minute {
+            if state.reservations.len() >= limit as usize {
+                return false;
+            }
+        }
+        if let Some(limit) = self.tokens_per_minute {
+            let used: u64 = state.reservations.iter().map(|r| r.tokens as u64).sum();
+            if used + tokens as u64 > limit as u64 {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// A reservation made with `Throttle::reserve`.
+///
+/// Dropping it unsettled, e.g. when the request failed, frees its tokens;
+/// the request still counts toward `requests_per_minute`.
+#[derive(Debug)]
+pub struct ThrottlePermit {
+    throttle: Arc<Throttle>,
+    id: u64,
+    done: bool,
+}
+
+impl ThrottlePermit {
+    /// Replaces the estimate with the tokens the response reported.
+    pub fn settle(mut self, tokens: u32) {
+        self.throttle.settle(self.id, tokens);
+        self.done = true;
+    }
+
+    /// Keeps the estimate, for responses that report no usage.
+    pub fn keep(mut self) {
+        self.done = true;
+    }
+}
+
+impl Drop for ThrottlePermit {
+    fn drop(&mut self) {
+        if !self.done {
+            self.throttle.settle(self.id, 0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::v1::chat_completion::ChatCompletionStreamResponse;
+    use crate::v1::stream::{SseStream, StreamingResponse};
+    use std::collections::HashMap;
+
+    fn reserved(throttle: &Throttle) -> Vec<u32> {
+        let state = throttle.state.lock().unwrap();
+        state.reservations.iter().map(|r| r.tokens).collect()
+    }
+
+    fn stream(body: &str, permit: ThrottlePermit) -> SseStream<ChatCompletionStreamResponse> {
+        let response = StreamingResponse::from_bytes(200, HashMap::new(), body.into());
+        SseStream::new(response).with_throttle_permit(permit)
+    }
+
+    const CHUNK: &str = r#"data: {"id":"c","object":"chat.completion.chunk","created":0,"model":"m","choices":[{"index":0,"delta":{"content":"hi"},"finish_reason":null}]}"#;
+
+    #[test]
+    fn permits_settle_keep_or_free_their_tokens() {
+        let throttle = Arc::new(Throttle::new(None, Some(1000)));
+        throttle.reserve(100).settle(40);
+        throttle.reserve(100).keep();
+        drop(throttle.reserve(100));
+        assert_eq!(reserved(&throttle), vec![40, 100, 0]);
+    }
+
+    #[test]
+    fn streams_settle_from_the_usage_chunk() {
+        let throttle = Arc::new(Throttle::new(None, None));
+        let usage = r#"data: {"id":"c","object":"chat.completion.chunk","created":0,"model":"m","choices":[],"usage":{"prompt_tokens":5,"completion_tokens":7,"total_tokens":12}}"#;
+        let body = format!("{}\n\n{}\n\ndata: [DONE]\n\n", CHUNK, usage);
+        assert_eq!(stream(&body, throttle.reserve(100)).count(), 2);
+        assert_eq!(reserved(&throttle), vec![12]);
+    }
+
+    #[test]
+    fn streams_keep_the_estimate_without_usage() {
+        let throttle = Arc::new(Throttle::new(None, None));
+        let body = format!("{}\n\ndata: [DONE]\n\n", CHUNK);
+        assert_eq!(stream(&body, throttle.reserve(100)).count(), 1);
+        assert_eq!(reserved(&throttle), vec![100]);
+    }
+
+    #[test]
+    fn streams_dropped_early_free_their_tokens() {
+        let throttle = Arc::new(Throttle::new(None, None));
+        let body = format!("{}\n\n{}\n\ndata: [DONE]\n\n", CHUNK, CHUNK);
+        let mut stream = stream(&body, throttle.reserve(100));
+        stream.next().unwrap().unwrap();
+        drop(stream);
+        assert_eq!(reserved(&throttle), vec![0]);
+    }
+
+    #[test]
+    fn failed_requests_free_their_tokens() {
+        use crate::v1::chat_completion::{ChatCompletionMessage, Content, MessageRole};
+        use crate::v1::mock::{MockResponse, MockServer};
+
+        let server = MockServer::start();
+        server.mock(
+            "POST",
+            "/chat/completions",
+            MockResponse::error(400, "bad request"),
+        );
+        let throttle = Arc::new(Throttle::new(None, Some(1000)));
+        let mut client = server.client();
+        client.throttle = Some(Arc::clone(&throttle));
+        let req = ChatCompletionRequest::new(
+            "gpt-4o".to_owned(),
+            vec![ChatCompletionMessage::new(
+                MessageRole::user,
+                Content::from("hi"),
+            )],
+        )
+        .max_tokens(500);
+        assert!(client.chat_completion(req).is_err());
+        assert_eq!(reserved(&throttle), vec![0]);
+    }
+}
@@ -0,0 +1,119 @@
+use serde_json::{json, Map};
+use std::collections::{BTreeMap, HashMap};
+
+use crate::v1::chat_completion::{Function, Tool, ToolType};
+
+pub use serde_json::Value;
+
+#[cfg(feature = "derive")]
+pub use openai_api_rs_derive::ToolSchema;
+
+/// JSON schema of a type used as a tool argument.
+pub trait ToolSchema {
+    fn schema() -> Value;
+
+    /// Whether the argument may be left out; only `Option` is.
+    fn optional() -> bool {
+        false
+    }
+}
+
+/// A type whose fields are the arguments of a tool the model can call.
+pub trait ToolDefinition: ToolSchema {
+    fn name() -> &'static str;
+
+    fn description() -> Option<&'static str>;
+
+    fn function() -> Function {
+        Function {
+            name: Self::name().to_owned(),
+            description: Self::description().map(str::to_owned),
+            parameters: Self::schema(),
+            strict: None,
+        }
+    }
+
+    fn tool() -> Tool {
+        Tool {
+            r#type: ToolType::Function,
+            function: Self::function(),
+        }
+    }
+}
+
+/// A struct field, as described by `#[derive(ToolSchema)]`.
+pub struct Property {
+    pub name: &'static str,
+    pub description: Option<&'static str>,
+    pub schema: Value,
+    pub required: bool,
+}
+
+pub fn object_schema(properties: Vec<Property>) -> Value {
+    let mut props = Map::new();
+    let mut required = Vec::new();
+    for property in properties {
+        let mut schema = property.schema;
+        if let (Some(description), Some(object)) = (property.description, schema.as_object_mut()) {
+            object.insert("description".to_owned(), json!(description));
+        }
+        if property.required {
+            required.push(property.name);
+        }
+        props.insert(property.name.to_owned(), schema);
+    }
+    json!({
+        "type": "object",
+        "properties": props,
+        "required": required,
+    })
+}
+
+pub fn enum_schema(variants: &[&str]) -> Value {
+    json!({ "type": "string", "enum": variants })
+}
+
+macro_rules! impl_tool_schema {
+    ($schema_type:literal: $($ty:ty),*) => {
+        $(
+            impl ToolSchema for $ty {
+                fn schema() -> Value {
+                    json!({ "type": $schema_type })
+                }
+            }
+        )*
+    };
+}
+
+impl_tool_schema!("string": String, &str, char);
+impl_tool_schema!("boolean": bool);
+impl_tool_schema!("integer": i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+impl_tool_schema!("number": f32, f64);
+
+impl<T: ToolSchema> ToolSchema for Option<T> {
+    fn schema() -> Value {
+        T::schema()
+    }
+
+    fn optional() -> bool {
+        true
+    }
+}
+
+impl<T: ToolSchema> ToolSchema for Vec<T> {
+    fn schema() -> Value {
+        json!({ "type": "array", "items": T::schema() })
+    }
+}
+
+impl<T: ToolSchema> ToolSchema for HashMap<String, T> {
+    fn schema() -> Value {
+        json!({ "type": "object", "additionalProperties": T::schema() })
+    }
+}
+
+impl<T: ToolSchema> ToolSchema for BTreeMap<String, T> {
+    fn schema() -> Value {
+        json!({ "type": "object", "additionalProperties": T::schema() })
+    }
+}
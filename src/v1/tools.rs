@@ -0,0 +1,178 @@
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+use crate::v1::chat_completion::{
+    ChatCompletionMessage, ChatCompletionRequest, ChatCompletionResponse, Function, Tool, ToolCall,
+    ToolCallFunction, ToolType,
+};
+use crate::v1::client::OpenAIClient;
+use crate::v1::conversation::Conversation;
+use crate::v1::error::APIError;
+use crate::v1::json_repair::repair_json;
+use crate::v1::tool_schema::ToolDefinition;
+
+/// Runs a tool from the raw JSON arguments of a call. The returned text, or the
+/// error message, is sent back to the model as the tool result.
+pub type ToolHandler = Arc<dyn Fn(&str) -> Result<String, String> + Send + Sync>;
+
+const DEFAULT_MAX_ITERATIONS: usize = 10;
+
+/// Tools offered to the model, together with the handlers that execute them.
+#[derive(Clone)]
+pub struct ToolRegistry {
+    functions: Vec<Function>,
+    handlers: HashMap<String, ToolHandler>,
+    /// Upper bound on the requests `run_tools_loop` sends.
+    pub max_iterations: usize,
+    /// Pass arguments that are not valid JSON through `repair_json` before
+    /// handing them to the handler.
+    pub repair_arguments: bool,
+}
+
+impl ToolRegistry {
+    pub fn new() -> Self {
+        Self {
+            functions: Vec::new(),
+            handlers: HashMap::new(),
+            max_iterations: DEFAULT_MAX_ITERATIONS,
+            repair_arguments: false,
+        }
+    }
+
+    pub fn max_iterations(mut self, max_iterations: usize) -> Self {
+        self.max_iterations = max_iterations;
+        self
+    }
+
+    pub fn repair_arguments(mut self, repair_arguments: bool) -> Self {
+        self.repair_arguments = repair_arguments;
+        self
+    }
+
+    /// Registers `function`, replacing any tool with the same name.
+    pub fn register<F>(mut self, function: Function, handler: F) -> Self
+    where
+        F: Fn(&str) -> Result<String, String> + Send + Sync + 'static,
+    {
+        self.functions.retain(|f| f.name != function.name);
+        self.handlers
+            .insert(function.name.clone(), Arc::new(handler));
+        self.functions.push(function);
+        self
+    }
+
+    /// Registers the tool described by `T`, passing the handler its parsed arguments.
+    pub fn register_typed<T, F>(self, handler: F) -> Self
+    where
+        T: ToolDefinition + DeserializeOwned,
+        F: Fn(T) -> Result<String, String> + Send + Sync + 'static,
+    {
+        let name = T::name();
+        self.register(T::function(), move |arguments| {
+            let function = ToolCallFunction {
+                name: Some(name.to_owned()),
+                arguments: Some(arguments.to_owned()),
+            };
+            let arguments = function.parse_arguments::<T>().map_err(|e| e.to_string())?;
+            handler(arguments)
+        })
+    }
+
+    pub fn tools(&self) -> Vec<Tool> {
+        self.functions
+            .iter()
+            .map(|function| Tool {
+                r#type: ToolType::Function,
+                function: function.clone(),
+            })
+            .collect()
+    }
+
+    /// Runs one tool call. Unknown tools and handler errors become the result
+    /// text, so the model can correct itself on the next turn.
+    pub fn call(&self, tool_call: &ToolCall) -> ChatCompletionMessage {
+        let name = tool_call.function.name.as_deref().unwrap_or_default();
+        let mut arguments =
+            Cow::Borrowed(tool_call.function.arguments.as_deref().unwrap_or_default());
+        if self.repair_arguments && serde_json::from_str::<Value>(&arguments).is_err() {
+            arguments = Cow::Owned(repair_json(&arguments));
+        }
+        let output = match self.handlers.get(name) {
+            Some(handler) => handler(&arguments).unwrap_or_else(|e| format!("error: {}", e)),
+            None => format!("error: unknown tool `{}`", name),
+        };
+        ChatCompletionMessage::tool(tool_call.id.clone(), output)
+    }
+}
+
+impl Default for ToolRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for ToolRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let names: Vec<&str> = self.functions.iter().map(|f| f.name.as_str()).collect();
+        f.debug_struct("ToolRegistry")
+            .field("tools", &names)
+            .field("max_iterations", &self.max_iterations)
+            .field("repair_arguments", &self.repair_arguments)
+            .finish()
+    }
+}
+
+/// The last reply of `run_tools_loop` and the history that led to it.
+#[derive(Debug, Clone)]
+pub struct ToolsLoopOutput {
+    pub response: ChatCompletionResponse,
+    /// Every message sent and received, including the tool calls and results,
+    /// with the usage summed over all requests.
+    pub conversation: Conversation,
+}
+
+/// Sends `request`, runs the tools the model calls, appends their results and
+/// asks again until a reply comes back without tool calls.
+///
+/// The registry's tools are used when the request sets none. After
+/// `max_iterations` requests the last reply is returned even if it still calls
+/// tools.
+pub fn run_tools_loop<C: OpenAIClient + ?Sized>(
+    client: &C,
+    mut request: ChatCompletionRequest,
+    registry: &ToolRegistry,
+) -> Result<ToolsLoopOutput, APIError> {
+    if request.tools.is_none() {
+        request.tools = Some(registry.tools());
+    }
+    let mut conversation = Conversation {
+        messages: std::mem::take(&mut request.messages),
+        ..Default::default()
+    };
+    let mut iteration = 0;
+    loop {
+        iteration += 1;
+        request.messages = conversation.messages.clone();
+        let response = client.chat_completion(request.clone())?;
+        conversation.push_response(&response);
+
+        let tool_calls = response
+            .choices
+            .first()
+            .and_then(|choice| choice.message.tool_calls.clone())
+            .unwrap_or_default();
+        if tool_calls.is_empty() || iteration >= registry.max_iterations {
+            return Ok(ToolsLoopOutput {
+                response,
+                conversation,
+            });
+        }
+        for tool_call in &tool_calls {
+            conversation.push(registry.call(tool_call));
+        }
+    }
+}
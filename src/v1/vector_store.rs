@@ -0,0 +1,199 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::{impl_builder_methods, impl_response_headers};
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ExpiresAfter {
+    /// Only `last_active_at` is supported.
+    pub anchor: String,
+    pub days: i64,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct CreateVectorStoreRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_ids: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_after: Option<ExpiresAfter>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<HashMap<String, String>>,
+}
+
+impl CreateVectorStoreRequest {
+    pub fn new() -> Self {
+        Self {
+            file_ids: None,
+            name: None,
+            expires_after: None,
+            metadata: None,
+        }
+    }
+}
+
+impl Default for CreateVectorStoreRequest {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl_builder_methods!(
+    CreateVectorStoreRequest,
+    file_ids: Vec<String>,
+    name: String,
+    expires_after: ExpiresAfter,
+    metadata: HashMap<String, String>
+);
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ModifyVectorStoreRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_after: Option<ExpiresAfter>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<HashMap<String, String>>,
+}
+
+impl ModifyVectorStoreRequest {
+    pub fn new() -> Self {
+        Self {
+            name: None,
+            expires_after: None,
+            metadata: None,
+        }
+    }
+}
+
+impl Default for ModifyVectorStoreRequest {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl_builder_methods!(
+    ModifyVectorStoreRequest,
+    name: String,
+    expires_after: ExpiresAfter,
+    metadata: HashMap<String, String>
+);
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct FileCounts {
+    pub in_progress: i64,
+    pub completed: i64,
+    pub failed: i64,
+    pub cancelled: i64,
+    pub total: i64,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct VectorStoreObject {
+    pub id: String,
+    pub object: String,
+    pub created_at: i64,
+    pub name: Option<String>,
+    pub usage_bytes: i64,
+    pub file_counts: FileCounts,
+    pub status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_after: Option<ExpiresAfter>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_active_at: Option<i64>,
+    pub metadata: HashMap<String, String>,
+    #[serde(skip)]
+    pub headers: Option<HashMap<String, String>>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct ListVectorStore {
+    pub object: String,
+    pub data: Vec<VectorStoreObject>,
+    pub first_id: Option<String>,
+    pub last_id: Option<String>,
+    pub has_more: bool,
+    #[serde(skip)]
+    pub headers: Option<HashMap<String, String>>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct CreateVectorStoreFileRequest {
+    pub file_id: String,
+}
+
+impl CreateVectorStoreFileRequest {
+    pub fn new(file_id: String) -> Self {
+        Self { file_id }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct VectorStoreFileError {
+    pub code: String,
+    pub message: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct VectorStoreFileObject {
+    pub id: String,
+    pub object: String,
+    pub usage_bytes: i64,
+    pub created_at: i64,
+    pub vector_store_id: String,
+    pub status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<VectorStoreFileError>,
+    #[serde(skip)]
+    pub headers: Option<HashMap<String, String>>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct ListVectorStoreFile {
+    pub object: String,
+    pub data: Vec<VectorStoreFileObject>,
+    pub first_id: Option<String>,
+    pub last_id: Option<String>,
+    pub has_more: bool,
+    #[serde(skip)]
+    pub headers: Option<HashMap<String, String>>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct CreateVectorStoreFileBatchRequest {
+    pub file_ids: Vec<String>,
+}
+
+impl CreateVectorStoreFileBatchRequest {
+    pub fn new(file_ids: Vec<String>) -> Self {
+        Self { file_ids }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct VectorStoreFileBatchObject {
+    pub id: String,
+    pub object: String,
+    pub created_at: i64,
+    pub vector_store_id: String,
+    pub status: String,
+    pub file_counts: FileCounts,
+    #[serde(skip)]
+    pub headers: Option<HashMap<String, String>>,
+}
+
+impl_response_headers!(
+    VectorStoreObject,
+    ListVectorStore,
+    VectorStoreFileObject,
+    ListVectorStoreFile,
+    VectorStoreFileBatchObject,
+);